@@ -0,0 +1,337 @@
+use crate::commands::beta::from::avro::{read_ion, time_of_day, timestamp};
+use crate::commands::beta::from::{civil_from_days, Elements};
+use crate::commands::beta::to::arrow::{ANNOTATIONS_SUFFIX, ION_TYPE_KEY};
+use crate::commands::util::element_to_string;
+use anyhow::{bail, Context, Result};
+use arrow_array::cast::AsArray;
+use arrow_array::types::*;
+use arrow_array::{Array, RecordBatch};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType, Field, TimeUnit};
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::{Builder, IonElement, IonSequence};
+use ion_rs::{Integer, IonType, Symbol};
+use num_bigint::BigInt;
+use std::io::{Cursor, Read};
+
+/// The bytes that an Arrow IPC file starts with. The streaming format starts with a message.
+const FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// Reads the rows of an Arrow IPC file or stream as structs, one field per column.
+pub(crate) fn read_arrow(mut input: Box<dyn Read>) -> Result<Elements> {
+    let mut magic = Vec::new();
+    input
+        .by_ref()
+        .take(FILE_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let batches: Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>> = if magic == FILE_MAGIC
+    {
+        // The file format's footer, which locates its record batches, is at the end.
+        let mut bytes = magic;
+        input.read_to_end(&mut bytes)?;
+        Box::new(FileReader::try_new(Cursor::new(bytes), None)?)
+    } else {
+        Box::new(StreamReader::try_new(
+            Cursor::new(magic).chain(input),
+            None,
+        )?)
+    };
+    Ok(Box::new(batches.flat_map(|batch| {
+        match batch.map_err(Into::into).and_then(|batch| rows(&batch)) {
+            Ok(rows) => rows.into_iter().map(Ok).collect(),
+            Err(error) => vec![Err(error)],
+        }
+    })))
+}
+
+/// Converts each row of the batch to a struct. The lists of strings in a column named
+/// `$ion_annotations` become the row's annotations, and those in a column named
+/// `<name>$ion_annotations` become the annotations of the `<name>` field.
+fn rows(batch: &RecordBatch) -> Result<Vec<Element>> {
+    let schema = batch.schema();
+    let names: Vec<&str> = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    let mut row_annotations = None;
+    let mut columns = Vec::new();
+    let mut field_annotations = Vec::new();
+    for (field, array) in schema.fields().iter().zip(batch.columns()) {
+        let values = to_elements(array.as_ref(), field)?;
+        match field.name().strip_suffix(ANNOTATIONS_SUFFIX) {
+            Some("") => row_annotations = Some((field.name(), values)),
+            Some(name) if names.contains(&name) => {
+                field_annotations.push((name, field.name(), values))
+            }
+            _ => columns.push((field.name().as_str(), values)),
+        }
+    }
+
+    let mut rows = Vec::with_capacity(batch.num_rows());
+    for index in 0..batch.num_rows() {
+        let mut fields = Vec::with_capacity(columns.len());
+        for (name, values) in &columns {
+            let mut value = values[index].clone();
+            if let Some((_, column, annotations)) = field_annotations
+                .iter()
+                .find(|(annotated, _, _)| annotated == name)
+            {
+                value = value.with_annotations(annotations_of(&annotations[index], column)?);
+            }
+            fields.push((Symbol::owned(*name), value));
+        }
+        let mut row: Element = fields.into_iter().collect::<Struct>().into();
+        if let Some((column, annotations)) = &row_annotations {
+            row = row.with_annotations(annotations_of(&annotations[index], column)?);
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn annotations_of(element: &Element, column: &str) -> Result<Vec<Symbol>> {
+    if element.is_null() {
+        return Ok(Vec::new());
+    }
+    let not_text = || {
+        format!(
+            "The annotations column '{}' must hold lists of strings",
+            column
+        )
+    };
+    element
+        .as_sequence()
+        .with_context(not_text)?
+        .iter()
+        .map(|annotation| {
+            annotation
+                .as_str()
+                .map(Symbol::owned)
+                .with_context(not_text)
+        })
+        .collect()
+}
+
+/// Converts each value in the array to Ion. Strings are read as symbols or as Ion text if the
+/// field's metadata says that's what they were written from.
+fn to_elements(array: &dyn Array, field: &Field) -> Result<Vec<Element>> {
+    let ion_type = field.metadata().get(ION_TYPE_KEY).map(String::as_str);
+    let text = |text: &str| -> Result<Element> {
+        match ion_type {
+            Some("ion") => read_ion(text),
+            Some("symbol") => Ok(Value::Symbol(Symbol::owned(text)).into()),
+            _ => Ok(text.to_string().into()),
+        }
+    };
+    let elements = match array.data_type() {
+        DataType::Null => each(array, |_| Ok(IonType::Null.into()))?,
+        DataType::Boolean => each(array, |index| Ok(array.as_boolean().value(index).into()))?,
+        DataType::Int8 => integers::<Int8Type>(array)?,
+        DataType::Int16 => integers::<Int16Type>(array)?,
+        DataType::Int32 => integers::<Int32Type>(array)?,
+        DataType::Int64 => integers::<Int64Type>(array)?,
+        DataType::UInt8 => integers::<UInt8Type>(array)?,
+        DataType::UInt16 => integers::<UInt16Type>(array)?,
+        DataType::UInt32 => integers::<UInt32Type>(array)?,
+        DataType::UInt64 => each(array, |index| {
+            let value = array.as_primitive::<UInt64Type>().value(index);
+            Ok(match i64::try_from(value) {
+                Ok(value) => value.into(),
+                Err(_) => Value::Integer(Integer::BigInt(BigInt::from(value))).into(),
+            })
+        })?,
+        DataType::Float16 => each(array, |index| {
+            Ok(array
+                .as_primitive::<Float16Type>()
+                .value(index)
+                .to_f64()
+                .into())
+        })?,
+        DataType::Float32 => each(array, |index| {
+            Ok(f64::from(array.as_primitive::<Float32Type>().value(index)).into())
+        })?,
+        DataType::Float64 => each(array, |index| {
+            Ok(array.as_primitive::<Float64Type>().value(index).into())
+        })?,
+        DataType::Decimal128(_, scale) => each(array, |index| {
+            let unscaled = array.as_primitive::<Decimal128Type>().value(index);
+            read_ion(&format!("{}d{}", unscaled, -i64::from(*scale)))
+        })?,
+        DataType::Decimal256(_, scale) => each(array, |index| {
+            let unscaled = array.as_primitive::<Decimal256Type>().value(index);
+            read_ion(&format!("{}d{}", unscaled, -i64::from(*scale)))
+        })?,
+        DataType::Utf8 => each(array, |index| text(array.as_string::<i32>().value(index)))?,
+        DataType::LargeUtf8 => each(array, |index| text(array.as_string::<i64>().value(index)))?,
+        DataType::Utf8View => each(array, |index| text(array.as_string_view().value(index)))?,
+        DataType::Binary => each(array, |index| {
+            Ok(Value::Blob(array.as_binary::<i32>().value(index).to_vec()).into())
+        })?,
+        DataType::LargeBinary => each(array, |index| {
+            Ok(Value::Blob(array.as_binary::<i64>().value(index).to_vec()).into())
+        })?,
+        DataType::BinaryView => each(array, |index| {
+            Ok(Value::Blob(array.as_binary_view().value(index).to_vec()).into())
+        })?,
+        DataType::FixedSizeBinary(_) => each(array, |index| {
+            Ok(Value::Blob(array.as_fixed_size_binary().value(index).to_vec()).into())
+        })?,
+        // Arrow timestamps count from the epoch in UTC; one without a time zone is a local
+        // date and time, which Ion writes with an unknown offset.
+        DataType::Timestamp(unit, zone) => {
+            let offset = if zone.is_some() { "Z" } else { "-00:00" };
+            let values = match unit {
+                TimeUnit::Second => array.as_primitive::<TimestampSecondType>().values(),
+                TimeUnit::Millisecond => array.as_primitive::<TimestampMillisecondType>().values(),
+                TimeUnit::Microsecond => array.as_primitive::<TimestampMicrosecondType>().values(),
+                TimeUnit::Nanosecond => array.as_primitive::<TimestampNanosecondType>().values(),
+            };
+            each(array, |index| {
+                timestamp(values[index], units_per_second(unit), offset)
+            })?
+        }
+        DataType::Date32 => each(array, |index| {
+            date(i64::from(array.as_primitive::<Date32Type>().value(index)))
+        })?,
+        DataType::Date64 => each(array, |index| {
+            let millis = array.as_primitive::<Date64Type>().value(index);
+            date(millis.div_euclid(86_400_000))
+        })?,
+        DataType::Time32(unit) => each(array, |index| {
+            let value = match unit {
+                TimeUnit::Second => array.as_primitive::<Time32SecondType>().value(index),
+                _ => array.as_primitive::<Time32MillisecondType>().value(index),
+            };
+            Ok(time_of_day(i64::from(value), units_per_second(unit)).into())
+        })?,
+        DataType::Time64(unit) => each(array, |index| {
+            let value = match unit {
+                TimeUnit::Microsecond => array.as_primitive::<Time64MicrosecondType>().value(index),
+                _ => array.as_primitive::<Time64NanosecondType>().value(index),
+            };
+            Ok(time_of_day(value, units_per_second(unit)).into())
+        })?,
+        DataType::List(item) => {
+            let list = array.as_list::<i32>();
+            let items = to_elements(list.values().as_ref(), item)?;
+            each(array, |index| {
+                let offsets = &list.value_offsets()[index..=index + 1];
+                Ok(Element::new_list(
+                    items[offsets[0] as usize..offsets[1] as usize]
+                        .iter()
+                        .cloned(),
+                ))
+            })?
+        }
+        DataType::LargeList(item) => {
+            let list = array.as_list::<i64>();
+            let items = to_elements(list.values().as_ref(), item)?;
+            each(array, |index| {
+                let offsets = &list.value_offsets()[index..=index + 1];
+                Ok(Element::new_list(
+                    items[offsets[0] as usize..offsets[1] as usize]
+                        .iter()
+                        .cloned(),
+                ))
+            })?
+        }
+        DataType::FixedSizeList(item, length) => {
+            let list = array.as_fixed_size_list();
+            let items = to_elements(list.values().as_ref(), item)?;
+            each(array, |index| {
+                let start = list.value_offset(index) as usize;
+                Ok(Element::new_list(
+                    items[start..start + *length as usize].iter().cloned(),
+                ))
+            })?
+        }
+        DataType::Struct(fields) => {
+            let children = fields
+                .iter()
+                .zip(array.as_struct().columns())
+                .map(|(field, child)| to_elements(child.as_ref(), field))
+                .collect::<Result<Vec<_>>>()?;
+            each(array, |index| {
+                Ok(fields
+                    .iter()
+                    .zip(&children)
+                    .map(|(field, values)| (Symbol::owned(field.name()), values[index].clone()))
+                    .collect::<Struct>()
+                    .into())
+            })?
+        }
+        // Map entries become struct fields, with keys that aren't text written as Ion text.
+        DataType::Map(entries, _) => {
+            let map = array.as_map();
+            let (key_field, value_field) = match entries.data_type() {
+                DataType::Struct(fields) if fields.len() == 2 => (&fields[0], &fields[1]),
+                data_type => bail!(
+                    "Arrow map entries must be key-value structs, not {}",
+                    data_type
+                ),
+            };
+            let keys = to_elements(map.keys().as_ref(), key_field)?;
+            let values = to_elements(map.values().as_ref(), value_field)?;
+            each(array, |index| {
+                let offsets = &map.value_offsets()[index..=index + 1];
+                (offsets[0] as usize..offsets[1] as usize)
+                    .map(|entry| {
+                        let key = match keys[entry].as_str() {
+                            Some(key) => key.to_string(),
+                            None => element_to_string(&keys[entry])?,
+                        };
+                        Ok((Symbol::owned(key), values[entry].clone()))
+                    })
+                    .collect::<Result<Vec<(Symbol, Element)>>>()
+                    .map(|fields| fields.into_iter().collect::<Struct>().into())
+            })?
+        }
+        DataType::Dictionary(_, _) => {
+            let dictionary = array.as_any_dictionary();
+            let values = to_elements(dictionary.values().as_ref(), field)?;
+            let keys = dictionary.normalized_keys();
+            each(array, |index| Ok(values[keys[index]].clone()))?
+        }
+        data_type => bail!(
+            "Cannot convert the column '{}' from Arrow: its type {} is not supported",
+            field.name(),
+            data_type
+        ),
+    };
+    Ok(elements)
+}
+
+/// Converts each value in the array with `convert`, except for nulls.
+fn each(array: &dyn Array, convert: impl Fn(usize) -> Result<Element>) -> Result<Vec<Element>> {
+    (0..array.len())
+        .map(|index| match array.is_null(index) {
+            true => Ok(IonType::Null.into()),
+            false => convert(index),
+        })
+        .collect()
+}
+
+fn integers<T>(array: &dyn Array) -> Result<Vec<Element>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i64>,
+{
+    each(array, |index| {
+        Ok(array.as_primitive::<T>().value(index).into().into())
+    })
+}
+
+fn date(days: i64) -> Result<Element> {
+    let (year, month, day) = civil_from_days(days);
+    read_ion(&format!("{:04}-{:02}-{:02}T", year, month, day))
+}
+
+fn units_per_second(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    }
+}
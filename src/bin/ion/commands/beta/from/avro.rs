@@ -0,0 +1,186 @@
+use crate::commands::beta::from::{civil_from_days, Elements};
+use crate::commands::beta::to::avro::AvroNames;
+use anyhow::{Context, Result};
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{Reader, Schema};
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::Builder;
+use ion_rs::{IonType, Symbol};
+use num_bigint::BigInt;
+use std::io::Read;
+
+/// Reads the records of an Avro object container file, converting each one according to the
+/// schema stored in the file.
+pub(crate) fn read_avro(input: Box<dyn Read>) -> Result<Elements> {
+    let mut reader = Reader::new(input)?;
+    let schema = reader.writer_schema().clone();
+    let names = AvroNames::new(&schema)?;
+    Ok(Box::new(std::iter::from_fn(move || {
+        let value = reader.next()?;
+        Some(
+            value
+                .map_err(Into::into)
+                .and_then(|value| to_element(value, &schema, &names)),
+        )
+    })))
+}
+
+/// Converts an Avro value to Ion. The schema is needed for the scale of decimals and to follow
+/// records, unions, arrays, and maps down to them.
+fn to_element(value: AvroValue, schema: &Schema, names: &AvroNames) -> Result<Element> {
+    let schema = names.resolve(schema)?;
+    let element = match value {
+        AvroValue::Null => Element::from(IonType::Null),
+        AvroValue::Boolean(value) => value.into(),
+        AvroValue::Int(value) => i64::from(value).into(),
+        AvroValue::Long(value) => value.into(),
+        AvroValue::Float(value) => f64::from(value).into(),
+        AvroValue::Double(value) => value.into(),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Value::Blob(bytes).into(),
+        AvroValue::String(text) => text.into(),
+        AvroValue::Uuid(uuid) => uuid.to_string().into(),
+        AvroValue::Enum(_, symbol) => Value::Symbol(Symbol::owned(symbol)).into(),
+        AvroValue::Union(index, value) => {
+            let variant = match schema {
+                Schema::Union(union) => union.variants().get(index as usize),
+                _ => None,
+            };
+            let variant = variant.with_context(|| "Avro union value doesn't match its schema")?;
+            to_element(*value, variant, names)?
+        }
+        AvroValue::Array(values) => {
+            let items = match schema {
+                Schema::Array(items) => items.as_ref(),
+                _ => schema,
+            };
+            Element::new_list(
+                values
+                    .into_iter()
+                    .map(|value| to_element(value, items, names))
+                    .collect::<Result<Vec<Element>>>()?,
+            )
+        }
+        AvroValue::Map(entries) => {
+            let values_schema = match schema {
+                Schema::Map(values) => values.as_ref(),
+                _ => schema,
+            };
+            // Avro maps are unordered; sort the entries so the output is stable.
+            let mut entries: Vec<(String, AvroValue)> = entries.into_iter().collect();
+            entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+            entries
+                .into_iter()
+                .map(|(name, value)| {
+                    Ok((
+                        Symbol::owned(name),
+                        to_element(value, values_schema, names)?,
+                    ))
+                })
+                .collect::<Result<Vec<(Symbol, Element)>>>()?
+                .into_iter()
+                .collect::<Struct>()
+                .into()
+        }
+        AvroValue::Record(fields) => {
+            let field_schemas = match schema {
+                Schema::Record(record) => record.fields.iter().map(|field| &field.schema).collect(),
+                _ => vec![],
+            };
+            fields
+                .into_iter()
+                .enumerate()
+                .map(|(index, (name, value))| {
+                    let field_schema = field_schemas.get(index).copied().unwrap_or(schema);
+                    Ok((Symbol::owned(name), to_element(value, field_schema, names)?))
+                })
+                .collect::<Result<Vec<(Symbol, Element)>>>()?
+                .into_iter()
+                .collect::<Struct>()
+                .into()
+        }
+        AvroValue::Decimal(decimal) => {
+            let scale = match schema {
+                Schema::Decimal(decimal) => decimal.scale,
+                _ => 0,
+            };
+            let bytes: Vec<u8> = Vec::try_from(&decimal)?;
+            let unscaled = BigInt::from_signed_bytes_be(&bytes);
+            read_ion(&format!("{}d-{}", unscaled, scale))?
+        }
+        AvroValue::Date(days) => {
+            let (year, month, day) = civil_from_days(i64::from(days));
+            read_ion(&format!("{:04}-{:02}-{:02}T", year, month, day))?
+        }
+        AvroValue::TimeMillis(millis) => time_of_day(i64::from(millis), 1_000).into(),
+        AvroValue::TimeMicros(micros) => time_of_day(micros, 1_000_000).into(),
+        AvroValue::TimestampMillis(millis) => timestamp(millis, 1_000, "Z")?,
+        AvroValue::TimestampMicros(micros) => timestamp(micros, 1_000_000, "Z")?,
+        // Local timestamps have no time zone, which Ion writes as an unknown offset.
+        AvroValue::LocalTimestampMillis(millis) => timestamp(millis, 1_000, "-00:00")?,
+        AvroValue::LocalTimestampMicros(micros) => timestamp(micros, 1_000_000, "-00:00")?,
+        AvroValue::Duration(duration) => vec![
+            ("months", i64::from(u32::from(duration.months()))),
+            ("days", i64::from(u32::from(duration.days()))),
+            ("milliseconds", i64::from(u32::from(duration.millis()))),
+        ]
+        .into_iter()
+        .collect::<Struct>()
+        .into(),
+    };
+    Ok(element)
+}
+
+pub(crate) fn read_ion(text: &str) -> Result<Element> {
+    element_reader()
+        .read_one(text.as_bytes())
+        .with_context(|| format!("'{}' is not valid Ion", text))
+}
+
+/// Returns a timestamp `value` units after 1970-01-01T00:00:00Z, with as many fractional digits as
+/// there are in a unit.
+pub(crate) fn timestamp(value: i64, units_per_second: i64, offset: &str) -> Result<Element> {
+    let (seconds, fraction) = (
+        value.div_euclid(units_per_second),
+        value.rem_euclid(units_per_second),
+    );
+    let (days, second_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    let digits = units_per_second.to_string().len() - 1;
+    let fraction = match digits {
+        0 => String::new(),
+        _ => format!(".{:0width$}", fraction, width = digits),
+    };
+    read_ion(&format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{}",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day % 3600 / 60,
+        second_of_day % 60,
+        fraction,
+        offset
+    ))
+}
+
+/// Writes a number of `units_per_second` units since midnight as a time of day like
+/// `07:32:00.250`, with as many fractional digits as there are in a unit.
+pub(crate) fn time_of_day(value: i64, units_per_second: i64) -> String {
+    let seconds = value / units_per_second;
+    let digits = units_per_second.to_string().len() - 1;
+    let mut text = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    if digits > 0 {
+        text.push_str(&format!(
+            ".{:0width$}",
+            value % units_per_second,
+            width = digits
+        ));
+    }
+    text
+}
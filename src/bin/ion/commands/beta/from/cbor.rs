@@ -0,0 +1,148 @@
+use crate::commands::beta::from::{epoch_timestamp, Elements};
+use crate::commands::beta::to::cbor::{
+    ANNOTATIONS_TAG, DATE_TIME_STRING_TAG, DECIMAL_FRACTION_TAG, EPOCH_DATE_TIME_TAG,
+    FULL_DATE_STRING_TAG, NEGATIVE_BIGNUM_TAG, POSITIVE_BIGNUM_TAG,
+};
+use anyhow::{bail, Context, Result};
+use ciborium::value::Value as CborValue;
+use ion_rs::types::timestamp::Precision;
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::{Builder, IonElement};
+use ion_rs::{IonType, Symbol};
+use num_bigint::{BigInt, BigUint};
+use std::io::{BufRead, BufReader, Read};
+
+/// Reads a CBOR sequence (RFC 8742), converting each data item into a top-level Ion value.
+pub(crate) fn read_cbor(input: Box<dyn Read>) -> Result<Elements> {
+    let mut reader = BufReader::new(input);
+    let mut elements = Vec::new();
+    while !reader.fill_buf()?.is_empty() {
+        let value: CborValue =
+            ciborium::de::from_reader(&mut reader).with_context(|| "Could not read CBOR")?;
+        elements.push(to_element(value));
+    }
+    Ok(Box::new(elements.into_iter()))
+}
+
+/// Converts a CBOR data item to Ion, reversing the mapping used by `beta to cbor`. The tags for
+/// date/times, bignums, decimal fractions, and annotations are converted to the equivalent Ion
+/// values; any other tag is ignored and its content converted on its own.
+fn to_element(value: CborValue) -> Result<Element> {
+    let element = match value {
+        CborValue::Null => Element::from(IonType::Null),
+        CborValue::Bool(value) => Element::from(value),
+        CborValue::Integer(integer) => integer_element(BigInt::from(i128::from(integer))),
+        CborValue::Float(value) => Element::from(value),
+        CborValue::Text(text) => Element::from(text),
+        CborValue::Bytes(bytes) => Element::from(Value::Blob(bytes)),
+        CborValue::Array(values) => Element::new_list(
+            values
+                .into_iter()
+                .map(to_element)
+                .collect::<Result<Vec<Element>>>()?,
+        ),
+        CborValue::Map(entries) => {
+            let mut fields = Vec::with_capacity(entries.len());
+            for (key, value) in entries {
+                let name = match key {
+                    CborValue::Text(text) => text,
+                    CborValue::Integer(integer) => i128::from(integer).to_string(),
+                    key => bail!(
+                        "Only text and integer map keys can be field names; found {:?}",
+                        key
+                    ),
+                };
+                fields.push((Symbol::owned(name), to_element(value)?));
+            }
+            fields.into_iter().collect::<Struct>().into()
+        }
+        CborValue::Tag(tag, content) => tagged_element(tag, *content)?,
+        value => bail!("Unsupported CBOR value: {:?}", value),
+    };
+    Ok(element)
+}
+
+fn tagged_element(tag: u64, content: CborValue) -> Result<Element> {
+    let element = match (tag, content) {
+        (DATE_TIME_STRING_TAG, CborValue::Text(text)) => {
+            // RFC 3339 allows a lowercase 't' and 'z', but Ion does not.
+            timestamp_element(&text.to_uppercase(), Precision::Second)
+                .with_context(|| format!("'{}' is not a valid date/time string", text))?
+        }
+        (FULL_DATE_STRING_TAG, CborValue::Text(text)) => {
+            timestamp_element(&text, Precision::Day)
+                .with_context(|| format!("'{}' is not a valid date string", text))?
+        }
+        (EPOCH_DATE_TIME_TAG, CborValue::Integer(seconds)) => {
+            let seconds =
+                i64::try_from(seconds).with_context(|| "The epoch date/time is out of range")?;
+            epoch_timestamp(seconds, 0)?
+        }
+        (EPOCH_DATE_TIME_TAG, CborValue::Float(seconds)) if seconds.is_finite() => {
+            let nanoseconds = ((seconds - seconds.floor()) * 1e9).round() as u32;
+            epoch_timestamp(seconds.floor() as i64, nanoseconds)?
+        }
+        (POSITIVE_BIGNUM_TAG, CborValue::Bytes(bytes)) => {
+            integer_element(BigInt::from(BigUint::from_bytes_be(&bytes)))
+        }
+        (NEGATIVE_BIGNUM_TAG, CborValue::Bytes(bytes)) => {
+            integer_element(-1 - BigInt::from(BigUint::from_bytes_be(&bytes)))
+        }
+        (DECIMAL_FRACTION_TAG, CborValue::Array(parts)) if parts.len() == 2 => {
+            let mut parts = parts.into_iter();
+            let exponent = to_element(parts.next().unwrap())?;
+            let mantissa = to_element(parts.next().unwrap())?;
+            match (exponent.as_integer(), mantissa.as_integer()) {
+                (Some(exponent), Some(mantissa)) => {
+                    element_reader().read_one(format!("{}d{}", mantissa, exponent).as_bytes())?
+                }
+                _ => bail!("A decimal fraction must contain two integers"),
+            }
+        }
+        (ANNOTATIONS_TAG, CborValue::Array(parts)) if parts.len() == 2 => {
+            let mut parts = parts.into_iter();
+            let annotations = match parts.next().unwrap() {
+                CborValue::Array(annotations) => annotations
+                    .into_iter()
+                    .map(|annotation| match annotation {
+                        CborValue::Text(text) => Ok(Symbol::owned(text)),
+                        annotation => bail!("Annotations must be text; found {:?}", annotation),
+                    })
+                    .collect::<Result<Vec<Symbol>>>()?,
+                _ => bail!("Annotations must be an array of text"),
+            };
+            to_element(parts.next().unwrap())?.with_annotations(annotations)
+        }
+        (tag, content) => match tag {
+            DATE_TIME_STRING_TAG | EPOCH_DATE_TIME_TAG | FULL_DATE_STRING_TAG
+            | POSITIVE_BIGNUM_TAG | NEGATIVE_BIGNUM_TAG | DECIMAL_FRACTION_TAG
+            | ANNOTATIONS_TAG => {
+                bail!("Tag {} cannot contain {:?}", tag, content)
+            }
+            _ => to_element(content)?,
+        },
+    };
+    Ok(element)
+}
+
+/// Reads `text` as an Ion timestamp, which must have the given precision. This rejects other Ion
+/// values, like `"1"` in a date/time string, and precisions the tag doesn't allow, like `2020T`.
+fn timestamp_element(text: &str, precision: Precision) -> Result<Element> {
+    let element = element_reader().read_one(text.as_bytes())?;
+    match element.as_timestamp() {
+        Some(timestamp) if timestamp.precision() == precision => Ok(element),
+        Some(timestamp) => bail!(
+            "found a timestamp with {:?} precision",
+            timestamp.precision()
+        ),
+        None => bail!("found {}", element.ion_type()),
+    }
+}
+
+fn integer_element(value: BigInt) -> Element {
+    match i64::try_from(&value) {
+        Ok(value) => Element::from(value),
+        Err(_) => Element::from(value),
+    }
+}
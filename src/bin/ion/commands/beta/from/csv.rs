@@ -0,0 +1,76 @@
+use crate::commands::beta::from::Elements;
+use anyhow::{bail, Result};
+use clap::{Arg, ArgAction, ArgMatches};
+use ion_rs::value::owned::{Element, Struct};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::IonElement;
+use ion_rs::IonType;
+use std::io::Read;
+
+// Format-specific options for the `from` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![Arg::new("infer-types")
+        .long("infer-types")
+        .action(ArgAction::SetTrue)
+        .help_heading("CSV and TSV options")
+        .help(
+            "Convert cells that look like ints, decimals, timestamps, or booleans to those \
+             types and empty cells to null [default: every cell is a string]",
+        )]
+}
+
+/// Reads a header row followed by data rows, converting each data row into a struct whose field
+/// names are taken from the header. Trailing cells that are missing from a row are left out of
+/// its struct.
+pub(crate) fn read_csv(
+    input: Box<dyn Read>,
+    delimiter: u8,
+    matches: &ArgMatches,
+) -> Result<Elements> {
+    let infer_types = matches.get_flag("infer-types");
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(input);
+    let header: Vec<String> = reader.headers()?.iter().map(String::from).collect();
+    let rows = reader.into_records().map(move |record| {
+        let record = record?;
+        if record.len() > header.len() {
+            let line = record.position().map_or(0, |position| position.line());
+            bail!(
+                "Row on line {} has {} cells but the header only has {}",
+                line,
+                record.len(),
+                header.len()
+            );
+        }
+        let fields = header.iter().zip(record.iter()).map(|(name, cell)| {
+            let value = if infer_types {
+                infer_value(cell)
+            } else {
+                Element::from(cell.to_string())
+            };
+            (name.as_str(), value)
+        });
+        Ok(fields.collect::<Struct>().into())
+    });
+    Ok(Box::new(rows))
+}
+
+/// Converts a cell that holds the Ion text of an int, decimal, timestamp, or bool to that type.
+/// Empty cells become null and everything else is kept as a string.
+fn infer_value(cell: &str) -> Element {
+    if cell.is_empty() {
+        return Element::from(IonType::Null);
+    }
+    if let Ok(element) = element_reader().read_one(cell.as_bytes()) {
+        let inferred = matches!(
+            element.ion_type(),
+            IonType::Integer | IonType::Decimal | IonType::Timestamp | IonType::Boolean
+        );
+        if inferred && !element.is_null() && element.annotations().next().is_none() {
+            return element;
+        }
+    }
+    Element::from(cell.to_string())
+}
@@ -0,0 +1,405 @@
+use crate::commands::beta::from::Elements;
+use crate::commands::beta::to::json::{
+    ANNOTATIONS_KEY, BLOB_KEY, CLOB_KEY, DECIMAL_KEY, FLOAT_KEY, KEY_PREFIX, NULL_KEY, SEXP_KEY,
+    STRUCT_KEY, SYMBOL_KEY, TIMESTAMP_KEY, VALUE_KEY,
+};
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches};
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::{Builder, IonElement, IonSequence, IonStruct};
+use ion_rs::{Integer, IonType, Symbol};
+use num_bigint::BigInt;
+use regex::Regex;
+use serde_json::{Map, Number, Value as JsonValue};
+use std::io::{BufReader, Read};
+use std::str::FromStr;
+
+const HEADING: &str = "JSON options";
+
+// Format-specific options for the `from` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("preserve-types")
+            .long("preserve-types")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help(
+                "Rebuild the Ion values that `beta to json --preserve-types` wrote as '$ion_' \
+                 objects",
+            ),
+        Arg::new("timestamps")
+            .long("timestamps")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Convert strings in the ISO 8601 date or date-time format to timestamps"),
+        Arg::new("decimals")
+            .long("decimals")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Convert numbers with an exponent, like 1.5e3, to decimals instead of floats"),
+        Arg::new("symbol-fields")
+            .long("symbol-fields")
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help_heading(HEADING)
+            .help("Comma-separated field names whose string values are converted to symbols"),
+        Arg::new("blob-fields")
+            .long("blob-fields")
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help_heading(HEADING)
+            .help(
+                "Comma-separated field names whose string values are converted to blobs if \
+                 they are base64 text",
+            ),
+        Arg::new("rules").long("rules").help_heading(HEADING).help(
+            "An Ion file holding a struct of conversion rules, like \
+                 {timestamps: true, symbol_fields: [status]}, which are combined with the flags",
+        ),
+    ]
+}
+
+/// Opt-in rules for converting JSON values to the Ion types that JSON lacks.
+#[derive(Clone, Default)]
+pub(crate) struct UpConverter {
+    timestamps: bool,
+    decimals: bool,
+    symbol_fields: Vec<String>,
+    blob_fields: Vec<String>,
+    timestamp: Option<Regex>,
+    base64: Option<Regex>,
+}
+
+impl UpConverter {
+    /// Builds the rules from the `--rules` file, if any, and the flags.
+    pub(crate) fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        let mut converter = UpConverter::default();
+        if let Some(rules_file) = matches.get_one::<String>("rules") {
+            let rules = std::fs::read(rules_file)
+                .with_context(|| format!("Could not open file '{}'", rules_file))?;
+            let rules = element_reader()
+                .read_one(&rules)
+                .with_context(|| format!("Could not read rules file '{}'", rules_file))?;
+            converter
+                .add_rules(&rules)
+                .with_context(|| format!("Invalid rules file '{}'", rules_file))?;
+        }
+        converter.timestamps |= matches.get_flag("timestamps");
+        converter.decimals |= matches.get_flag("decimals");
+        for (flag, fields) in [
+            ("symbol-fields", &mut converter.symbol_fields),
+            ("blob-fields", &mut converter.blob_fields),
+        ] {
+            if let Some(names) = matches.get_many::<String>(flag) {
+                fields.extend(names.cloned());
+            }
+        }
+        if converter.timestamps {
+            converter.timestamp = Some(
+                Regex::new(concat!(
+                    r"^(?P<date>[0-9]{4}-[0-9]{2}-[0-9]{2})",
+                    r"(?:[Tt ](?P<time>[0-9]{2}:[0-9]{2}(?::[0-9]{2}(?:\.[0-9]+)?)?)",
+                    r"(?P<offset>[Zz]|[-+][0-9]{2}:?[0-9]{2})?)?$"
+                ))
+                .unwrap(),
+            );
+        }
+        if !converter.blob_fields.is_empty() {
+            converter.base64 = Some(
+                Regex::new(r"^(?:[A-Za-z0-9+/]{4})+(?:[A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$")
+                    .unwrap(),
+            );
+        }
+        Ok(converter)
+    }
+
+    fn add_rules(&mut self, rules: &Element) -> Result<()> {
+        let rules = match rules.as_struct() {
+            Some(rules) => rules,
+            None => bail!("the rules must be a struct"),
+        };
+        for (name, value) in rules.iter() {
+            match name.text().unwrap_or_default() {
+                "timestamps" => self.timestamps = rule_flag(name, value)?,
+                "decimals" => self.decimals = rule_flag(name, value)?,
+                "symbol_fields" => self.symbol_fields.extend(rule_names(name, value)?),
+                "blob_fields" => self.blob_fields.extend(rule_names(name, value)?),
+                other => bail!(
+                    "unknown rule '{}'; expected timestamps, decimals, symbol_fields, or \
+                     blob_fields",
+                    other
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.timestamps
+            && !self.decimals
+            && self.symbol_fields.is_empty()
+            && self.blob_fields.is_empty()
+    }
+
+    /// Applies the rules to a value and everything nested in it. `field` is the name of the
+    /// field holding the value; the values in a list or s-expression share the list's name.
+    pub(crate) fn convert(&self, element: Element, field: Option<&str>) -> Result<Element> {
+        if element.is_null() {
+            return Ok(element);
+        }
+        let annotations: Vec<Symbol> = element.annotations().cloned().collect();
+        let is_field =
+            |fields: &[String]| field.is_some_and(|field| fields.iter().any(|f| f == field));
+        let converted = match element.ion_type() {
+            IonType::String if is_field(&self.symbol_fields) => {
+                Value::Symbol(Symbol::owned(element.as_str().unwrap())).into()
+            }
+            IonType::String
+                if is_field(&self.blob_fields)
+                    && self
+                        .base64
+                        .as_ref()
+                        .unwrap()
+                        .is_match(element.as_str().unwrap()) =>
+            {
+                element_reader()
+                    .read_one(format!("{{{{{}}}}}", element.as_str().unwrap()).as_bytes())?
+            }
+            IonType::String if self.timestamps => {
+                match self.timestamp_text(element.as_str().unwrap()) {
+                    // Strings that look like timestamps but aren't valid dates are left alone.
+                    Some(text) => match element_reader().read_one(text.as_bytes()) {
+                        Ok(timestamp) => timestamp,
+                        Err(_) => return Ok(element),
+                    },
+                    None => return Ok(element),
+                }
+            }
+            IonType::Float if self.decimals => {
+                // Rust writes the shortest text that reads back as the same float.
+                let text = format!("{:e}", element.as_f64().unwrap()).replace('e', "d");
+                element_reader().read_one(text.as_bytes())?
+            }
+            IonType::List | IonType::SExpression => {
+                let values = element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .map(|value| self.convert(value.clone(), field))
+                    .collect::<Result<Vec<Element>>>()?;
+                if element.ion_type() == IonType::List {
+                    Element::new_list(values)
+                } else {
+                    Element::new_sexp(values)
+                }
+            }
+            IonType::Struct => element
+                .as_struct()
+                .unwrap()
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), self.convert(value.clone(), name.text())?)))
+                .collect::<Result<Vec<(Symbol, Element)>>>()?
+                .into_iter()
+                .collect::<Struct>()
+                .into(),
+            _ => return Ok(element),
+        };
+        Ok(converted.with_annotations(annotations))
+    }
+
+    /// Returns the Ion text for an ISO 8601 timestamp. Date-times without an offset have an
+    /// unknown offset.
+    fn timestamp_text(&self, text: &str) -> Option<String> {
+        let captures = self.timestamp.as_ref()?.captures(text)?;
+        let date = &captures["date"];
+        let time = match captures.name("time") {
+            Some(time) => time.as_str(),
+            None => return Some(format!("{}T", date)),
+        };
+        let offset = match captures.name("offset").map(|offset| offset.as_str()) {
+            None => "-00:00".to_string(),
+            Some("Z" | "z") => "Z".to_string(),
+            Some(offset) if offset.contains(':') => offset.to_string(),
+            Some(offset) => format!("{}:{}", &offset[..3], &offset[3..]),
+        };
+        Some(format!("{}T{}{}", date, time, offset))
+    }
+}
+
+fn rule_flag(name: &Symbol, value: &Element) -> Result<bool> {
+    match value.as_bool() {
+        Some(flag) => Ok(flag),
+        None => bail!("'{}' must be a bool", name.text().unwrap_or_default()),
+    }
+}
+
+fn rule_names(name: &Symbol, value: &Element) -> Result<Vec<String>> {
+    let names = value.as_sequence().and_then(|names| {
+        names
+            .iter()
+            .map(|name| name.as_str().map(|name| name.to_string()))
+            .collect::<Option<Vec<String>>>()
+    });
+    match names {
+        Some(names) => Ok(names),
+        None => bail!(
+            "'{}' must be a list of field names",
+            name.text().unwrap_or_default()
+        ),
+    }
+}
+
+/// Reads a stream of JSON values, which is also valid Ion, and applies the up-conversion rules.
+pub(crate) fn read_json(mut input: Box<dyn Read>, converter: UpConverter) -> Result<Elements> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let elements = element_reader().read_all(&bytes)?;
+    Ok(Box::new(
+        elements
+            .into_iter()
+            .map(move |element| converter.convert(element, None)),
+    ))
+}
+
+/// Reads a stream of JSON values written by `beta to json --preserve-types`, turning each
+/// `$ion_` object back into the Ion value it stands for, then applies the up-conversion rules.
+pub(crate) fn read_typed_json(input: Box<dyn Read>, converter: UpConverter) -> Result<Elements> {
+    let values = serde_json::Deserializer::from_reader(BufReader::new(input)).into_iter();
+    Ok(Box::new(values.map(move |value| {
+        converter.convert(from_typed_json_value(value?)?, None)
+    })))
+}
+
+fn from_typed_json_value(value: JsonValue) -> Result<Element> {
+    let element = match value {
+        JsonValue::Null => Value::Null(IonType::Null).into(),
+        JsonValue::Bool(value) => value.into(),
+        JsonValue::Number(number) => number_element(&number)?,
+        JsonValue::String(text) => text.into(),
+        JsonValue::Array(values) => Element::new_list(from_typed_json_values(values)?),
+        JsonValue::Object(object) if object.keys().any(|key| key.starts_with(KEY_PREFIX)) => {
+            from_envelope(object)?
+        }
+        JsonValue::Object(object) => object
+            .into_iter()
+            .map(|(name, value)| Ok((Symbol::owned(name), from_typed_json_value(value)?)))
+            .collect::<Result<Vec<(Symbol, Element)>>>()?
+            .into_iter()
+            .collect::<Struct>()
+            .into(),
+    };
+    Ok(element)
+}
+
+fn from_typed_json_values(values: Vec<JsonValue>) -> Result<Vec<Element>> {
+    values.into_iter().map(from_typed_json_value).collect()
+}
+
+/// JSON numbers with a fraction or an exponent are floats; other numbers are ints of any size.
+fn number_element(number: &Number) -> Result<Element> {
+    let text = number.to_string();
+    if text.contains(['.', 'e', 'E']) {
+        return Ok(f64::from_str(&text)?.into());
+    }
+    let element = match number.as_i64() {
+        Some(value) => value.into(),
+        None => Value::Integer(Integer::BigInt(BigInt::from_str(&text)?)).into(),
+    };
+    Ok(element)
+}
+
+fn from_envelope(mut object: Map<String, JsonValue>) -> Result<Element> {
+    if object.len() == 2 && object.contains_key(ANNOTATIONS_KEY) {
+        let annotations = match object.remove(ANNOTATIONS_KEY) {
+            Some(JsonValue::Array(annotations)) => annotations
+                .into_iter()
+                .map(|annotation| match annotation {
+                    JsonValue::String(text) => Ok(Symbol::owned(text)),
+                    other => bail!(
+                        "'{}' annotations must be strings; found {}",
+                        ANNOTATIONS_KEY,
+                        other
+                    ),
+                })
+                .collect::<Result<Vec<Symbol>>>()?,
+            other => bail!("'{}' must be a list; found {:?}", ANNOTATIONS_KEY, other),
+        };
+        let value = match object.remove(VALUE_KEY) {
+            Some(value) => from_typed_json_value(value)?,
+            None => bail!(
+                "'{}' must be accompanied by '{}'",
+                ANNOTATIONS_KEY,
+                VALUE_KEY
+            ),
+        };
+        return Ok(value.with_annotations(annotations));
+    }
+    if object.len() != 1 {
+        bail!(
+            "Objects with '{}' keys must have exactly one key; found {}",
+            KEY_PREFIX,
+            JsonValue::Object(object)
+        );
+    }
+    let (key, value) = object.into_iter().next().unwrap();
+    let element = match (key.as_str(), value) {
+        (NULL_KEY, JsonValue::String(name)) => read_as(&format!("null.{}", name), None, &key)?,
+        (FLOAT_KEY, JsonValue::String(text)) => read_as(&text, Some(IonType::Float), &key)?,
+        (DECIMAL_KEY, JsonValue::String(text)) => read_as(&text, Some(IonType::Decimal), &key)?,
+        (TIMESTAMP_KEY, JsonValue::String(text)) => read_as(&text, Some(IonType::Timestamp), &key)?,
+        (SYMBOL_KEY, JsonValue::String(text)) => Value::Symbol(Symbol::owned(text)).into(),
+        (BLOB_KEY, JsonValue::String(text)) => {
+            read_as(&format!("{{{{{}}}}}", text), Some(IonType::Blob), &key)?
+        }
+        (CLOB_KEY, JsonValue::String(text)) => {
+            let blob = read_as(&format!("{{{{{}}}}}", text), Some(IonType::Blob), &key)?;
+            Value::Clob(blob.as_bytes().unwrap().to_vec()).into()
+        }
+        (SEXP_KEY, JsonValue::Array(values)) => Element::new_sexp(from_typed_json_values(values)?),
+        (STRUCT_KEY, JsonValue::Array(fields)) => fields
+            .into_iter()
+            .map(|field| match field {
+                JsonValue::Array(pair) if pair.len() == 2 => {
+                    let mut pair = pair.into_iter();
+                    let name = match pair.next().unwrap() {
+                        JsonValue::String(name) => Symbol::owned(name),
+                        other => bail!("'{}' field names must be strings; found {}", key, other),
+                    };
+                    Ok((name, from_typed_json_value(pair.next().unwrap())?))
+                }
+                other => bail!(
+                    "'{}' fields must be [name, value] pairs; found {}",
+                    key,
+                    other
+                ),
+            })
+            .collect::<Result<Vec<(Symbol, Element)>>>()?
+            .into_iter()
+            .collect::<Struct>()
+            .into(),
+        (_, value) => bail!(
+            "Unrecognized '{}' object: {}",
+            KEY_PREFIX,
+            JsonValue::Object(Map::from_iter([(key, value)]))
+        ),
+    };
+    Ok(element)
+}
+
+/// Reads Ion text that must hold a single unannotated value of the expected type, or a typed
+/// null if no type is given.
+fn read_as(text: &str, expected: Option<IonType>, key: &str) -> Result<Element> {
+    let element = element_reader()
+        .read_one(text.as_bytes())
+        .with_context(|| format!("'{}' is not a valid '{}' value", text, key))?;
+    let valid = element.annotations().next().is_none()
+        && match expected {
+            Some(ion_type) => element.ion_type() == ion_type && !element.is_null(),
+            None => element.is_null(),
+        };
+    if !valid {
+        bail!("'{}' is not a valid '{}' value", text, key);
+    }
+    Ok(element)
+}
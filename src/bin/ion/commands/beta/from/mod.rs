@@ -0,0 +1,209 @@
+pub mod arrow;
+pub mod avro;
+pub mod cbor;
+pub mod csv;
+pub mod json;
+pub mod msgpack;
+pub mod protobuf;
+pub mod toml;
+pub mod xml;
+pub mod yaml;
+
+use crate::commands::dump;
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::value::native_writer::NativeElementWriter;
+use ion_rs::value::owned::Element;
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::writer::ElementWriter;
+use ion_rs::{BinaryWriterBuilder, IonWriter, TextWriterBuilder};
+use std::fs::File;
+use std::io::{stdin, Read};
+
+const ABOUT: &str =
+    "Converts data from a particular format into Ion. Currently supports json, csv, tsv, \
+                     yaml, cbor, msgpack, toml, xml, avro, arrow, and protobuf.";
+
+// Creates a `clap` (Command Line Arguments Parser) configuration for the `from` command.
+// This function is invoked by the `from` command's parent, `beta`, so it can describe its
+// child commands.
+pub fn app() -> Command {
+    Command::new("from")
+        .about(ABOUT)
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("pretty")
+                .value_parser(["binary", "text", "pretty", "lines"])
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("source_format")
+                .index(1)
+                .required(true)
+                .value_parser([
+                    "json", "csv", "tsv", "yaml", "cbor", "msgpack", "toml", "xml", "avro",
+                    "arrow", "protobuf",
+                ])
+                .help("Format of the data to convert."),
+        )
+        .arg(
+            // All argv entries after the source format and any `clap`-managed options are
+            // considered input files.
+            Arg::new("input")
+                .index(2)
+                .trailing_var_arg(true)
+                .action(ArgAction::Append)
+                .help("Input file"),
+        )
+        .args(csv::args())
+        .args(json::args())
+        .args(crate::commands::beta::to::msgpack::args())
+        .args(crate::commands::beta::to::xml::args())
+        .args(xml::args())
+        .args(crate::commands::beta::to::protobuf::args())
+}
+
+// This function is invoked by the `from` command's parent, `beta`.
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    let result = match matches
+        .get_one::<String>("source_format")
+        .with_context(|| "No `source_format` was specified.")?
+        .as_str()
+    {
+        "json" => {
+            let converter = json::UpConverter::from_matches(matches)?;
+            if matches.get_flag("preserve-types") {
+                convert_inputs(matches, |input| json::read_typed_json(input, converter.clone()))
+            } else if !converter.is_empty() {
+                convert_inputs(matches, |input| json::read_json(input, converter.clone()))
+            } else {
+                // Because JSON data is valid Ion, the `dump` command may be reused for converting
+                // JSON when no up-conversion rules were given.
+                dump::run("from", matches)
+            }
+        }
+        "csv" => convert_inputs(matches, |input| csv::read_csv(input, b',', matches)),
+        "tsv" => convert_inputs(matches, |input| csv::read_csv(input, b'\t', matches)),
+        "yaml" => convert_inputs(matches, yaml::read_yaml),
+        "cbor" => convert_inputs(matches, cbor::read_cbor),
+        "msgpack" => convert_inputs(matches, |input| msgpack::read_msgpack(input, matches)),
+        "toml" => convert_inputs(matches, toml::read_toml),
+        "xml" => convert_inputs(matches, |input| xml::read_xml(input, matches)),
+        "avro" => convert_inputs(matches, avro::read_avro),
+        "arrow" => convert_inputs(matches, arrow::read_arrow),
+        "protobuf" => convert_inputs(matches, |input| protobuf::read_protobuf(input, matches)),
+        unrecognized => unreachable!(
+            "'source_format' was '{}' instead of 'json', 'csv', 'tsv', 'yaml', 'cbor', 'msgpack', 'toml', 'xml', 'avro', 'arrow', or 'protobuf'",
+            unrecognized
+        ),
+    };
+    result
+}
+
+/// The values converted from a single input.
+pub(crate) type Elements = Box<dyn Iterator<Item = Result<Element>>>;
+
+/// Converts each of the inputs into Ion values using `convert` and writes them all to the
+/// output as a single stream in the requested `--format`.
+pub(crate) fn convert_inputs<C>(matches: &ArgMatches, convert: C) -> Result<()>
+where
+    C: Fn(Box<dyn Read>) -> Result<Elements>,
+{
+    let output = dump::open_output(matches)?;
+    // `clap` validates the specified format and provides a default otherwise.
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "pretty" => write_converted(TextWriterBuilder::pretty().build(output)?, matches, convert),
+        "text" => write_converted(
+            TextWriterBuilder::default().build(output)?,
+            matches,
+            convert,
+        ),
+        "lines" => write_converted(TextWriterBuilder::lines().build(output)?, matches, convert),
+        "binary" => write_converted(BinaryWriterBuilder::new().build(output)?, matches, convert),
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'pretty', 'text', 'lines', or 'binary'",
+            unrecognized
+        ),
+    }
+}
+
+fn write_converted<W, C>(writer: W, matches: &ArgMatches, convert: C) -> Result<()>
+where
+    W: IonWriter,
+    C: Fn(Box<dyn Read>) -> Result<Elements>,
+{
+    let mut element_writer = NativeElementWriter::new(writer);
+    if let Some(input_file_iter) = matches.get_many::<String>("input") {
+        for input_file in input_file_iter {
+            let file = File::open(input_file)
+                .with_context(|| format!("Could not open file '{}'", input_file))?;
+            for element in convert(Box::new(file))? {
+                let element =
+                    element.with_context(|| format!("Could not convert '{}'", input_file))?;
+                element_writer.write(&element)?;
+            }
+        }
+    } else {
+        for element in convert(Box::new(stdin()))? {
+            element_writer.write(&element?)?;
+        }
+    }
+    element_writer.finish()?.flush()?;
+    Ok(())
+}
+
+/// Converts a number of seconds and nanoseconds since 1970-01-01T00:00:00Z into a UTC timestamp
+/// with up to nanosecond precision.
+pub(crate) fn epoch_timestamp(seconds: i64, nanoseconds: u32) -> Result<Element> {
+    if nanoseconds >= 1_000_000_000 {
+        bail!("{} is too many nanoseconds for a timestamp", nanoseconds);
+    }
+    let (days, second_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    let mut text = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day % 3600 / 60,
+        second_of_day % 60
+    );
+    if nanoseconds > 0 {
+        let fraction = format!("{:09}", nanoseconds);
+        text.push('.');
+        text.push_str(fraction.trim_end_matches('0'));
+    }
+    text.push('Z');
+    element_reader()
+        .read_one(text.as_bytes())
+        .with_context(|| format!("{} seconds since the epoch is out of range", seconds))
+}
+
+/// Returns the proleptic Gregorian (year, month, day) that is `days` after 1970-01-01, using
+/// Howard Hinnant's `civil_from_days` algorithm.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,117 @@
+use crate::commands::beta::from::{epoch_timestamp, Elements};
+use crate::commands::beta::to::msgpack::{ExtTypes, TIMESTAMP_EXT_TYPE};
+use anyhow::{bail, Context, Result};
+use clap::ArgMatches;
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::{Builder, IonElement};
+use ion_rs::{IonType, Symbol};
+use rmpv::Value as MsgPackValue;
+use std::io::{BufRead, BufReader, Read};
+
+/// Reads a stream of MessagePack objects, converting each into a top-level Ion value as it is
+/// read.
+pub(crate) fn read_msgpack(input: Box<dyn Read>, matches: &ArgMatches) -> Result<Elements> {
+    let ext_types = ExtTypes::from_matches(matches)?;
+    let mut reader = BufReader::new(input);
+    let elements = std::iter::from_fn(move || {
+        match reader.fill_buf() {
+            Ok([]) => return None,
+            Ok(_) => {}
+            Err(error) => return Some(Err(error.into())),
+        }
+        let element = rmpv::decode::read_value(&mut reader)
+            .with_context(|| "Could not read MessagePack")
+            .and_then(|value| to_element(value, &ext_types));
+        Some(element)
+    });
+    Ok(Box::new(elements))
+}
+
+/// Converts a MessagePack object to Ion, reversing the mapping used by `beta to msgpack`. Bin
+/// and any extension types other than the timestamp, decimal, and annotations types become
+/// blobs.
+fn to_element(value: MsgPackValue, ext_types: &ExtTypes) -> Result<Element> {
+    let element = match value {
+        MsgPackValue::Nil => Element::from(IonType::Null),
+        MsgPackValue::Boolean(value) => Element::from(value),
+        MsgPackValue::Integer(integer) => match integer.as_i64() {
+            Some(value) => Element::from(value),
+            // Larger than an i64; let the Ion reader make it into a big int.
+            None => element_reader().read_one(integer.to_string().as_bytes())?,
+        },
+        MsgPackValue::F32(value) => Element::from(f64::from(value)),
+        MsgPackValue::F64(value) => Element::from(value),
+        MsgPackValue::String(text) => match text.into_str() {
+            Some(text) => Element::from(text),
+            None => bail!("MessagePack strings must be valid UTF-8"),
+        },
+        MsgPackValue::Binary(bytes) => Element::from(Value::Blob(bytes)),
+        MsgPackValue::Array(values) => Element::new_list(
+            values
+                .into_iter()
+                .map(|value| to_element(value, ext_types))
+                .collect::<Result<Vec<Element>>>()?,
+        ),
+        MsgPackValue::Map(entries) => {
+            let mut fields = Vec::with_capacity(entries.len());
+            for (key, value) in entries {
+                let name = match key {
+                    MsgPackValue::String(text) => match text.into_str() {
+                        Some(text) => text,
+                        None => bail!("MessagePack strings must be valid UTF-8"),
+                    },
+                    MsgPackValue::Integer(integer) => integer.to_string(),
+                    key => bail!(
+                        "Only string and int map keys can be field names; found {}",
+                        key
+                    ),
+                };
+                fields.push((Symbol::owned(name), to_element(value, ext_types)?));
+            }
+            fields.into_iter().collect::<Struct>().into()
+        }
+        MsgPackValue::Ext(TIMESTAMP_EXT_TYPE, data) => {
+            let (seconds, nanoseconds) = match data.len() {
+                4 => (i64::from(u32::from_be_bytes(data[..].try_into()?)), 0),
+                8 => {
+                    let data = u64::from_be_bytes(data[..].try_into()?);
+                    ((data & 0x3_ffff_ffff) as i64, (data >> 34) as u32)
+                }
+                12 => (
+                    i64::from_be_bytes(data[4..].try_into()?),
+                    u32::from_be_bytes(data[..4].try_into()?),
+                ),
+                length => bail!("A timestamp extension cannot have {} bytes of data", length),
+            };
+            epoch_timestamp(seconds, nanoseconds)?
+        }
+        MsgPackValue::Ext(ext_type, data) if ext_type == ext_types.decimal => {
+            let text = String::from_utf8(data)?;
+            match element_reader().read_one(text.as_bytes()) {
+                Ok(element) if element.ion_type() == IonType::Decimal => element,
+                _ => bail!("'{}' is not a valid decimal", text),
+            }
+        }
+        MsgPackValue::Ext(ext_type, data) if ext_type == ext_types.annotations => {
+            let parts = match rmpv::decode::read_value(&mut data.as_slice())? {
+                MsgPackValue::Array(parts) if parts.len() == 2 => parts,
+                _ => bail!("Annotated values must be an array of annotations and a value"),
+            };
+            let mut parts = parts.into_iter();
+            let annotations = match parts.next().unwrap() {
+                MsgPackValue::Array(annotations) => annotations
+                    .into_iter()
+                    .map(|annotation| match annotation.as_str() {
+                        Some(text) => Ok(Symbol::owned(text)),
+                        None => bail!("Annotations must be strings; found {}", annotation),
+                    })
+                    .collect::<Result<Vec<Symbol>>>()?,
+                _ => bail!("Annotations must be an array of strings"),
+            };
+            to_element(parts.next().unwrap(), ext_types)?.with_annotations(annotations)
+        }
+        MsgPackValue::Ext(_, data) => Element::from(Value::Blob(data)),
+    };
+    Ok(element)
+}
@@ -0,0 +1,177 @@
+use crate::commands::beta::from::avro::read_ion;
+use crate::commands::beta::from::{epoch_timestamp, Elements};
+use crate::commands::beta::to::protobuf::{message_descriptor, WRAPPERS};
+use anyhow::{bail, Context, Result};
+use clap::ArgMatches;
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::Builder;
+use ion_rs::{Integer, Symbol};
+use num_bigint::BigInt;
+use prost_reflect::{
+    DynamicMessage, FieldDescriptor, Kind, MapKey, MessageDescriptor, ReflectMessage,
+    Value as ProtoValue,
+};
+use std::io::{BufReader, ErrorKind, Read};
+use std::iter;
+
+/// Reads a stream of length-delimited `--message`s from the `--descriptor-set` as structs. Fields
+/// that aren't set, which in proto3 includes fields set to their default value, are left out.
+pub(crate) fn read_protobuf(input: Box<dyn Read>, matches: &ArgMatches) -> Result<Elements> {
+    let descriptor = message_descriptor(matches)?;
+    let mut input = BufReader::new(input);
+    Ok(Box::new(iter::from_fn(move || {
+        read_message(&mut input, &descriptor).transpose()
+    })))
+}
+
+/// Reads the next message, or returns `None` at the end of the stream.
+fn read_message(input: &mut impl Read, descriptor: &MessageDescriptor) -> Result<Option<Element>> {
+    let length = match read_varint(input)? {
+        Some(length) => length,
+        None => return Ok(None),
+    };
+    // The length is untrusted, so the buffer only grows as bytes actually arrive.
+    let mut bytes = Vec::new();
+    input.take(length).read_to_end(&mut bytes)?;
+    if (bytes.len() as u64) < length {
+        bail!(
+            "The protobuf stream ends in the middle of a message; expected {} bytes but found {}",
+            length,
+            bytes.len()
+        );
+    }
+    let message = DynamicMessage::decode(descriptor.clone(), bytes.as_slice())
+        .with_context(|| format!("Could not read a '{}' message", descriptor.full_name()))?;
+    Ok(Some(message_element(&message)?))
+}
+
+/// Reads a message's length prefix, or returns `None` if the stream has ended.
+fn read_varint(input: &mut impl Read) -> Result<Option<u64>> {
+    let mut value = 0u64;
+    for index in 0..10 {
+        let mut byte = [0u8];
+        if let Err(error) = input.read_exact(&mut byte) {
+            return match (error.kind(), index) {
+                (ErrorKind::UnexpectedEof, 0) => Ok(None),
+                (ErrorKind::UnexpectedEof, _) => {
+                    bail!("The protobuf stream ends in the middle of a message length")
+                }
+                _ => Err(error.into()),
+            };
+        }
+        value |= u64::from(byte[0] & 0x7f) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    bail!("The protobuf stream has a message length that is too long")
+}
+
+/// Converts a message to a struct with a field for each field that is set. The well-known
+/// `Timestamp`, `Duration`, and wrapper types become timestamps, decimal seconds, and bare values.
+fn message_element(message: &DynamicMessage) -> Result<Element> {
+    let descriptor = message.descriptor();
+    let number = |name: &str| message.get_field_by_name(name).unwrap().into_owned();
+    match descriptor.full_name() {
+        "google.protobuf.Timestamp" => {
+            let (seconds, nanoseconds) = (number("seconds"), number("nanos"));
+            let nanoseconds = u32::try_from(nanoseconds.as_i32().unwrap())
+                .with_context(|| "A Timestamp's nanos must not be negative")?;
+            return epoch_timestamp(seconds.as_i64().unwrap(), nanoseconds);
+        }
+        "google.protobuf.Duration" => {
+            let nanoseconds = i128::from(number("seconds").as_i64().unwrap()) * 1_000_000_000
+                + i128::from(number("nanos").as_i32().unwrap());
+            return seconds_decimal(nanoseconds);
+        }
+        name if WRAPPERS.contains(&name) => {
+            let field = descriptor.get_field_by_name("value").unwrap();
+            return value_element(&message.get_field(&field), &field.kind());
+        }
+        _ => {}
+    }
+    message
+        .fields()
+        .map(|(field, value)| Ok((Symbol::owned(field.name()), field_element(value, &field)?)))
+        .collect::<Result<Vec<(Symbol, Element)>>>()
+        .map(|fields| fields.into_iter().collect::<Struct>().into())
+}
+
+/// Writes a number of nanoseconds as a decimal number of seconds with no more fractional digits
+/// than it needs.
+fn seconds_decimal(nanoseconds: i128) -> Result<Element> {
+    let (mut coefficient, mut exponent) = (nanoseconds, -9);
+    while exponent < 0 && coefficient % 10 == 0 {
+        coefficient /= 10;
+        exponent += 1;
+    }
+    read_ion(&format!("{}d{}", coefficient, exponent))
+}
+
+/// Converts a field's value, which is a list for a repeated field and a struct for a map field.
+fn field_element(value: &ProtoValue, field: &FieldDescriptor) -> Result<Element> {
+    match value {
+        ProtoValue::List(values) => Ok(Element::new_list(
+            values
+                .iter()
+                .map(|value| value_element(value, &field.kind()))
+                .collect::<Result<Vec<Element>>>()?,
+        )),
+        ProtoValue::Map(entries) => {
+            let value_kind = match field.kind() {
+                Kind::Message(entry) => entry.map_entry_value_field().kind(),
+                kind => kind,
+            };
+            // Protobuf maps are unordered; sort the entries so the output is stable.
+            let mut entries: Vec<(&MapKey, &ProtoValue)> = entries.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        MapKey::String(key) => key.clone(),
+                        MapKey::Bool(key) => key.to_string(),
+                        MapKey::I32(key) => key.to_string(),
+                        MapKey::I64(key) => key.to_string(),
+                        MapKey::U32(key) => key.to_string(),
+                        MapKey::U64(key) => key.to_string(),
+                    };
+                    Ok((Symbol::owned(key), value_element(value, &value_kind)?))
+                })
+                .collect::<Result<Vec<(Symbol, Element)>>>()
+                .map(|fields| fields.into_iter().collect::<Struct>().into())
+        }
+        value => value_element(value, &field.kind()),
+    }
+}
+
+/// Converts a single value of the given type. Enum values become symbols of their names, or ints
+/// if the enum doesn't name them.
+fn value_element(value: &ProtoValue, kind: &Kind) -> Result<Element> {
+    let element = match value {
+        ProtoValue::Bool(value) => (*value).into(),
+        ProtoValue::I32(value) => i64::from(*value).into(),
+        ProtoValue::I64(value) => (*value).into(),
+        ProtoValue::U32(value) => i64::from(*value).into(),
+        ProtoValue::U64(value) => match i64::try_from(*value) {
+            Ok(value) => value.into(),
+            Err(_) => Value::Integer(Integer::BigInt(BigInt::from(*value))).into(),
+        },
+        ProtoValue::F32(value) => f64::from(*value).into(),
+        ProtoValue::F64(value) => (*value).into(),
+        ProtoValue::String(value) => value.clone().into(),
+        ProtoValue::Bytes(value) => Value::Blob(value.to_vec()).into(),
+        ProtoValue::EnumNumber(number) => match kind {
+            Kind::Enum(descriptor) => match descriptor.get_value(*number) {
+                Some(value) => Value::Symbol(Symbol::owned(value.name())).into(),
+                None => i64::from(*number).into(),
+            },
+            _ => i64::from(*number).into(),
+        },
+        ProtoValue::Message(message) => message_element(message)?,
+        ProtoValue::List(_) | ProtoValue::Map(_) => {
+            bail!("Protobuf lists and maps can only be the values of fields")
+        }
+    };
+    Ok(element)
+}
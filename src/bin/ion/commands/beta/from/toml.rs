@@ -0,0 +1,58 @@
+use crate::commands::beta::from::Elements;
+use anyhow::{Context, Result};
+use ion_rs::value::owned::{Element, Struct};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::Builder;
+use std::io::Read;
+use toml::value::Datetime;
+use toml::Value as TomlValue;
+
+/// Reads a TOML document and converts it into a single Ion struct.
+pub(crate) fn read_toml(mut input: Box<dyn Read>) -> Result<Elements> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let table: toml::Table = ::toml::from_str(&text)?;
+    let element = to_element(TomlValue::Table(table));
+    Ok(Box::new(std::iter::once(element)))
+}
+
+fn to_element(value: TomlValue) -> Result<Element> {
+    let element = match value {
+        TomlValue::String(text) => Element::from(text),
+        TomlValue::Integer(value) => Element::from(value),
+        TomlValue::Float(value) => Element::from(value),
+        TomlValue::Boolean(value) => Element::from(value),
+        TomlValue::Datetime(datetime) => datetime_element(&datetime)?,
+        TomlValue::Array(values) => Element::new_list(
+            values
+                .into_iter()
+                .map(to_element)
+                .collect::<Result<Vec<Element>>>()?,
+        ),
+        TomlValue::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| Ok((key.as_str().into(), to_element(value)?)))
+            .collect::<Result<Vec<(ion_rs::Symbol, Element)>>>()?
+            .into_iter()
+            .collect::<Struct>()
+            .into(),
+    };
+    Ok(element)
+}
+
+/// Converts a TOML date-time to a timestamp with the same precision. Local date-times become
+/// timestamps with an unknown offset and local dates become day-precision timestamps. Ion has no
+/// type for a time of day on its own, so local times become strings.
+fn datetime_element(datetime: &Datetime) -> Result<Element> {
+    // TOML's date-time text is also valid Ion timestamp text, apart from the parts that Ion
+    // requires and a local date-time or date leaves out.
+    let text = match (&datetime.date, &datetime.time, &datetime.offset) {
+        (Some(_), Some(_), Some(_)) => datetime.to_string(),
+        (Some(_), Some(_), None) => format!("{}-00:00", datetime),
+        (Some(_), None, _) => format!("{}T", datetime),
+        (None, _, _) => return Ok(Element::from(datetime.to_string())),
+    };
+    element_reader()
+        .read_one(text.as_bytes())
+        .with_context(|| format!("'{}' is not a valid timestamp", datetime))
+}
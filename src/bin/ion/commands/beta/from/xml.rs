@@ -0,0 +1,243 @@
+use crate::commands::beta::from::Elements;
+use crate::commands::beta::to::xml::XmlMapping;
+use anyhow::{bail, Result};
+use clap::{Arg, ArgMatches};
+use ion_rs::value::owned::{Element, Struct, Value};
+use ion_rs::value::Builder;
+use ion_rs::{IonType, Symbol};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{QName, ResolveResult};
+use quick_xml::NsReader;
+use std::io::{BufRead, BufReader, Read};
+
+const HEADING: &str = "XML options";
+
+// Format-specific options for the `from` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("repeated")
+            .long("repeated")
+            .default_value("list")
+            .value_parser(["list", "fields"])
+            .help_heading(HEADING)
+            .help(
+                "With --mapping struct, whether children that share a name are gathered into a \
+                 list or written as repeated field names",
+            ),
+        Arg::new("namespaces")
+            .long("namespaces")
+            .default_value("keep")
+            .value_parser(["keep", "strip", "expand"])
+            .help_heading(HEADING)
+            .help(
+                "Keep names as written (like ns:name, with xmlns attributes), strip their \
+                 prefixes and xmlns attributes, or expand them to {namespace-uri}name",
+            ),
+    ]
+}
+
+/// An XML element, with names already adjusted for `--namespaces`.
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    content: Vec<XmlContent>,
+}
+
+enum XmlContent {
+    Element(XmlElement),
+    Text(String),
+}
+
+/// Reads an XML document and converts its root element to a single Ion value. A sequence of
+/// root elements, as `beta to xml` writes for a stream of values, becomes a value for each one.
+pub(crate) fn read_xml(input: Box<dyn Read>, matches: &ArgMatches) -> Result<Elements> {
+    let converter = XmlConverter {
+        mapping: XmlMapping::from_matches(matches),
+        gather_repeated: matches.get_one::<String>("repeated").unwrap() == "list",
+        namespaces: matches.get_one::<String>("namespaces").unwrap().clone(),
+    };
+    let roots = converter.parse(BufReader::new(input))?;
+    let elements: Vec<Result<Element>> = roots
+        .into_iter()
+        .map(|root| {
+            if converter.mapping.sexp {
+                Ok(converter.to_sexp(root))
+            } else {
+                let name = Symbol::owned(root.name.clone());
+                Ok(vec![(name, converter.to_struct_value(root))]
+                    .into_iter()
+                    .collect::<Struct>()
+                    .into())
+            }
+        })
+        .collect();
+    Ok(Box::new(elements.into_iter()))
+}
+
+struct XmlConverter {
+    mapping: XmlMapping,
+    gather_repeated: bool,
+    namespaces: String,
+}
+
+impl XmlConverter {
+    /// Parses the input into a tree of elements for each root element. Comments, processing
+    /// instructions, and text that is only whitespace are skipped.
+    fn parse<R: BufRead>(&self, input: R) -> Result<Vec<XmlElement>> {
+        let mut reader = NsReader::from_reader(input);
+        reader.expand_empty_elements(true);
+        let mut buffer = Vec::new();
+        let mut open: Vec<XmlElement> = Vec::new();
+        let mut roots = Vec::new();
+        loop {
+            let text = match reader.read_event_into(&mut buffer)? {
+                Event::Start(start) => {
+                    open.push(self.start_element(&reader, &start)?);
+                    None
+                }
+                Event::End(_) => {
+                    let element = open.pop().unwrap();
+                    match open.last_mut() {
+                        Some(parent) => parent.content.push(XmlContent::Element(element)),
+                        None => roots.push(element),
+                    }
+                    None
+                }
+                Event::Text(text) => Some(text.unescape()?.into_owned()),
+                Event::CData(data) => Some(String::from_utf8(data.into_inner().into_owned())?),
+                Event::Eof => break,
+                _ => None,
+            };
+            if let (Some(text), Some(parent)) = (text, open.last_mut()) {
+                if !text.trim().is_empty() {
+                    parent.content.push(XmlContent::Text(text));
+                }
+            }
+            buffer.clear();
+        }
+        Ok(roots)
+    }
+
+    fn start_element<R>(&self, reader: &NsReader<R>, start: &BytesStart) -> Result<XmlElement> {
+        let (namespace, _) = reader.resolve_element(start.name());
+        let name = self.name(namespace, start.name())?;
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute?;
+            let key = attribute.key;
+            let is_declaration =
+                key.as_ref() == b"xmlns" || key.prefix().is_some_and(|p| p.as_ref() == b"xmlns");
+            if is_declaration && self.namespaces != "keep" {
+                continue;
+            }
+            let (namespace, _) = reader.resolve_attribute(key);
+            let name = self.name(namespace, key)?;
+            attributes.push((name, attribute.unescape_value()?.into_owned()));
+        }
+        Ok(XmlElement {
+            name,
+            attributes,
+            content: Vec::new(),
+        })
+    }
+
+    fn name(&self, namespace: ResolveResult, name: QName) -> Result<String> {
+        let local_name = String::from_utf8(name.local_name().as_ref().to_vec())?;
+        let name = match (self.namespaces.as_str(), namespace) {
+            ("keep", _) => String::from_utf8(name.as_ref().to_vec())?,
+            (_, ResolveResult::Unknown(prefix)) => bail!(
+                "The XML namespace prefix '{}' is not declared",
+                String::from_utf8_lossy(&prefix)
+            ),
+            ("expand", ResolveResult::Bound(uri)) => {
+                format!(
+                    "{{{}}}{}",
+                    String::from_utf8_lossy(uri.as_ref()),
+                    local_name
+                )
+            }
+            _ => local_name,
+        };
+        Ok(name)
+    }
+
+    /// Converts an element to `(name {attributes} child...)`, with text as strings.
+    fn to_sexp(&self, element: XmlElement) -> Element {
+        let attributes = element
+            .attributes
+            .into_iter()
+            .map(|(name, value)| (Symbol::owned(name), Element::from(value)))
+            .collect::<Struct>();
+        let mut values = vec![
+            Value::Symbol(Symbol::owned(element.name)).into(),
+            attributes.into(),
+        ];
+        for content in element.content {
+            values.push(match content {
+                XmlContent::Element(child) => self.to_sexp(child),
+                XmlContent::Text(text) => text.into(),
+            });
+        }
+        Element::new_sexp(values)
+    }
+
+    /// Converts an element to the value of the field named after it. An element with only text
+    /// becomes a string, and an empty element becomes null. Otherwise, the element becomes a
+    /// struct of its attributes, its trimmed text, and its children.
+    fn to_struct_value(&self, element: XmlElement) -> Element {
+        let text = element
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                XmlContent::Text(text) => Some(text.trim()),
+                XmlContent::Element(_) => None,
+            })
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let has_children = element
+            .content
+            .iter()
+            .any(|content| matches!(content, XmlContent::Element(_)));
+        if element.attributes.is_empty() && !has_children {
+            return match text.is_empty() {
+                true => Value::Null(IonType::Null).into(),
+                false => text.into(),
+            };
+        }
+
+        let mut fields: Vec<(String, Element)> = element
+            .attributes
+            .into_iter()
+            .map(|(name, value)| {
+                let name = format!("{}{}", self.mapping.attribute_prefix, name);
+                (name, Element::from(value))
+            })
+            .collect();
+        if !text.is_empty() {
+            fields.push((self.mapping.text_key.clone(), text.into()));
+        }
+        let mut children: Vec<(String, Vec<Element>)> = Vec::new();
+        for content in element.content {
+            if let XmlContent::Element(child) = content {
+                let name = child.name.clone();
+                let value = self.to_struct_value(child);
+                match children.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, values)) if self.gather_repeated => values.push(value),
+                    _ => children.push((name, vec![value])),
+                }
+            }
+        }
+        for (name, mut values) in children {
+            let value = match values.len() {
+                1 => values.pop().unwrap(),
+                _ => Element::new_list(values),
+            };
+            fields.push((name, value));
+        }
+        fields
+            .into_iter()
+            .map(|(name, value)| (Symbol::owned(name), value))
+            .collect::<Struct>()
+            .into()
+    }
+}
@@ -0,0 +1,332 @@
+use crate::commands::beta::from::Elements;
+use anyhow::{bail, Context, Result};
+use ion_rs::value::owned::{Element, Struct};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::{Builder, IonElement};
+use ion_rs::{IonType, Symbol};
+use num_bigint::BigInt;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+/// The prefix that the `!!` tag handle stands for, as in `!!str`.
+const CORE_TAG_PREFIX: &str = "tag:yaml.org,2002:";
+
+/// Reads a stream of YAML documents, converting each one into a top-level Ion value. Anchors and
+/// aliases (including `<<` merge keys) are expanded.
+pub(crate) fn read_yaml(mut input: Box<dyn Read>) -> Result<Elements> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let mut loader = Loader::default();
+    Parser::new_from_str(&text)
+        .load(&mut loader, true)
+        .with_context(|| "Could not read YAML")?;
+    if let Some(anchor) = loader.recursive_anchor {
+        bail!(
+            "The alias of anchor {} refers to a collection that contains it",
+            anchor
+        );
+    }
+    let converter = YamlConverter::new();
+    let elements: Vec<Result<Element>> = loader
+        .documents
+        .into_iter()
+        .map(|node| converter.to_element(node))
+        .collect();
+    Ok(Box::new(elements.into_iter()))
+}
+
+/// A YAML node with its aliases expanded. Scalars keep their style, since only plain scalars are
+/// read as anything other than strings.
+#[derive(Clone)]
+enum Node {
+    Scalar(String, TScalarStyle, Option<Tag>),
+    Sequence(Vec<Node>, Option<Tag>),
+    Mapping(Vec<(Node, Node)>, Option<Tag>),
+}
+
+/// Builds a [Node] for each document from the parser's events.
+#[derive(Default)]
+struct Loader {
+    documents: Vec<Node>,
+    // The collections being read, each with its anchor ID and, for a mapping, a key that's
+    // waiting for its value.
+    open: Vec<(Node, usize, Option<Node>)>,
+    anchors: HashMap<usize, Node>,
+    recursive_anchor: Option<usize>,
+}
+
+impl MarkedEventReceiver for Loader {
+    fn on_event(&mut self, event: Event, _mark: Marker) {
+        match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                self.add(Node::Scalar(text, style, tag), anchor)
+            }
+            Event::SequenceStart(anchor, tag) => {
+                self.open
+                    .push((Node::Sequence(Vec::new(), tag), anchor, None))
+            }
+            Event::MappingStart(anchor, tag) => {
+                self.open
+                    .push((Node::Mapping(Vec::new(), tag), anchor, None))
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (node, anchor, _) = self.open.pop().unwrap();
+                self.add(node, anchor);
+            }
+            Event::Alias(anchor) => match self.anchors.get(&anchor) {
+                Some(node) => self.add(node.clone(), 0),
+                // The anchored collection hasn't ended yet; leave a placeholder and report it
+                // once the parser is done.
+                None => {
+                    self.recursive_anchor.get_or_insert(anchor);
+                    self.add(Node::Scalar(String::new(), TScalarStyle::Plain, None), 0);
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Loader {
+    fn add(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+        match self.open.last_mut() {
+            None => self.documents.push(node),
+            Some((Node::Sequence(values, _), _, _)) => values.push(node),
+            Some((Node::Mapping(entries, _), _, key)) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None => *key = Some(node),
+            },
+            Some((Node::Scalar(..), _, _)) => unreachable!("scalars are never open"),
+        }
+    }
+}
+
+pub(crate) struct YamlConverter {
+    int: Regex,
+    float: Regex,
+    infinity: Regex,
+    // The text of a `!!decimal` scalar, like `17.50`.
+    decimal: Regex,
+    // The implicit timestamp format from the YAML 1.1 type repository, which also allows minute
+    // precision: https://yaml.org/type/timestamp.html
+    timestamp: Regex,
+    // Ion's timestamps with year, month, or day precision, like `2020-05T`.
+    ion_timestamp: Regex,
+}
+
+impl YamlConverter {
+    pub(crate) fn new() -> Self {
+        YamlConverter {
+            int: Regex::new(r"^(?:[-+]?(?:0|[1-9][0-9]*)|0o[0-7]+|0x[0-9a-fA-F]+)$").unwrap(),
+            float: Regex::new(concat!(
+                r"^[-+]?(?:(?:[0-9]+\.[0-9]*|\.[0-9]+)(?:[eE][-+]?[0-9]+)?",
+                r"|[0-9]+[eE][-+]?[0-9]+)$"
+            ))
+            .unwrap(),
+            infinity: Regex::new(r"^[-+]?\.(?:inf|Inf|INF)$").unwrap(),
+            decimal: Regex::new(concat!(
+                r"^(?P<sign>[-+]?)(?P<digits>[0-9]+(?:\.[0-9]*)?|\.[0-9]+)",
+                r"(?:[eE](?P<exponent>[-+]?[0-9]+))?$"
+            ))
+            .unwrap(),
+            timestamp: Regex::new(concat!(
+                r"^(?P<date>[0-9]{4})-(?P<month>[0-9]{1,2})-(?P<day>[0-9]{1,2})",
+                r"(?:(?:[Tt]|[ \t]+)(?P<hour>[0-9]{1,2})",
+                r"(?P<time>:[0-9]{2}(?::[0-9]{2}(?:\.[0-9]*)?)?)",
+                r"(?:[ \t]*(?P<offset>Z|[-+][0-9]{1,2}(?::[0-9]{2})?))?)?$"
+            ))
+            .unwrap(),
+            ion_timestamp: Regex::new(r"^[0-9]{4}(?:-[0-9]{2}(?:-[0-9]{2})?)?T$").unwrap(),
+        }
+    }
+
+    /// Converts a YAML node to Ion. Tags like `!a::b` become annotations, and plain scalars are
+    /// read with [YamlConverter::resolve]; quoted scalars are always strings.
+    fn to_element(&self, node: Node) -> Result<Element> {
+        let (element, tag) = match node {
+            Node::Scalar(text, style, tag) => (self.scalar(text, style, tag.as_ref())?, tag),
+            Node::Sequence(values, tag) => {
+                let values = values
+                    .into_iter()
+                    .map(|value| self.to_element(value))
+                    .collect::<Result<Vec<Element>>>()?;
+                (Element::new_list(values), tag)
+            }
+            Node::Mapping(entries, tag) => {
+                let fields = self.fields(entries)?;
+                (fields.into_iter().collect::<Struct>().into(), tag)
+            }
+        };
+        // Core tags like `!!str` and the non-specific tag `!` only affect how a scalar is read.
+        match tag {
+            Some(tag) if tag.handle != CORE_TAG_PREFIX && !is_non_specific(&tag) => {
+                let tag = format!("{}{}", tag.handle, tag.suffix);
+                let annotations = tag
+                    .trim_start_matches('!')
+                    .split("::")
+                    .map(Symbol::owned)
+                    .collect::<Vec<Symbol>>();
+                Ok(element.with_annotations(annotations))
+            }
+            _ => Ok(element),
+        }
+    }
+
+    fn scalar(&self, text: String, style: TScalarStyle, tag: Option<&Tag>) -> Result<Element> {
+        let core_tag = match tag {
+            Some(tag) if tag.handle == CORE_TAG_PREFIX => Some(tag.suffix.as_str()),
+            Some(tag) if is_non_specific(tag) => Some("str"),
+            _ => None,
+        };
+        let expected = match core_tag {
+            None if style == TScalarStyle::Plain => return self.resolve(&text),
+            None | Some("str") => return Ok(Element::from(text)),
+            Some("null") => IonType::Null,
+            Some("bool") => IonType::Boolean,
+            Some("int") => IonType::Integer,
+            Some("float") => IonType::Float,
+            Some("timestamp") => IonType::Timestamp,
+            Some("decimal") => return self.decimal(&text),
+            Some(tag) => bail!("The YAML tag !!{} is not supported", tag),
+        };
+        let element = self.resolve(&text)?;
+        match element.ion_type() {
+            ion_type if ion_type == expected => Ok(element),
+            IonType::Integer if expected == IonType::Float => {
+                Ok(Element::from(text.parse::<f64>()?))
+            }
+            _ => bail!("'{}' is not a valid {:?}", text, expected),
+        }
+    }
+
+    /// Reads the text of a `!!decimal` scalar, which `beta to yaml` writes for Ion decimals, as a
+    /// decimal with all of its digits.
+    fn decimal(&self, text: &str) -> Result<Element> {
+        let captures = match self.decimal.captures(text) {
+            Some(captures) => captures,
+            None => bail!("'{}' is not a valid Decimal", text),
+        };
+        // Ion's decimal text has no `+` sign and needs a digit before the decimal point.
+        let sign = captures["sign"].trim_start_matches('+');
+        let exponent = captures
+            .name("exponent")
+            .map_or("0", |exponent| exponent.as_str());
+        let digits = &captures["digits"];
+        let zero = if digits.starts_with('.') { "0" } else { "" };
+        let ion_text = format!("{}{}{}d{}", sign, zero, digits, exponent);
+        Ok(element_reader().read_one(ion_text.as_bytes())?)
+    }
+
+    /// Converts a mapping's entries to fields, expanding `<<` merge keys. Merged fields come after
+    /// the mapping's own, and don't replace them.
+    fn fields(&self, entries: Vec<(Node, Node)>) -> Result<Vec<(Symbol, Element)>> {
+        let mut fields = Vec::with_capacity(entries.len());
+        let mut merged = Vec::new();
+        for (key, value) in entries {
+            let name = match key {
+                Node::Scalar(text, style, tag) => {
+                    if text == "<<" && style == TScalarStyle::Plain && tag.is_none() {
+                        let mappings = match value {
+                            Node::Sequence(values, _) => values,
+                            value => vec![value],
+                        };
+                        for mapping in mappings {
+                            match mapping {
+                                Node::Mapping(entries, _) => merged.extend(self.fields(entries)?),
+                                _ => bail!("A '<<' merge key's value must be a mapping or a sequence of mappings"),
+                            }
+                        }
+                        continue;
+                    }
+                    text
+                }
+                _ => bail!("Only scalar mapping keys can be field names"),
+            };
+            fields.push((Symbol::owned(name), self.to_element(value)?));
+        }
+        let mut names: HashSet<String> = fields
+            .iter()
+            .map(|(name, _)| name.text().unwrap_or_default().to_string())
+            .collect();
+        for (name, value) in merged {
+            if names.insert(name.text().unwrap_or_default().to_string()) {
+                fields.push((name, value));
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Reads a plain scalar using the YAML 1.2 core schema's nulls, booleans, ints, and floats,
+    /// and the timestamps of YAML 1.1 and Ion. Anything else is a string.
+    pub(crate) fn resolve(&self, text: &str) -> Result<Element> {
+        let element = match text {
+            "" | "~" | "null" | "Null" | "NULL" => Element::from(IonType::Null),
+            "true" | "True" | "TRUE" => Element::from(true),
+            "false" | "False" | "FALSE" => Element::from(false),
+            ".nan" | ".NaN" | ".NAN" => Element::from(f64::NAN),
+            _ if self.infinity.is_match(text) && text.starts_with('-') => {
+                Element::from(f64::NEG_INFINITY)
+            }
+            _ if self.infinity.is_match(text) => Element::from(f64::INFINITY),
+            _ if self.int.is_match(text) => {
+                let (digits, radix) = match text.get(..2) {
+                    Some("0o") => (&text[2..], 8),
+                    Some("0x") => (&text[2..], 16),
+                    _ => (text, 10),
+                };
+                let value = BigInt::parse_bytes(digits.as_bytes(), radix).unwrap();
+                match i64::try_from(&value) {
+                    Ok(value) => Element::from(value),
+                    Err(_) => Element::from(value),
+                }
+            }
+            _ if self.float.is_match(text) => Element::from(text.parse::<f64>()?),
+            _ => match self.timestamp_text(text) {
+                Some(timestamp) => element_reader()
+                    .read_one(timestamp.as_bytes())
+                    .with_context(|| format!("'{}' is not a valid timestamp", text))?,
+                None => Element::from(text.to_string()),
+            },
+        };
+        Ok(element)
+    }
+
+    /// If `text` is a YAML or Ion timestamp, returns the equivalent Ion timestamp text.
+    fn timestamp_text(&self, text: &str) -> Option<String> {
+        if self.ion_timestamp.is_match(text) {
+            return Some(text.to_string());
+        }
+        let captures = self.timestamp.captures(text)?;
+        let date = format!(
+            "{}-{:0>2}-{:0>2}",
+            &captures["date"], &captures["month"], &captures["day"]
+        );
+        let hour = match captures.name("hour") {
+            Some(hour) => hour.as_str(),
+            None => return Some(format!("{}T", date)),
+        };
+        // YAML timestamps without an offset are in UTC.
+        let offset = match captures.name("offset").map(|offset| offset.as_str()) {
+            None | Some("Z") => "Z".to_string(),
+            Some(offset) => {
+                let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "00"));
+                format!("{}{:0>2}:{}", &offset[..1], hours, minutes)
+            }
+        };
+        Some(format!(
+            "{}T{:0>2}{}{}",
+            date, hour, &captures["time"], offset
+        ))
+    }
+}
+
+/// Returns whether the tag is `!`, which makes a scalar a string.
+fn is_non_specific(tag: &Tag) -> bool {
+    tag.handle.len() + tag.suffix.len() == 1
+}
@@ -1,6 +1,9 @@
 use crate::commands::dump;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::*;
+use std::fs::File;
+use std::io::{stdin, Write};
 
 pub fn app() -> Command {
     Command::new("head")
@@ -40,9 +43,50 @@ pub fn app() -> Command {
 }
 
 pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
-    //TODO: Extract common value-handling logic for both `head` and `dump`
-    // https://github.com/amazon-ion/ion-cli/issues/49
-    //TODO: Multiple file handling in classic `head` includes a header per file.
-    // https://github.com/amazon-ion/ion-cli/issues/48
-    dump::run(_command_name, matches)
+    for_each_input(matches, |reader, output, format, values| {
+        dump::write_in_format(reader, output, format, Some(values), None)?;
+        Ok(())
+    })
+}
+
+/// Opens the output and each of the inputs specified in `matches`, then calls `process` with a
+/// reader for each input along with the requested output format and number of values.
+/// As with coreutils' `head` and `tail`, a `==> file <==` header is written before each input if
+/// there is more than one. Headers are omitted from binary output, where they would corrupt the
+/// stream.
+pub(crate) fn for_each_input<F>(matches: &ArgMatches, mut process: F) -> Result<()>
+where
+    F: FnMut(&mut Reader, &mut Box<dyn Write>, &str, usize) -> Result<()>,
+{
+    // --format pretty|text|lines|binary
+    // `clap` validates the specified format and provides a default otherwise.
+    let format = matches.get_one::<String>("format").unwrap();
+    // --values has a default value, so we can unwrap this safely.
+    let values = *matches.get_one::<usize>("values").unwrap();
+    let mut output = dump::open_output(matches)?;
+
+    if let Some(input_file_iter) = matches.get_many::<String>("input") {
+        let input_files: Vec<&String> = input_file_iter.collect();
+        let write_headers = input_files.len() > 1 && format != "binary";
+        for (index, input_file) in input_files.into_iter().enumerate() {
+            if write_headers {
+                // The text writers don't end their output with a newline, so it takes two to
+                // leave a blank line between files.
+                if index > 0 {
+                    write!(output, "\n\n")?;
+                }
+                writeln!(output, "==> {} <==", input_file)?;
+            }
+            let file = File::open(input_file)
+                .with_context(|| format!("Could not open file '{}'", input_file))?;
+            let mut reader = ReaderBuilder::new().build(file)?;
+            process(&mut reader, &mut output, format, values)?;
+        }
+    } else {
+        let mut reader = ReaderBuilder::new().build(stdin().lock())?;
+        process(&mut reader, &mut output, format, values)?;
+    }
+
+    output.flush()?;
+    Ok(())
 }
@@ -1,20 +1,23 @@
 use std::cmp::min;
+use std::collections::HashMap;
 use std::fmt::{Display, Write};
 use std::fs::File;
 use std::io;
 use std::io::BufWriter;
 use std::ops::Range;
-use std::str::{from_utf8_unchecked, FromStr};
+use std::str::{from_utf8, from_utf8_unchecked, FromStr};
 
 use anyhow::{bail, Context, Result};
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
 use ion_rs::result::{decoding_error, IonResult};
+use ion_rs::types::SymbolId;
 use ion_rs::*;
 use memmap::MmapOptions;
+use serde_json::{Map, Value as JsonValue};
 
 const ABOUT: &str =
-    "Displays hex-encoded binary Ion alongside its equivalent text for human-friendly debugging.";
+    "Displays hex-encoded binary Ion (or the source spans of text Ion) alongside its equivalent text for human-friendly debugging.";
 
 // Creates a `clap` (Command Line Arguments Parser) configuration for the `inspect` command.
 // This function is invoked by the `inspect` command's parent, `beta`, so it can describe its
@@ -29,18 +32,16 @@ pub fn app() -> Command {
                 .help("Output file [default: STDOUT]"),
         )
         .arg(
-            // Any number of input files can be specified by repeating the "-i" or "--input" flags.
-            // Unlabeled positional arguments will also be considered input file names.
+            // All argv entries after the program name (argv[0])
+            // and any `clap`-managed options are considered input files.
             Arg::new("input")
-                .long("input")
-                .short('i')
                 .index(1)
                 .trailing_var_arg(true)
                 .action(ArgAction::Append)
-                .help("Input file"),
+                .help("Input file [default: STDIN]"),
         )
         .arg(
-            // This is named `skip-bytes` instead of `skip` to accommodate a future `skip-values` option.
+            // This is named `skip-bytes` instead of `skip` to distinguish it from `skip-values`.
             Arg::new("skip-bytes")
                 .long("skip-bytes")
                 .short('s')
@@ -58,7 +59,7 @@ in one or more containers, those containers will be displayed too.",
                 ),
         )
         .arg(
-            // This is named `limit-bytes` instead of `limit` to accommodate a future `limit-values` option.
+            // This is named `limit-bytes` instead of `limit` to distinguish it from `limit-values`.
             Arg::new("limit-bytes")
                 .long("limit-bytes")
                 .short('l')
@@ -71,6 +72,88 @@ processing `n` bytes of Ion data. If `n` falls within a value, the
 complete value will be displayed.",
                 ),
         )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("table")
+                .value_parser(["table", "ion", "json"])
+                .help("Output format")
+                .long_help(
+                    "The default `table` format displays the encoding alongside its
+equivalent text Ion for human readers. The `ion` and `json` formats
+instead emit one record per encoded item (version marker or value)
+with its offset, length, depth, and decoded value. For binary Ion,
+each record also includes its type descriptor, length bytes, field ID,
+annotation wrapper bytes, and symbol IDs. Skipped values and limits
+are not reported in these formats.",
+                ),
+        )
+        .arg(
+            Arg::new("skip-values")
+                .long("skip-values")
+                .default_value("0")
+                .hide_default_value(true)
+                .help("Do not display the first `n` top-level user values in the stream.")
+                .long_help(
+                    "When specified, the inspector will skip the first `n` top-level
+user values before beginning to display the contents of the stream.
+System values like Ion version markers and symbol tables are not
+counted and will still be displayed. This can be combined with
+--skip-bytes; a value is skipped if either option calls for it.",
+                ),
+        )
+        .arg(
+            Arg::new("limit-values")
+                .long("limit-values")
+                .default_value("0")
+                .hide_default_value(true)
+                .help("Only display the next `n` top-level user values in the stream.")
+                .long_help(
+                    "When specified, the inspector will stop printing values after
+displaying `n` top-level user values. System values are not counted.
+Each top-level value is displayed in its entirety, including any
+values nested inside of it.",
+                ),
+        )
+        .arg(
+            Arg::new("label-bytes")
+                .long("label-bytes")
+                .action(ArgAction::SetTrue)
+                .help("Label the role of each byte in the binary Ion column.")
+                .long_help(
+                    "When specified, each row of hex bytes in the table is followed by a
+row of labels describing the role that each byte plays in the encoding:
+
+  td  type descriptor
+  ln  VarUInt length
+  fi  field ID
+  aw  annotation wrapper (type descriptor and lengths)
+  as  annotation symbol ID
+  bd  value body
+
+Bytes are also color-coded by role when writing to a terminal.
+This option has no effect on text Ion input or on the `ion` and
+`json` formats.",
+                ),
+        )
+        .arg(
+            Arg::new("show-symbols")
+                .long("show-symbols")
+                .action(ArgAction::SetTrue)
+                .help("Show where each symbol ID came from and display local symbol tables.")
+                .long_help(
+                    "When specified, each symbol ID in the text column is followed by the
+symbol table that defined it: `system`, `table #n`, where local
+symbol tables are numbered in the order they appear in the stream,
+or the name and version of an imported shared symbol table.
+Each local symbol table is also rendered as a numbered list of the
+symbol IDs it imports and the symbols it appends, with any symbol
+text that was already defined flagged as a duplicate.
+This option has no effect on text Ion input or on the `ion` and
+`json` formats.",
+                ),
+        )
 }
 
 // Create a type alias to simplify working with a shared reference to our output stream.
@@ -102,6 +185,44 @@ pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
         limit_bytes = usize::MAX
     }
 
+    // --skip-values has a default value, so we can unwrap this safely.
+    let skip_values_arg = matches.get_one::<String>("skip-values").unwrap().as_str();
+
+    let values_to_skip = usize::from_str(skip_values_arg)
+        .with_context(|| format!("Invalid value for '--skip-values': '{}'", skip_values_arg))?;
+
+    // --limit-values has a default value, so we can unwrap this safely.
+    let limit_values_arg = matches.get_one::<String>("limit-values").unwrap().as_str();
+
+    let mut limit_values = usize::from_str(limit_values_arg)
+        .with_context(|| format!("Invalid value for '--limit-values': '{}'", limit_values_arg))?;
+
+    // As with --limit-bytes, "0" means "no limit".
+    if limit_values == 0 {
+        limit_values = usize::MAX
+    }
+
+    // --format has a default value, and `clap` rejects unsupported formats.
+    let format = match matches.get_one::<String>("format").unwrap().as_str() {
+        "table" => InspectFormat::Table,
+        "ion" => InspectFormat::Ion,
+        "json" => InspectFormat::Json,
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'table', 'ion', or 'json'",
+            unrecognized
+        ),
+    };
+
+    let options = InspectorOptions {
+        format,
+        bytes_to_skip,
+        limit_bytes,
+        values_to_skip,
+        limit_values,
+        label_bytes: matches.get_flag("label-bytes"),
+        show_symbols: matches.get_flag("show-symbols"),
+    };
+
     // If the user has specified an output file, use it.
     let mut output: OutputRef = if let Some(file_name) = matches.get_one::<String>("output") {
         let output_file =
@@ -118,13 +239,7 @@ pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
         for input_file_name in input_file_iter {
             let input_file = File::open(input_file_name)
                 .with_context(|| format!("Could not open '{}'", input_file_name))?;
-            inspect_file(
-                input_file_name,
-                input_file,
-                &mut output,
-                bytes_to_skip,
-                limit_bytes,
-            )?;
+            inspect_file(input_file_name, input_file, &mut output, options)?;
         }
     } else {
         // If no input file was specified, run the inspector on STDIN.
@@ -150,13 +265,7 @@ pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
             .into_inner()
             .with_context(|| "Failed to read from temp file containing STDIN data.")?;
         // Read from the now-populated temporary file.
-        inspect_file(
-            "STDIN temp file",
-            input_file,
-            &mut output,
-            bytes_to_skip,
-            limit_bytes,
-        )?;
+        inspect_file("STDIN temp file", input_file, &mut output, options)?;
     }
     Ok(())
 }
@@ -166,8 +275,7 @@ fn inspect_file(
     input_file_name: &str,
     input_file: File,
     output: &mut OutputRef,
-    bytes_to_skip: usize,
-    limit_bytes: usize,
+    options: InspectorOptions,
 ) -> Result<()> {
     // mmap involves operating system interactions that inherently place its usage outside of Rust's
     // safety guarantees. If the file is unexpectedly truncated while it's being read, for example,
@@ -180,19 +288,29 @@ fn inspect_file(
 
     // Treat the mmap as a byte array.
     let ion_data: &[u8] = &mmap[..];
-    // Confirm that the input data is binary Ion, then run the inspector.
+    // Determine whether the input data is binary or text Ion, then run the matching inspector.
     match ion_data {
         // Pattern match the byte array to verify it starts with an IVM
         [0xE0, 0x01, 0x00, 0xEA, ..] => {
-            write_header(output)?;
-            let mut inspector = IonInspector::new(ion_data, output, bytes_to_skip, limit_bytes)?;
+            if options.format == InspectFormat::Table {
+                write_header(output, BINARY_COLUMN_TITLE)?;
+            }
+            let mut inspector = IonInspector::new(ion_data, output, options)?;
             // This inspects all values at the top level, recursing as necessary.
             inspector.inspect_level()?;
         }
+        // Anything else must be UTF-8 if it's going to be read as text Ion.
+        _ if from_utf8(ion_data).is_ok() => {
+            if options.format == InspectFormat::Table {
+                write_header(output, TEXT_COLUMN_TITLE)?;
+            }
+            let mut inspector = TextIonInspector::new(ion_data, output, options)?;
+            inspector.inspect_level()?;
+        }
         _ => {
             // bail! constructs an `anyhow::Result` with the given context and returns.
             bail!(
-                "Input file '{}' does not appear to be binary Ion.",
+                "Input file '{}' does not appear to be binary or text Ion.",
                 input_file_name
             );
         }
@@ -200,6 +318,8 @@ fn inspect_file(
     Ok(())
 }
 
+const IVM_BYTES: &[u8] = &[0xE0, 0x01, 0x00, 0xEA];
+const IVM_LENGTH: usize = IVM_BYTES.len();
 const IVM_HEX: &str = "e0 01 00 ea";
 const IVM_TEXT: &str = "// Ion 1.0 Version Marker";
 // System events (IVM, symtabs) are always at the top level.
@@ -207,13 +327,152 @@ const SYSTEM_EVENT_INDENTATION: &str = "";
 const LEVEL_INDENTATION: &str = "  "; // 2 spaces per level
 const TEXT_WRITER_INITIAL_BUFFER_SIZE: usize = 128;
 
-struct IonInspector<'a> {
-    output: &'a mut OutputRef,
-    reader: SystemReader<RawBinaryReader<io::Cursor<&'a [u8]>>>,
+// The ways in which the inspector can display what it finds. `Table` is meant for people; the
+// others emit an `InspectRecord` for each encoded item so the output can be processed by scripts.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InspectFormat {
+    Table,
+    Ion,
+    Json,
+}
+
+// Describes how the user has asked the inspector to display the stream and which portion of the
+// stream to display.
+#[derive(Clone, Copy)]
+struct InspectorOptions {
+    format: InspectFormat,
     bytes_to_skip: usize,
     limit_bytes: usize,
+    values_to_skip: usize,
+    limit_values: usize,
+    label_bytes: bool,
+    show_symbols: bool,
+}
+
+impl InspectorOptions {
+    // Returns true if the value occupying `complete_value_range` should not be displayed because
+    // the user asked to skip past it. `top_level_index` is the position of the value among the
+    // stream's top-level user values, or None if the value is nested or is a system value.
+    fn should_skip(
+        &self,
+        complete_value_range: &Range<usize>,
+        top_level_index: Option<usize>,
+    ) -> bool {
+        complete_value_range.end <= self.bytes_to_skip
+            || matches!(top_level_index, Some(index) if index < self.values_to_skip)
+    }
+
+    // If the value occupying `complete_value_range` falls beyond one of the user's limits,
+    // returns the name of the flag that set that limit.
+    fn limit_reached(
+        &self,
+        complete_value_range: &Range<usize>,
+        top_level_index: Option<usize>,
+    ) -> Option<&'static str> {
+        // Saturating subtraction: if the result would underflow, the answer will be zero.
+        let bytes_processed = complete_value_range
+            .start
+            .saturating_sub(self.bytes_to_skip);
+        if bytes_processed >= self.limit_bytes {
+            return Some("--limit-bytes");
+        }
+        match top_level_index {
+            Some(index) if index.saturating_sub(self.values_to_skip) >= self.limit_values => {
+                Some("--limit-values")
+            }
+            _ => None,
+        }
+    }
+}
+
+// Symbol IDs of the system symbols that are relevant when reading a local symbol table
+const ION_SYMBOL_TABLE_SID: SymbolId = 3;
+const NAME_SID: SymbolId = 4;
+const VERSION_SID: SymbolId = 5;
+const IMPORTS_SID: SymbolId = 6;
+const SYMBOLS_SID: SymbolId = 7;
+const MAX_ID_SID: SymbolId = 8;
+
+// The symbol table that defined a range of symbol IDs.
+enum SymbolOrigin {
+    System,
+    // The number of the local symbol table that appended the symbols.
+    Local(usize),
+    // A shared symbol table that a local symbol table imported. Without a catalog of shared tables
+    // the text of its symbols is unknown, so its symbol IDs are only reserved.
+    Shared { name: String, version: i64 },
+}
+
+// A contiguous range of symbol IDs in the current symbol table along with the table that defined
+// them.
+struct SymbolSource {
+    origin: SymbolOrigin,
+    first_id: SymbolId,
+    symbols: Vec<Option<String>>,
+}
+
+// A shared symbol table listed in a local symbol table's `imports`.
+struct SharedImport {
+    name: String,
+    version: i64,
+    max_id: usize,
+}
+
+impl SymbolSource {
+    fn system() -> SymbolSource {
+        let system_symbols = SymbolTable::new();
+        SymbolSource {
+            origin: SymbolOrigin::System,
+            // $0 is not defined by any table.
+            first_id: 1,
+            symbols: system_symbols.symbols()[1..]
+                .iter()
+                .map(|symbol| symbol.text().map(|text| text.to_string()))
+                .collect(),
+        }
+    }
+
+    fn ids(&self) -> Range<SymbolId> {
+        self.first_id..self.first_id + self.symbols.len()
+    }
+
+    fn text_for(&self, sid: SymbolId) -> Option<&str> {
+        if !self.ids().contains(&sid) {
+            return None;
+        }
+        self.symbols[sid - self.first_id].as_deref()
+    }
+
+    fn name(&self) -> String {
+        match &self.origin {
+            SymbolOrigin::System => "system".to_string(),
+            SymbolOrigin::Local(number) => format!("table #{}", number),
+            SymbolOrigin::Shared { name, version } => format!("'{}' version {}", name, version),
+        }
+    }
+}
+
+struct IonInspector<'a> {
+    output: &'a mut OutputRef,
+    input: &'a [u8],
+    // The system reader can't read shared symbol table imports, so the inspector uses a raw
+    // reader and tracks the symbol table itself in `symbol_sources`.
+    reader: RawBinaryReader<io::Cursor<&'a [u8]>>,
+    options: InspectorOptions,
+    // The number of top-level user values that have been encountered so far
+    user_values_seen: usize,
+    // The offset just past the last item (IVM or value) seen at the top level
+    top_level_end: usize,
+    // Where each symbol ID in the current symbol table came from
+    symbol_sources: Vec<SymbolSource>,
+    // Whether the current top-level value is a local symbol table
+    in_symbol_table: bool,
+    // The number of local symbol tables that have been encountered so far
+    symbol_tables_seen: usize,
     // Reusable buffer for formatting bytes as hex
     hex_buffer: String,
+    // Reusable buffer for the role of each byte in `hex_buffer`
+    role_buffer: Vec<ByteRole>,
     // Reusable buffer for formatting text
     text_buffer: String,
     // Reusable buffer for colorizing text
@@ -228,18 +487,23 @@ impl<'a> IonInspector<'a> {
     fn new<'b>(
         input: &'b [u8],
         out: &'b mut OutputRef,
-        bytes_to_skip: usize,
-        limit_bytes: usize,
+        options: InspectorOptions,
     ) -> IonResult<IonInspector<'b>> {
-        let reader = SystemReader::new(RawBinaryReader::new(io::Cursor::new(input)));
+        let reader = RawBinaryReader::new(io::Cursor::new(input));
         let text_ion_writer = RawTextWriterBuilder::new()
             .build(Vec::with_capacity(TEXT_WRITER_INITIAL_BUFFER_SIZE))?;
         let inspector = IonInspector {
             output: out,
+            input,
             reader,
-            bytes_to_skip,
-            limit_bytes,
+            options,
+            user_values_seen: 0,
+            top_level_end: 0,
+            symbol_sources: vec![SymbolSource::system()],
+            in_symbol_table: false,
+            symbol_tables_seen: 0,
             hex_buffer: String::new(),
+            role_buffer: Vec::new(),
             text_buffer: String::new(),
             color_buffer: String::new(),
             indentation_buffer: String::new(),
@@ -248,6 +512,27 @@ impl<'a> IonInspector<'a> {
         Ok(inspector)
     }
 
+    // If the reader is at the top level, returns the index of the user value on which it is
+    // parked and advances the count of user values seen.
+    fn next_top_level_index(&mut self) -> Option<usize> {
+        if self.reader.depth() > 0 {
+            return None;
+        }
+        self.user_values_seen += 1;
+        Some(self.user_values_seen - 1)
+    }
+
+    // Returns true if the reader is parked on a local symbol table: a top-level struct whose first
+    // annotation is `$ion_symbol_table`.
+    fn is_symbol_table(&self) -> bool {
+        self.reader.depth() == 0
+            && self.reader.ion_type() == Some(IonType::Struct)
+            && matches!(
+                self.reader.annotations().next(),
+                Some(Ok(token)) if token.local_sid() == Some(ION_SYMBOL_TABLE_SID)
+            )
+    }
+
     // Returns the offset of the first byte that pertains to the value on which the reader is
     // currently parked.
     fn first_value_byte_offset(&self) -> usize {
@@ -277,9 +562,11 @@ impl<'a> IonInspector<'a> {
         let mut bytes_skipped_this_level = 0;
 
         loop {
+            let top_level_index;
+            let record_kind;
             let ion_type = match self.reader.next()? {
-                SystemStreamItem::Nothing => break,
-                SystemStreamItem::VersionMarker(major, minor) => {
+                RawStreamItem::Nothing => break,
+                RawStreamItem::VersionMarker(major, minor) => {
                     if major != 1 || minor != 0 {
                         bail!(
                             "Only Ion 1.0 is supported. Found IVM for v{}.{}",
@@ -287,10 +574,31 @@ impl<'a> IonInspector<'a> {
                             minor
                         );
                     }
+                    // The reader doesn't report the IVM's offset. Unless there was padding
+                    // before it, it begins where the last top-level item ended.
+                    let ivm_offset = self.input[self.top_level_end..]
+                        .windows(IVM_LENGTH)
+                        .position(|bytes| bytes == IVM_BYTES)
+                        .map(|position| self.top_level_end + position)
+                        .unwrap_or(self.top_level_end);
+                    self.top_level_end = ivm_offset + IVM_LENGTH;
+                    // An IVM resets the symbol table.
+                    self.symbol_sources.truncate(1);
+                    if self.options.format != InspectFormat::Table {
+                        let record = InspectRecord {
+                            kind: "version_marker",
+                            offset: ivm_offset,
+                            length: IVM_LENGTH,
+                            value: Some(TEXT_IVM_TEXT.to_string()),
+                            ..Default::default()
+                        };
+                        record.write(self.output, self.options.format)?;
+                        continue;
+                    }
                     output(
                         self.output,
                         None,
-                        Some(4),
+                        Some(IVM_LENGTH),
                         SYSTEM_EVENT_INDENTATION,
                         IVM_HEX,
                         IVM_TEXT.dimmed(),
@@ -298,46 +606,77 @@ impl<'a> IonInspector<'a> {
                     .expect("output() failure from on_ivm()");
                     continue;
                 }
-                // We don't care if this is a system or user-level value; that distinction
-                // is handled inside the SystemReader.
-                SystemStreamItem::SymbolTableValue(ion_type)
-                | SystemStreamItem::Value(ion_type)
-                | SystemStreamItem::SymbolTableNull(ion_type)
-                | SystemStreamItem::Null(ion_type) => ion_type,
+                RawStreamItem::Value(ion_type) | RawStreamItem::Null(ion_type) => {
+                    if self.reader.depth() == 0 {
+                        self.in_symbol_table = self.is_symbol_table();
+                    }
+                    // System values are displayed like any other value, but they don't count
+                    // toward --skip-values or --limit-values.
+                    if self.in_symbol_table {
+                        top_level_index = None;
+                        record_kind = "symbol_table";
+                    } else {
+                        top_level_index = self.next_top_level_index();
+                        record_kind = "value";
+                    }
+                    ion_type
+                }
             };
-            // See if we've already processed `bytes_to_skip` bytes; if not, move to the next value.
             let complete_value_range = self.complete_value_range();
-            if complete_value_range.end <= self.bytes_to_skip {
+            if self.reader.depth() == 0 {
+                self.top_level_end = complete_value_range.end;
+            }
+            // Track the symbols that each local symbol table defines, even if it will be skipped.
+            let mut symbol_table_number = None;
+            if self.reader.depth() == 0
+                && record_kind == "symbol_table"
+                && ion_type == IonType::Struct
+                && !self.reader.is_null()
+            {
+                let table_number = self.add_symbol_table(&complete_value_range)?;
+                if self.options.show_symbols && self.options.format == InspectFormat::Table {
+                    symbol_table_number = Some(table_number);
+                }
+            }
+            // See if we've already processed `bytes_to_skip` bytes or `values_to_skip` values;
+            // if not, move to the next value.
+            if self
+                .options
+                .should_skip(&complete_value_range, top_level_index)
+            {
                 bytes_skipped_this_level += complete_value_range.len();
                 continue;
             }
 
-            // Saturating subtraction: if the result would underflow, the answer will be zero.
-            let bytes_processed = complete_value_range
-                .start
-                .saturating_sub(self.bytes_to_skip);
-            // See if we've already processed `limit_bytes`; if so, stop processing.
-            if bytes_processed >= self.limit_bytes {
-                let limit_message = if self.reader.depth() > 0 {
-                    "// --limit-bytes reached, stepping out."
-                } else {
-                    "// --limit-bytes reached, ending."
-                };
-                output(
-                    self.output,
-                    None,
-                    None,
-                    &self.indentation_buffer,
-                    "...",
-                    limit_message.dimmed(),
-                )?;
+            // See if we've already processed `limit_bytes` bytes or `limit_values` values; if so,
+            // stop processing.
+            if let Some(limit_flag) = self
+                .options
+                .limit_reached(&complete_value_range, top_level_index)
+            {
+                if self.options.format == InspectFormat::Table {
+                    self.text_buffer.clear();
+                    if self.reader.depth() > 0 {
+                        write!(self.text_buffer, "// {} reached, stepping out.", limit_flag)?;
+                    } else {
+                        write!(self.text_buffer, "// {} reached, ending.", limit_flag)?;
+                    }
+                    output(
+                        self.output,
+                        None,
+                        None,
+                        &self.indentation_buffer,
+                        "...",
+                        self.text_buffer.dimmed(),
+                    )?;
+                }
                 self.decrease_indentation();
                 return Ok(());
             }
 
             // We're no longer skip-scanning to `bytes_to_skip`. If we skipped values at this depth
             // to get to this point, make a note of it in the output.
-            if bytes_skipped_this_level > 0 {
+            if bytes_skipped_this_level > 0 && self.options.format == InspectFormat::Table {
                 self.text_buffer.clear();
                 write!(
                     &mut self.text_buffer,
@@ -350,15 +689,19 @@ impl<'a> IonInspector<'a> {
                     None,
                     &self.indentation_buffer,
                     "...",
-                    &self.text_buffer.dimmed(),
+                    self.text_buffer.dimmed(),
                 )?;
                 bytes_skipped_this_level = 0;
             }
 
-            self.write_field_if_present()?;
-            self.write_annotations_if_present()?;
-            // Print the value or, if it's a container, its opening delimiter: {, (, or [
-            self.write_value()?;
+            if self.options.format == InspectFormat::Table {
+                self.write_field_if_present()?;
+                self.write_annotations_if_present()?;
+                // Print the value or, if it's a container, its opening delimiter: {, (, or [
+                self.write_value()?;
+            } else {
+                self.write_record(record_kind, &complete_value_range)?;
+            }
 
             // If the current value is a container, step into it and inspect its contents.
             match ion_type {
@@ -366,11 +709,14 @@ impl<'a> IonInspector<'a> {
                     self.reader.step_in()?;
                     self.inspect_level()?;
                     self.reader.step_out()?;
+                    if self.options.format != InspectFormat::Table {
+                        continue;
+                    }
                     // Print the container's closing delimiter: }, ), or ]
                     self.text_buffer.clear();
-                    self.text_buffer.push_str(&closing_delimiter_for(ion_type));
+                    self.text_buffer.push_str(closing_delimiter_for(ion_type));
                     if ion_type != IonType::SExpression && self.reader.depth() > 0 {
-                        self.text_buffer.push_str(",");
+                        self.text_buffer.push(',');
                     }
                     output(
                         self.output,
@@ -380,6 +726,9 @@ impl<'a> IonInspector<'a> {
                         "",
                         &self.text_buffer,
                     )?;
+                    if let Some(table_number) = symbol_table_number {
+                        self.write_symbol_table(table_number)?;
+                    }
                 }
                 _ => {}
             }
@@ -389,6 +738,105 @@ impl<'a> IonInspector<'a> {
         Ok(())
     }
 
+    // Reads the local symbol table occupying `range` and records the symbol IDs it imports and
+    // defines. Returns the table's number.
+    fn add_symbol_table(&mut self, range: &Range<usize>) -> Result<usize> {
+        let (is_append, imports, symbols) = read_local_symbol_table(&self.input[range.clone()])
+            .with_context(|| {
+                format!("Could not read the symbol table at offset {}", range.start)
+            })?;
+        if !is_append {
+            // Only the system symbols remain.
+            self.symbol_sources.truncate(1);
+        }
+        for import in imports {
+            let first_id = self.symbol_sources.last().unwrap().ids().end;
+            self.symbol_sources.push(SymbolSource {
+                origin: SymbolOrigin::Shared {
+                    name: import.name,
+                    version: import.version,
+                },
+                first_id,
+                symbols: vec![None; import.max_id],
+            });
+        }
+        self.symbol_tables_seen += 1;
+        let first_id = self.symbol_sources.last().unwrap().ids().end;
+        self.symbol_sources.push(SymbolSource {
+            origin: SymbolOrigin::Local(self.symbol_tables_seen),
+            first_id,
+            symbols,
+        });
+        Ok(self.symbol_tables_seen)
+    }
+
+    // Renders the most recently added local symbol table: the ranges of symbol IDs it imports
+    // followed by a numbered list of the symbols it appends.
+    fn write_symbol_table(&mut self, table_number: usize) -> IonResult<()> {
+        let (table, imports) = self.symbol_sources.split_last().unwrap();
+        let mut lines = vec![format!(
+            "// Local symbol table #{}: {} imported, {} appended",
+            table_number,
+            table.first_id - 1,
+            table.symbols.len()
+        )];
+        // The first ID assigned to each symbol text, used to flag duplicates.
+        let mut first_ids: HashMap<&str, SymbolId> = HashMap::new();
+        for source in imports {
+            if source.symbols.is_empty() {
+                continue;
+            }
+            lines.push(format!(
+                "//   {:<11} imported from {}",
+                id_range_text(&source.ids()),
+                source.name()
+            ));
+            for (sid, symbol) in source.ids().zip(source.symbols.iter()) {
+                if let Some(text) = symbol {
+                    first_ids.entry(text).or_insert(sid);
+                }
+            }
+        }
+        for (sid, symbol) in table.ids().zip(table.symbols.iter()) {
+            let description = match symbol {
+                Some(text) => match first_ids.get(text.as_str()) {
+                    Some(first_id) => format!("'{}' (duplicate of ${})", text, first_id),
+                    None => {
+                        first_ids.insert(text, sid);
+                        format!("'{}'", text)
+                    }
+                },
+                None => "<unknown text>".to_string(),
+            };
+            lines.push(format!("//   {:<11} {}", format!("${}", sid), description));
+        }
+        for line in lines {
+            output(
+                self.output,
+                None,
+                None,
+                SYSTEM_EVENT_INDENTATION,
+                "",
+                line.dimmed(),
+            )?;
+        }
+        Ok(())
+    }
+
+    // Returns `$sid` followed by the symbol table that defined it if the user asked to see it.
+    fn sid_text(&self, sid: SymbolId) -> String {
+        if !self.options.show_symbols {
+            return format!("${}", sid);
+        }
+        let origin = self
+            .symbol_sources
+            .iter()
+            .find(|source| source.ids().contains(&sid))
+            .map(SymbolSource::name)
+            .unwrap_or_else(|| "undefined".to_string());
+        format!("${} ({})", sid, origin)
+    }
+
     fn increase_indentation(&mut self) {
         // Add a level's worth of indentation to the buffer.
         if self.reader.depth() > 0 {
@@ -409,37 +857,44 @@ impl<'a> IonInspector<'a> {
             // We're not in a struct; nothing to do.
             return Ok(());
         }
-        let field_token = self.reader.raw_field_name_token()?;
+        let field_token = self.reader.field_name()?;
         let field_id = field_token.local_sid().expect("No SID for field name.");
         self.hex_buffer.clear();
-        to_hex(
-            &mut self.hex_buffer,
-            self.reader.raw_field_id_bytes().unwrap(),
+        let field_id_bytes = self.reader.raw_field_id_bytes().unwrap();
+        to_hex(&mut self.hex_buffer, field_id_bytes);
+        self.role_buffer.clear();
+        push_roles(
+            &mut self.role_buffer,
+            ByteRole::FieldId,
+            field_id_bytes.len(),
         );
 
-        let field_name_result = self.reader.field_name();
-        let field_name = field_name_result
-            .as_ref()
-            .ok()
-            .and_then(|name| name.text())
-            .unwrap_or("<UNKNOWN>");
-
+        let field_name = symbol_text(&self.symbol_sources, field_id);
         self.text_buffer.clear();
-        write!(&mut self.text_buffer, "'{}':", field_name)?;
+        match field_name {
+            Some(Some(text)) => write!(&mut self.text_buffer, "'{}':", text)?,
+            Some(None) => write!(&mut self.text_buffer, "${}:", field_id)?,
+            None => write!(&mut self.text_buffer, "'<UNKNOWN>':")?,
+        }
 
+        let field_id_text = self.sid_text(field_id);
         self.color_buffer.clear();
-        write!(&mut self.color_buffer, " // ${}:", field_id)?;
+        write!(&mut self.color_buffer, " // {}:", field_id_text)?;
         write!(&mut self.text_buffer, "{}", &self.color_buffer.dimmed())?;
         output(
             self.output,
             self.reader.field_id_offset(),
             self.reader.field_id_length(),
             &self.indentation_buffer,
-            &self.hex_buffer,
+            HexColumn::new(
+                &self.hex_buffer,
+                &self.role_buffer,
+                self.options.label_bytes,
+            ),
             &self.text_buffer,
         )?;
 
-        if field_name_result.is_err() {
+        if field_name.is_none() {
             // If we had to write <UNKNOWN> for the field name above, return a fatal error now.
             return decoding_error(format!(
                 "Encountered a field ID (${}) with unknown text.",
@@ -451,32 +906,40 @@ impl<'a> IonInspector<'a> {
     }
 
     fn write_annotations_if_present(&mut self) -> IonResult<()> {
-        let num_annotations = self.reader.raw_annotations().count();
+        let num_annotations = self.reader.annotations().count();
         if num_annotations > 0 {
             self.hex_buffer.clear();
-            to_hex(
-                &mut self.hex_buffer,
-                self.reader.raw_annotations_bytes().unwrap(),
-            );
+            let annotations_bytes = self.reader.raw_annotations_bytes().unwrap();
+            to_hex(&mut self.hex_buffer, annotations_bytes);
+            self.role_buffer.clear();
+            push_annotation_wrapper_roles(&mut self.role_buffer, annotations_bytes);
 
+            let annotation_sids: Vec<SymbolId> = self
+                .reader
+                .annotations()
+                .map(|a| a.unwrap().local_sid().unwrap())
+                .collect();
             self.text_buffer.clear();
-            write!(&mut self.text_buffer, "'")?;
             join_into(
                 &mut self.text_buffer,
-                "'::'",
-                self.reader.annotations().map(|o| o.unwrap()),
+                "::",
+                annotation_sids
+                    .iter()
+                    .map(|sid| match symbol_text(&self.symbol_sources, *sid) {
+                        Some(Some(text)) => format!("'{}'", text),
+                        Some(None) => format!("${}", sid),
+                        None => "'<UNKNOWN>'".to_string(),
+                    }),
             );
-            write!(&mut self.text_buffer, "'::")?;
+            write!(&mut self.text_buffer, "::")?;
 
+            let annotation_ids: Vec<String> = annotation_sids
+                .iter()
+                .map(|sid| self.sid_text(*sid))
+                .collect();
             self.color_buffer.clear();
-            write!(&mut self.color_buffer, " // $")?;
-            join_into(
-                &mut self.color_buffer,
-                "::$",
-                self.reader
-                    .raw_annotations()
-                    .map(|a| a.local_sid().unwrap()),
-            );
+            write!(&mut self.color_buffer, " // ")?;
+            join_into(&mut self.color_buffer, "::", annotation_ids.iter());
             write!(&mut self.color_buffer, "::")?;
 
             write!(self.text_buffer, "{}", self.color_buffer.dimmed())?;
@@ -485,7 +948,11 @@ impl<'a> IonInspector<'a> {
                 self.reader.annotations_offset(),
                 self.reader.annotations_length(),
                 &self.indentation_buffer,
-                &self.hex_buffer,
+                HexColumn::new(
+                    &self.hex_buffer,
+                    &self.role_buffer,
+                    self.options.label_bytes,
+                ),
                 &self.text_buffer,
             )?;
         }
@@ -497,19 +964,42 @@ impl<'a> IonInspector<'a> {
         // Populates `self.text_buffer` with the Ion text representation of the current value
         // if it is a scalar. If the value is a container, format_value() will write the opening
         // delimiter of that container instead.
-        self.format_value()?;
+        let symbol_id = self.format_value()?;
+        if !self.reader.ion_type().unwrap().is_container() {
+            // If we're in a container, add a delimiting comma. Text Ion allows trailing commas, so
+            // we don't need to treat the last value as a special case.
+            if self.reader.depth() > 0 {
+                self.text_buffer.push(',');
+            }
+            if let Some(sid) = symbol_id {
+                let sid_text = self.sid_text(sid);
+                self.color_buffer.clear();
+                write!(self.color_buffer, " // {}", sid_text)?;
+                write!(self.text_buffer, "{}", self.color_buffer.dimmed())?;
+            }
+        }
 
         self.hex_buffer.clear();
-        to_hex(
-            &mut self.hex_buffer,
-            self.reader.raw_header_bytes().unwrap(),
+        let header_bytes = self.reader.raw_header_bytes().unwrap();
+        to_hex(&mut self.hex_buffer, header_bytes);
+        // The type descriptor may be followed by a VarUInt length.
+        self.role_buffer.clear();
+        self.role_buffer.push(ByteRole::TypeDescriptor);
+        push_roles(
+            &mut self.role_buffer,
+            ByteRole::Length,
+            header_bytes.len() - 1,
         );
         // Only write the bytes representing the body of the value if it is a scalar.
         // If it is a container, `inspect_level` will handle stepping into it and writing any
         // nested values.
         if !self.reader.ion_type().unwrap().is_container() {
-            self.hex_buffer.push(' ');
-            to_hex(&mut self.hex_buffer, self.reader.raw_value_bytes().unwrap());
+            let value_bytes = self.reader.raw_value_bytes().unwrap();
+            if !value_bytes.is_empty() {
+                self.hex_buffer.push(' ');
+                to_hex(&mut self.hex_buffer, value_bytes);
+                push_roles(&mut self.role_buffer, ByteRole::Body, value_bytes.len());
+            }
         }
 
         let length = self.reader.header_length() + self.reader.value_length();
@@ -518,12 +1008,68 @@ impl<'a> IonInspector<'a> {
             Some(self.reader.header_offset()),
             Some(length),
             &self.indentation_buffer,
-            &self.hex_buffer,
+            HexColumn::new(
+                &self.hex_buffer,
+                &self.role_buffer,
+                self.options.label_bytes,
+            ),
             &self.text_buffer,
         )
     }
 
-    fn format_value(&mut self) -> IonResult<()> {
+    // Writes a record describing the current value for the `ion` and `json` output formats.
+    fn write_record(
+        &mut self,
+        kind: &'static str,
+        complete_value_range: &Range<usize>,
+    ) -> Result<()> {
+        let header_bytes = self.reader.raw_header_bytes().unwrap();
+        let mut record = InspectRecord {
+            kind,
+            offset: complete_value_range.start,
+            length: complete_value_range.len(),
+            depth: self.reader.depth(),
+            ion_type: self.reader.ion_type(),
+            type_descriptor: Some(header_bytes[0]),
+            ..Default::default()
+        };
+        if header_bytes.len() > 1 {
+            record.length_bytes = Some(hex_string(&header_bytes[1..]));
+        }
+        if self.reader.parent_type() == Some(IonType::Struct) {
+            record.field_id = self.reader.field_name()?.local_sid();
+            record.field_id_bytes = self.reader.raw_field_id_bytes().map(hex_string);
+            record.field_name = record
+                .field_id
+                .and_then(|sid| symbol_text(&self.symbol_sources, sid).flatten())
+                .map(|text| text.to_string());
+        }
+        if self.reader.has_annotations() {
+            record.annotation_wrapper_bytes = self.reader.raw_annotations_bytes().map(hex_string);
+            record.annotation_ids = self
+                .reader
+                .annotations()
+                .filter_map(|a| a.ok().and_then(|a| a.local_sid()))
+                .collect();
+            record.annotations = record
+                .annotation_ids
+                .iter()
+                .filter_map(|sid| symbol_text(&self.symbol_sources, *sid).flatten())
+                .map(|text| text.to_string())
+                .collect();
+        }
+        if !self.reader.ion_type().unwrap().is_container() || self.reader.is_null() {
+            self.text_buffer.clear();
+            record.symbol_id = self.format_value()?;
+            record.value = Some(self.text_buffer.trim_start().to_string());
+        }
+        record.write(self.output, self.options.format)
+    }
+
+    // Populates `self.text_buffer` with the Ion text representation of the current value if it is
+    // a scalar, or with its opening delimiter if it is a container. If the value is a symbol,
+    // returns its symbol ID.
+    fn format_value(&mut self) -> IonResult<Option<SymbolId>> {
         use ion_rs::IonType::*;
 
         // Destructure `self` to get multiple simultaneous mutable references to its constituent
@@ -532,15 +1078,11 @@ impl<'a> IonInspector<'a> {
             ref mut reader,
             ref mut text_ion_writer,
             ref mut text_buffer,
-            ref mut color_buffer,
+            ref symbol_sources,
             ..
         } = self;
 
-        // If we need to write comments alongside any of the values, we'll add them here so we can
-        // colorize them separately.
-        let comment_buffer = color_buffer;
-        comment_buffer.clear();
-
+        let mut symbol_id = None;
         let writer = text_ion_writer; // Local alias for brevity.
         let ion_type = reader
             .ion_type()
@@ -556,15 +1098,14 @@ impl<'a> IonInspector<'a> {
                 Decimal => writer.write_decimal(&reader.read_decimal()?),
                 Timestamp => writer.write_timestamp(&reader.read_timestamp()?),
                 Symbol => {
-                    // TODO: Make this easier in the reader
-                    let symbol_token = reader.read_raw_symbol()?;
-                    let sid = symbol_token.local_sid().unwrap();
-                    let text = reader
-                        .symbol_table()
-                        .text_for(sid)
-                        .unwrap_or_else(|| panic!("Could not resolve text for symbol ID ${}", sid));
-                    write!(comment_buffer, " // ${}", sid)?;
-                    writer.write_symbol(text)
+                    let sid = reader.read_symbol()?.local_sid().unwrap();
+                    symbol_id = Some(sid);
+                    match symbol_text(symbol_sources, sid) {
+                        Some(Some(text)) => writer.write_symbol(text),
+                        // Symbols with unknown text are written as their ID, like `$10`.
+                        Some(None) => writer.write_symbol(sid),
+                        None => decoding_error(format!("Symbol ID ${} is not defined", sid)),
+                    }
                 }
                 String => reader.map_string(|s| writer.write_string(s))?,
                 Clob => reader.map_clob(|c| writer.write_clob(c))?,
@@ -573,15 +1114,15 @@ impl<'a> IonInspector<'a> {
                 // the appropriate opening delimiter.
                 List => {
                     write!(text_buffer, "[")?;
-                    return Ok(());
+                    return Ok(None);
                 }
                 SExpression => {
                     write!(text_buffer, "(")?;
-                    return Ok(());
+                    return Ok(None);
                 }
                 Struct => {
                     write!(text_buffer, "{{")?;
-                    return Ok(());
+                    return Ok(None);
                 }
             }?;
         }
@@ -590,117 +1131,959 @@ impl<'a> IonInspector<'a> {
         // The writer produces valid UTF-8, so there's no need to re-validate it.
         let value_text = unsafe { from_utf8_unchecked(writer.output().as_slice()) };
         write!(text_buffer, "{}", value_text.trim_end())?;
-        // If we're in a container, add a delimiting comma. Text Ion allows trailing commas, so we
-        // don't need to treat the last value as a special case.
-        if self.reader.depth() > 0 {
-            write!(text_buffer, ",")?;
-        }
-        write!(text_buffer, "{}", comment_buffer.dimmed())?;
         // Clear the writer's output Vec. We encode each scalar independently of one another.
         writer.output_mut().clear();
-        Ok(())
+        Ok(symbol_id)
     }
 }
 
-const COLUMN_DELIMITER: &str = " | ";
-const CHARS_PER_HEX_BYTE: usize = 3;
-const HEX_BYTES_PER_ROW: usize = 8;
-const HEX_COLUMN_SIZE: usize = HEX_BYTES_PER_ROW * CHARS_PER_HEX_BYTE;
-
-fn write_header(output: &mut OutputRef) -> IonResult<()> {
-    let line = "-".repeat(24 + 24 + 9 + 9 + (COLUMN_DELIMITER.len() * 3));
+const TEXT_IVM_TEXT: &str = "$ion_1_0";
 
-    writeln!(output, "{}", line)?;
-    write!(
-        output,
-        "{:^9}{}",
-        "Offset".bold().bright_white(),
-        COLUMN_DELIMITER
-    )?;
-    write!(
-        output,
-        "{:^9}{}",
-        "Length".bold().bright_white(),
-        COLUMN_DELIMITER
-    )?;
-    write!(
-        output,
-        "{:^24}{}",
-        "Binary Ion".bold().bright_white(),
-        COLUMN_DELIMITER
-    )?;
-    writeln!(output, "{:^24}", "Text Ion".bold().bright_white())?;
-    writeln!(output, "{}", line)?;
-    Ok(())
+// Text Ion doesn't have length prefixes, so the text inspector uses the number of bytes the reader
+// has consumed before and after each value to work out where that value lives in the input.
+struct TextIonInspector<'a> {
+    output: &'a mut OutputRef,
+    input: &'a [u8],
+    reader: RawTextReader<&'a [u8]>,
+    options: InspectorOptions,
+    // The number of top-level user values that have been encountered so far
+    user_values_seen: usize,
+    // The offset of the first byte of each line in the input, used to turn offsets into line/column
+    // pairs.
+    line_offsets: Vec<usize>,
+    // Maps the starting offset of each container to the offset just past its closing delimiter.
+    // Unlike binary Ion, the text encoding doesn't tell us how long a container is until we've
+    // read all of it, so these are found in a first pass over the data.
+    container_ends: HashMap<usize, usize>,
+    // Reusable buffer for formatting line/column spans
+    span_buffer: String,
+    // Reusable buffer for formatting text
+    text_buffer: String,
+    // Reusable buffer for tracking indentation
+    indentation_buffer: String,
+    // Text Ion writer for formatting scalar values
+    text_ion_writer: RawTextWriter<Vec<u8>>,
 }
 
-// Accepting a `T` allows us to pass in `&str`, `&String`, `&ColoredString`, etc as out text_column
-// TODO: This could be a method on IonInspector
-fn output<T: Display>(
-    output: &mut OutputRef,
-    offset: Option<usize>,
-    length: Option<usize>,
-    indentation: &str,
-    hex_column: &str,
-    text_column: T,
-) -> IonResult<()> {
-    // The current implementation always writes a single line of output for the offset, length,
-    // and text columns. Only the hex column can span multiple rows.
-    // TODO: It would be nice to allow important hex bytes (e.g. type descriptors or lengths)
-    //       to be color-coded. This complicates the output function, however, as the length
-    //       of a colored string is not the same as its display length. We would need to pass
-    //       uncolored strings to the output function paired with the desired color/style so
-    //       the output function could break the text into the necessary row lengths and then apply
-    //       the provided colors just before writing.
-
-    // Write the offset column
-    if let Some(offset) = offset {
-        write!(output, "{:9}{}", offset, COLUMN_DELIMITER)?;
-    } else {
-        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+impl<'a> TextIonInspector<'a> {
+    fn new<'b>(
+        input: &'b [u8],
+        out: &'b mut OutputRef,
+        options: InspectorOptions,
+    ) -> IonResult<TextIonInspector<'b>> {
+        let container_ends = find_container_ends(input)?;
+        let line_offsets = std::iter::once(0)
+            .chain(
+                input
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == b'\n')
+                    .map(|(offset, _)| offset + 1),
+            )
+            .collect();
+        let text_ion_writer = RawTextWriterBuilder::new()
+            .build(Vec::with_capacity(TEXT_WRITER_INITIAL_BUFFER_SIZE))?;
+        let inspector = TextIonInspector {
+            output: out,
+            input,
+            reader: RawTextReader::new(input),
+            options,
+            user_values_seen: 0,
+            line_offsets,
+            container_ends,
+            span_buffer: String::new(),
+            text_buffer: String::new(),
+            indentation_buffer: String::new(),
+            text_ion_writer,
+        };
+        Ok(inspector)
     }
 
-    // Write the length column
-    if let Some(length) = length {
-        write!(output, "{:9}{}", length, COLUMN_DELIMITER)?;
-    } else {
-        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
-    }
+    // Displays all of the values (however deeply nested) at the current level.
+    fn inspect_level(&mut self) -> Result<()> {
+        self.increase_indentation();
 
-    // If the hex string is short enough to fit in a single row...
-    if hex_column.len() < HEX_COLUMN_SIZE {
-        // ...print the hex string...
-        write!(output, "{}", hex_column)?;
-        // ...and then write enough padding spaces to fill the rest of the row.
-        for _ in 0..(HEX_COLUMN_SIZE - hex_column.len()) {
-            write!(output, " ")?;
-        }
-    } else {
-        // Otherwise, write the first row's worth of the hex string.
-        write!(output, "{}", &hex_column[..HEX_COLUMN_SIZE])?;
-    }
-    // Write a delimiter, the write the text Ion as the final column.
-    write!(output, "{}", COLUMN_DELIMITER)?;
-    write!(output, " ")?;
-    writeln!(output, "{}{}", indentation, text_column)?;
+        // Per-level bytes skipped are tracked so we can add them to the text Ion comments that
+        // appear each time some number of values is skipped.
+        let mut bytes_skipped_this_level = 0;
 
-    // Revisit our hex column. Write as many additional rows as needed.
-    let mut col_1_written = HEX_COLUMN_SIZE;
-    while col_1_written < hex_column.len() {
-        // Padding for offset column
-        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
-        // Padding for length column
-        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
-        let remaining_bytes = hex_column.len() - col_1_written;
-        let bytes_to_write = min(remaining_bytes, HEX_COLUMN_SIZE);
-        let next_slice_to_write = &hex_column[col_1_written..(col_1_written + bytes_to_write)];
-        write!(output, "{}", next_slice_to_write)?;
-        for _ in 0..(HEX_COLUMN_SIZE - bytes_to_write) {
-            write!(output, " ")?;
-        }
-        writeln!(output, "{}", COLUMN_DELIMITER)?;
-        col_1_written += HEX_COLUMN_SIZE;
-        // No need to write anything for the text column since it's the last one.
+        loop {
+            let bytes_read_before = self.reader.bytes_read();
+            let item = self.reader.next()?;
+            let ion_type = match item {
+                RawStreamItem::Nothing => break,
+                RawStreamItem::VersionMarker(_major, _minor) => {
+                    // The text reader rejects any IVM other than `$ion_1_0`.
+                    let range =
+                        consumed_range(self.input, bytes_read_before, self.reader.bytes_read());
+                    if self.options.format != InspectFormat::Table {
+                        let record = InspectRecord {
+                            kind: "version_marker",
+                            offset: range.start,
+                            length: range.len(),
+                            span: Some(self.span_for(&range)),
+                            value: Some(TEXT_IVM_TEXT.to_string()),
+                            ..Default::default()
+                        };
+                        record.write(self.output, self.options.format)?;
+                        continue;
+                    }
+                    self.write_span(&range);
+                    output(
+                        self.output,
+                        Some(range.start),
+                        Some(range.len()),
+                        SYSTEM_EVENT_INDENTATION,
+                        self.span_buffer.as_str(),
+                        format!("{} {}", TEXT_IVM_TEXT, IVM_TEXT.dimmed()),
+                    )?;
+                    continue;
+                }
+                RawStreamItem::Value(ion_type) | RawStreamItem::Null(ion_type) => ion_type,
+            };
+            // Symbol tables are displayed like any other value, but they don't count toward
+            // --skip-values or --limit-values.
+            let (top_level_index, record_kind) = if self.is_symbol_table() {
+                (None, "symbol_table")
+            } else {
+                (self.next_top_level_index(), "value")
+            };
+
+            // The range of bytes that contains the current value and its annotations/field name if
+            // applicable.
+            let mut complete_value_range =
+                consumed_range(self.input, bytes_read_before, self.reader.bytes_read());
+            if ion_type.is_container() && !self.reader.is_null() {
+                complete_value_range.end = self.container_ends[&complete_value_range.start];
+            }
+
+            // See if we've already processed `bytes_to_skip` bytes or `values_to_skip` values;
+            // if not, move to the next value.
+            if self
+                .options
+                .should_skip(&complete_value_range, top_level_index)
+            {
+                bytes_skipped_this_level += complete_value_range.len();
+                // Calling `next()` would also skip the rest of the container, but the bytes it
+                // consumed would then be attributed to the following value.
+                if ion_type.is_container() && !self.reader.is_null() {
+                    self.reader.step_in()?;
+                    self.reader.step_out()?;
+                }
+                continue;
+            }
+
+            // See if we've already processed `limit_bytes` bytes or `limit_values` values; if so,
+            // stop processing.
+            if let Some(limit_flag) = self
+                .options
+                .limit_reached(&complete_value_range, top_level_index)
+            {
+                if self.options.format == InspectFormat::Table {
+                    self.text_buffer.clear();
+                    if self.reader.depth() > 0 {
+                        write!(self.text_buffer, "// {} reached, stepping out.", limit_flag)?;
+                    } else {
+                        write!(self.text_buffer, "// {} reached, ending.", limit_flag)?;
+                    }
+                    output(
+                        self.output,
+                        None,
+                        None,
+                        &self.indentation_buffer,
+                        "...",
+                        self.text_buffer.dimmed(),
+                    )?;
+                }
+                self.decrease_indentation();
+                return Ok(());
+            }
+
+            // We're no longer skip-scanning to `bytes_to_skip`. If we skipped values at this depth
+            // to get to this point, make a note of it in the output.
+            if bytes_skipped_this_level > 0 && self.options.format == InspectFormat::Table {
+                self.text_buffer.clear();
+                write!(
+                    &mut self.text_buffer,
+                    "// Skipped {} bytes of user-level data",
+                    bytes_skipped_this_level
+                )?;
+                output(
+                    self.output,
+                    None,
+                    None,
+                    &self.indentation_buffer,
+                    "...",
+                    self.text_buffer.dimmed(),
+                )?;
+                bytes_skipped_this_level = 0;
+            }
+
+            if self.options.format == InspectFormat::Table {
+                // Print the field name, annotations, and the value or, if it's a container, its
+                // opening delimiter: {, (, or [
+                self.text_buffer.clear();
+                self.write_field_if_present()?;
+                self.write_annotations_if_present()?;
+                self.format_value()?;
+                // If we're in a container, add a delimiting comma. Text Ion allows trailing
+                // commas, so we don't need to treat the last value as a special case.
+                if (!ion_type.is_container() || self.reader.is_null())
+                    && self.reader.depth() > 0
+                    && self.reader.parent_type() != Some(IonType::SExpression)
+                {
+                    self.text_buffer.push(',');
+                }
+                self.write_span(&complete_value_range);
+                output(
+                    self.output,
+                    Some(complete_value_range.start),
+                    Some(complete_value_range.len()),
+                    &self.indentation_buffer,
+                    self.span_buffer.as_str(),
+                    &self.text_buffer,
+                )?;
+            } else {
+                self.write_record(record_kind, &complete_value_range)?;
+            }
+
+            // If the current value is a container, step into it and inspect its contents.
+            if ion_type.is_container() && !self.reader.is_null() {
+                self.reader.step_in()?;
+                self.inspect_level()?;
+                self.reader.step_out()?;
+                if self.options.format != InspectFormat::Table {
+                    continue;
+                }
+                // Print the container's closing delimiter: }, ), or ]
+                self.text_buffer.clear();
+                self.text_buffer.push_str(closing_delimiter_for(ion_type));
+                if ion_type != IonType::SExpression && self.reader.depth() > 0 {
+                    self.text_buffer.push(',');
+                }
+                let closing_delimiter_range =
+                    (complete_value_range.end - 1)..complete_value_range.end;
+                self.write_span(&closing_delimiter_range);
+                output(
+                    self.output,
+                    Some(closing_delimiter_range.start),
+                    Some(closing_delimiter_range.len()),
+                    &self.indentation_buffer,
+                    self.span_buffer.as_str(),
+                    &self.text_buffer,
+                )?;
+            }
+        }
+
+        self.decrease_indentation();
+        Ok(())
+    }
+
+    fn increase_indentation(&mut self) {
+        // Add a level's worth of indentation to the buffer.
+        if self.reader.depth() > 0 {
+            self.indentation_buffer.push_str(LEVEL_INDENTATION);
+        }
+    }
+
+    fn decrease_indentation(&mut self) {
+        // Remove a level's worth of indentation from the buffer.
+        if self.reader.depth() > 0 {
+            let new_length = self.indentation_buffer.len() - LEVEL_INDENTATION.len();
+            self.indentation_buffer.truncate(new_length);
+        }
+    }
+
+    // If the reader is at the top level, returns the index of the user value on which it is
+    // parked and advances the count of user values seen.
+    fn next_top_level_index(&mut self) -> Option<usize> {
+        if self.reader.depth() > 0 {
+            return None;
+        }
+        self.user_values_seen += 1;
+        Some(self.user_values_seen - 1)
+    }
+
+    // Returns true if the reader is parked on a local symbol table: a top-level struct whose first
+    // annotation is `$ion_symbol_table`.
+    fn is_symbol_table(&self) -> bool {
+        self.reader.depth() == 0
+            && self.reader.ion_type() == Some(IonType::Struct)
+            && matches!(
+                self.reader.annotations().next(),
+                Some(Ok(RawSymbolToken::Text(text))) if text == "$ion_symbol_table"
+            )
+    }
+
+    // Writes a record describing the current value for the `ion` and `json` output formats.
+    fn write_record(
+        &mut self,
+        kind: &'static str,
+        complete_value_range: &Range<usize>,
+    ) -> Result<()> {
+        let mut record = InspectRecord {
+            kind,
+            offset: complete_value_range.start,
+            length: complete_value_range.len(),
+            depth: self.reader.depth(),
+            ion_type: self.reader.ion_type(),
+            span: Some(self.span_for(complete_value_range)),
+            ..Default::default()
+        };
+        if self.reader.parent_type() == Some(IonType::Struct) {
+            let field_name = self.reader.field_name()?;
+            record.field_id = field_name.local_sid();
+            record.field_name = field_name.text().map(|text| text.to_string());
+        }
+        for annotation in self.reader.annotations() {
+            match annotation? {
+                RawSymbolToken::SymbolId(sid) => record.annotation_ids.push(sid),
+                RawSymbolToken::Text(text) => record.annotations.push(text),
+            }
+        }
+        if self.reader.ion_type() == Some(IonType::Symbol) && !self.reader.is_null() {
+            record.symbol_id = self.reader.read_symbol()?.local_sid();
+        }
+        if !self.reader.ion_type().unwrap().is_container() || self.reader.is_null() {
+            self.text_buffer.clear();
+            self.format_value()?;
+            record.value = Some(self.text_buffer.clone());
+        }
+        record.write(self.output, self.options.format)
+    }
+
+    // Returns the 1-based line and column numbers of the first and last bytes in the given range.
+    fn span_for(&self, range: &Range<usize>) -> [usize; 4] {
+        let (start_line, start_column) = self.line_and_column(range.start);
+        let (end_line, end_column) = self.line_and_column(range.end.saturating_sub(1));
+        [start_line, start_column, end_line, end_column]
+    }
+
+    // Populates `self.span_buffer` with the 1-based line and column numbers of the first and last
+    // bytes in the given range.
+    fn write_span(&mut self, range: &Range<usize>) {
+        let [start_line, start_column, end_line, end_column] = self.span_for(range);
+        self.span_buffer.clear();
+        if range.len() <= 1 {
+            write!(self.span_buffer, "{}:{}", start_line, start_column).unwrap();
+        } else {
+            write!(
+                self.span_buffer,
+                "{}:{}-{}:{}",
+                start_line, start_column, end_line, end_column
+            )
+            .unwrap();
+        }
+    }
+
+    fn line_and_column(&self, offset: usize) -> (usize, usize) {
+        // The number of line starts at or before `offset` is the (1-based) line number.
+        let line = self
+            .line_offsets
+            .partition_point(|line_start| *line_start <= offset);
+        let column = offset - self.line_offsets[line - 1] + 1;
+        (line, column)
+    }
+
+    fn write_field_if_present(&mut self) -> IonResult<()> {
+        if self.reader.parent_type() != Some(IonType::Struct) {
+            // We're not in a struct; nothing to do.
+            return Ok(());
+        }
+        let field_name = self.reader.field_name()?;
+        write!(&mut self.text_buffer, "{}: ", raw_token_text(&field_name))?;
+        Ok(())
+    }
+
+    fn write_annotations_if_present(&mut self) -> IonResult<()> {
+        for annotation in self.reader.annotations() {
+            write!(&mut self.text_buffer, "{}::", raw_token_text(&annotation?))?;
+        }
+        Ok(())
+    }
+
+    // Appends the Ion text representation of the current value to `self.text_buffer` if it is a
+    // scalar. If the value is a container, the opening delimiter of that container is appended
+    // instead.
+    fn format_value(&mut self) -> IonResult<()> {
+        use ion_rs::IonType::*;
+
+        let TextIonInspector {
+            ref mut reader,
+            ref mut text_ion_writer,
+            ref mut text_buffer,
+            ..
+        } = self;
+
+        let writer = text_ion_writer; // Local alias for brevity.
+        let ion_type = reader
+            .ion_type()
+            .expect("format_value() called when reader was exhausted");
+        if reader.is_null() {
+            writer.write_null(ion_type)?;
+        } else {
+            match ion_type {
+                Null => writer.write_null(ion_type),
+                Boolean => writer.write_bool(reader.read_bool()?),
+                Integer => writer.write_integer(&reader.read_integer()?),
+                Float => writer.write_f64(reader.read_f64()?),
+                Decimal => writer.write_decimal(&reader.read_decimal()?),
+                Timestamp => writer.write_timestamp(&reader.read_timestamp()?),
+                Symbol => writer.write_symbol(reader.read_symbol()?),
+                String => reader.map_string(|s| writer.write_string(s))?,
+                Clob => reader.map_clob(|c| writer.write_clob(c))?,
+                Blob => reader.map_blob(|b| writer.write_blob(b))?,
+                List => {
+                    write!(text_buffer, "[")?;
+                    return Ok(());
+                }
+                SExpression => {
+                    write!(text_buffer, "(")?;
+                    return Ok(());
+                }
+                Struct => {
+                    write!(text_buffer, "{{")?;
+                    return Ok(());
+                }
+            }?;
+        }
+        // This is writing to a Vec, so flush() will always succeed.
+        let _ = writer.flush();
+        // The writer produces valid UTF-8, so there's no need to re-validate it.
+        let value_text = unsafe { from_utf8_unchecked(writer.output().as_slice()) };
+        // The writer separates each value from the one before it with whitespace; trim it off.
+        write!(text_buffer, "{}", value_text.trim())?;
+        // Clear the writer's output Vec. We encode each scalar independently of one another.
+        writer.output_mut().clear();
+        Ok(())
+    }
+}
+
+// Reads through all of the text Ion in `input`, recording the range of bytes occupied by each
+// container in the stream.
+fn find_container_ends(input: &[u8]) -> IonResult<HashMap<usize, usize>> {
+    fn visit_level(
+        input: &[u8],
+        reader: &mut RawTextReader<&[u8]>,
+        container_ends: &mut HashMap<usize, usize>,
+    ) -> IonResult<()> {
+        loop {
+            let bytes_read_before = reader.bytes_read();
+            match reader.next()? {
+                RawStreamItem::Nothing => return Ok(()),
+                RawStreamItem::Value(ion_type) if ion_type.is_container() => {
+                    let start = consumed_range(input, bytes_read_before, reader.bytes_read()).start;
+                    reader.step_in()?;
+                    visit_level(input, reader, container_ends)?;
+                    reader.step_out()?;
+                    // After stepping out, the reader has consumed the closing delimiter (and, if
+                    // the container was nested, the delimiter that followed it).
+                    let end = consumed_range(input, start, reader.bytes_read()).end;
+                    container_ends.insert(start, end);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut container_ends = HashMap::new();
+    let mut reader = RawTextReader::new(input);
+    visit_level(input, &mut reader, &mut container_ends)?;
+    Ok(container_ends)
+}
+
+// Given the offsets reported by the text reader before and after it advanced, returns the range of
+// bytes that holds the item it found. The reader also consumes whitespace, comments, and delimiting
+// commas around each item; those are not included in the range.
+fn consumed_range(input: &[u8], bytes_read_before: usize, bytes_read_after: usize) -> Range<usize> {
+    // When the last value in the stream can only be recognized at the end of the input (e.g. a
+    // trailing integer), the reader doesn't count the bytes it consumed. In that case, the value
+    // extends to the end of the input.
+    let end = if bytes_read_after > bytes_read_before {
+        min(bytes_read_after, input.len())
+    } else {
+        input.len()
+    };
+    // The input was validated as UTF-8 before the inspector was created.
+    let text = unsafe { from_utf8_unchecked(&input[bytes_read_before..end]) };
+    let leading_trivia_length = text.len() - skip_leading_trivia(text).len();
+    let start = bytes_read_before + leading_trivia_length;
+    let end = start + trim_trailing_trivia(&text[leading_trivia_length..]).len();
+    start..end
+}
+
+// Returns the given text with any leading whitespace and comments removed.
+fn skip_leading_trivia(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        if let Some(rest) = text.strip_prefix("//") {
+            text = rest.split_once('\n').map(|(_, rest)| rest).unwrap_or("");
+        } else if let Some(rest) = text.strip_prefix("/*") {
+            text = rest.split_once("*/").map(|(_, rest)| rest).unwrap_or("");
+        } else {
+            return text;
+        }
+    }
+}
+
+// Returns the given text with any trailing whitespace, comments, and commas removed. Comment
+// markers inside of strings, quoted symbols, and lobs are not treated as comments.
+fn trim_trailing_trivia(text: &str) -> &str {
+    let bytes = text.as_bytes();
+    // The offset just past the last byte that was not trivia.
+    let mut significant_end = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let remaining = &bytes[index..];
+        // Each of these is either a quoted region or a comment, followed by the sequence that
+        // ends it and whether its contents are part of the value.
+        let (region_end, is_significant) = if remaining.starts_with(b"'''") {
+            (find_closing(bytes, index + 3, b"'''", true), true)
+        } else if remaining.starts_with(b"{{") {
+            (find_closing(bytes, index + 2, b"}}", true), true)
+        } else if remaining[0] == b'"' || remaining[0] == b'\'' {
+            (find_closing(bytes, index + 1, &remaining[..1], true), true)
+        } else if remaining.starts_with(b"//") {
+            (find_closing(bytes, index + 2, b"\n", false), false)
+        } else if remaining.starts_with(b"/*") {
+            (find_closing(bytes, index + 2, b"*/", false), false)
+        } else {
+            let byte = remaining[0];
+            (index + 1, !byte.is_ascii_whitespace() && byte != b',')
+        };
+        if is_significant {
+            significant_end = region_end;
+        }
+        index = region_end;
+    }
+    &text[..significant_end]
+}
+
+// Returns the offset just past the first occurrence of `closing` at or after `index`. If
+// `allow_escapes` is true, backslash-escaped bytes are skipped over. If `closing` is never found,
+// returns the length of `bytes`.
+fn find_closing(bytes: &[u8], mut index: usize, closing: &[u8], allow_escapes: bool) -> usize {
+    while index < bytes.len() {
+        if allow_escapes && bytes[index] == b'\\' {
+            index += 2;
+        } else if bytes[index..].starts_with(closing) {
+            return index + closing.len();
+        } else {
+            index += 1;
+        }
+    }
+    bytes.len()
+}
+
+// Returns the text of the given symbol token, formatted as a quoted symbol, or `$<sid>` if the
+// token only has a symbol ID.
+fn raw_token_text(token: &RawSymbolToken) -> String {
+    match token {
+        RawSymbolToken::Text(text) => format!("'{}'", text),
+        RawSymbolToken::SymbolId(sid) => format!("${}", sid),
+    }
+}
+
+// Describes a single encoded item (an IVM or a value) for the `ion` and `json` output formats.
+// Fields that don't apply to the item or to the input's encoding are omitted from the output.
+#[derive(Default)]
+struct InspectRecord {
+    // One of `version_marker`, `symbol_table`, or `value`
+    kind: &'static str,
+    // The offset and length of the item, including its field ID and annotations if applicable
+    offset: usize,
+    length: usize,
+    depth: usize,
+    ion_type: Option<IonType>,
+    // The 1-based starting line, starting column, ending line, and ending column of a text item
+    span: Option<[usize; 4]>,
+    // The encoding details below are only available for binary items. Byte sequences are
+    // formatted as space-separated hex.
+    type_descriptor: Option<u8>,
+    length_bytes: Option<String>,
+    field_id_bytes: Option<String>,
+    annotation_wrapper_bytes: Option<String>,
+    field_id: Option<SymbolId>,
+    field_name: Option<String>,
+    annotation_ids: Vec<SymbolId>,
+    annotations: Vec<String>,
+    symbol_id: Option<SymbolId>,
+    // The text Ion representation of a scalar value
+    value: Option<String>,
+}
+
+// The types of values that can appear in an `InspectRecord`'s fields.
+enum RecordField {
+    Integer(usize),
+    Symbol(String),
+    String(String),
+    Integers(Vec<usize>),
+    Strings(Vec<String>),
+    Struct(Vec<(&'static str, RecordField)>),
+}
+
+impl InspectRecord {
+    // Returns the record's populated fields in the order they should be written.
+    fn fields(&self) -> Vec<(&'static str, RecordField)> {
+        use RecordField::*;
+        let mut fields = vec![
+            ("kind", Symbol(self.kind.to_string())),
+            ("offset", Integer(self.offset)),
+            ("length", Integer(self.length)),
+            ("depth", Integer(self.depth)),
+        ];
+        if let Some(ion_type) = self.ion_type {
+            fields.push(("ion_type", Symbol(ion_type.to_string())));
+        }
+        if let Some([start_line, start_column, end_line, end_column]) = self.span {
+            fields.push((
+                "span",
+                Struct(vec![
+                    ("start_line", Integer(start_line)),
+                    ("start_column", Integer(start_column)),
+                    ("end_line", Integer(end_line)),
+                    ("end_column", Integer(end_column)),
+                ]),
+            ));
+        }
+        if let Some(type_descriptor) = self.type_descriptor {
+            fields.push(("type_descriptor", Integer(type_descriptor as usize)));
+        }
+        let optional_fields = [
+            ("length_bytes", &self.length_bytes),
+            ("field_id_bytes", &self.field_id_bytes),
+            ("annotation_wrapper_bytes", &self.annotation_wrapper_bytes),
+        ];
+        for (name, bytes) in optional_fields {
+            if let Some(bytes) = bytes {
+                fields.push((name, String(bytes.clone())));
+            }
+        }
+        if let Some(field_id) = self.field_id {
+            fields.push(("field_id", Integer(field_id)));
+        }
+        if let Some(field_name) = &self.field_name {
+            fields.push(("field_name", String(field_name.clone())));
+        }
+        if !self.annotation_ids.is_empty() {
+            fields.push(("annotation_ids", Integers(self.annotation_ids.clone())));
+        }
+        if !self.annotations.is_empty() {
+            fields.push(("annotations", Strings(self.annotations.clone())));
+        }
+        if let Some(symbol_id) = self.symbol_id {
+            fields.push(("symbol_id", Integer(symbol_id)));
+        }
+        if let Some(value) = &self.value {
+            fields.push(("value", String(value.clone())));
+        }
+        fields
+    }
+
+    // Writes the record to `output` as a single line of text in the requested format.
+    fn write(&self, output: &mut OutputRef, format: InspectFormat) -> Result<()> {
+        let fields = self.fields();
+        match format {
+            InspectFormat::Ion => {
+                let mut writer = TextWriterBuilder::lines().build(&mut *output)?;
+                write_ion_struct(&mut writer, &fields)?;
+                writer.flush()?;
+                // Each record gets its own writer, so the writer doesn't know to separate it from
+                // the record that follows.
+                drop(writer);
+                writeln!(output)?;
+            }
+            InspectFormat::Json => {
+                writeln!(output, "{}", json_object(&fields))?;
+            }
+            InspectFormat::Table => {
+                unreachable!("records are not written in the table format")
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_ion_struct<W: IonWriter>(
+    writer: &mut W,
+    fields: &[(&'static str, RecordField)],
+) -> IonResult<()> {
+    writer.step_in(IonType::Struct)?;
+    for (name, field) in fields {
+        writer.set_field_name(*name);
+        match field {
+            RecordField::Integer(value) => writer.write_i64(*value as i64)?,
+            RecordField::Symbol(value) => writer.write_symbol(value.as_str())?,
+            RecordField::String(value) => writer.write_string(value)?,
+            RecordField::Integers(values) => {
+                writer.step_in(IonType::List)?;
+                for value in values {
+                    writer.write_i64(*value as i64)?;
+                }
+                writer.step_out()?;
+            }
+            RecordField::Strings(values) => {
+                writer.step_in(IonType::List)?;
+                for value in values {
+                    writer.write_string(value)?;
+                }
+                writer.step_out()?;
+            }
+            RecordField::Struct(fields) => write_ion_struct(writer, fields)?,
+        }
+    }
+    writer.step_out()
+}
+
+fn json_object(fields: &[(&'static str, RecordField)]) -> JsonValue {
+    let mut map = Map::new();
+    for (name, field) in fields {
+        let value = match field {
+            RecordField::Integer(value) => JsonValue::from(*value),
+            RecordField::Symbol(value) | RecordField::String(value) => {
+                JsonValue::from(value.as_str())
+            }
+            RecordField::Integers(values) => JsonValue::from(values.clone()),
+            RecordField::Strings(values) => JsonValue::from(values.clone()),
+            RecordField::Struct(fields) => json_object(fields),
+        };
+        map.insert(name.to_string(), value);
+    }
+    JsonValue::Object(map)
+}
+
+const COLUMN_DELIMITER: &str = " | ";
+const CHARS_PER_HEX_BYTE: usize = 3;
+const HEX_BYTES_PER_ROW: usize = 8;
+const HEX_COLUMN_SIZE: usize = HEX_BYTES_PER_ROW * CHARS_PER_HEX_BYTE;
+
+// The title of the third column depends on whether the input is binary or text Ion.
+const BINARY_COLUMN_TITLE: &str = "Binary Ion";
+const TEXT_COLUMN_TITLE: &str = "Line:Column Span";
+
+fn write_header(output: &mut OutputRef, encoding_column_title: &str) -> IonResult<()> {
+    let line = "-".repeat(24 + 24 + 9 + 9 + (COLUMN_DELIMITER.len() * 3));
+
+    writeln!(output, "{}", line)?;
+    write!(
+        output,
+        "{:^9}{}",
+        "Offset".bold().bright_white(),
+        COLUMN_DELIMITER
+    )?;
+    write!(
+        output,
+        "{:^9}{}",
+        "Length".bold().bright_white(),
+        COLUMN_DELIMITER
+    )?;
+    write!(
+        output,
+        "{:^24}{}",
+        encoding_column_title.bold().bright_white(),
+        COLUMN_DELIMITER
+    )?;
+    writeln!(output, "{:^24}", "Text Ion".bold().bright_white())?;
+    writeln!(output, "{}", line)?;
+    Ok(())
+}
+
+// The role that a byte plays in the binary encoding of a value. The inspector uses these to
+// color-code (and optionally label) the bytes in the hex column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteRole {
+    TypeDescriptor,
+    Length,
+    FieldId,
+    AnnotationWrapper,
+    AnnotationSymbolId,
+    Body,
+}
+
+impl ByteRole {
+    // A two-character label that fits beneath a byte in the hex column.
+    fn label(&self) -> &'static str {
+        use ByteRole::*;
+        match self {
+            TypeDescriptor => "td",
+            Length => "ln",
+            FieldId => "fi",
+            AnnotationWrapper => "aw",
+            AnnotationSymbolId => "as",
+            Body => "bd",
+        }
+    }
+
+    fn colorize(&self, text: &str) -> ColoredString {
+        use ByteRole::*;
+        match self {
+            TypeDescriptor => text.bright_yellow().bold(),
+            Length => text.cyan(),
+            FieldId => text.green(),
+            AnnotationWrapper => text.magenta(),
+            AnnotationSymbolId => text.bright_magenta(),
+            Body => text.normal(),
+        }
+    }
+}
+
+fn push_roles(roles: &mut Vec<ByteRole>, role: ByteRole, count: usize) {
+    roles.extend(std::iter::repeat_n(role, count));
+}
+
+// An annotation wrapper begins with a type descriptor, an optional VarUInt length (if the length
+// didn't fit in the type descriptor), and the VarUInt length of the annotation symbol IDs. The
+// remaining bytes are the annotation symbol IDs themselves.
+fn push_annotation_wrapper_roles(roles: &mut Vec<ByteRole>, annotations_bytes: &[u8]) {
+    let mut wrapper_length = 1;
+    if annotations_bytes[0] & 0x0F == 0x0E {
+        wrapper_length += var_uint_length(&annotations_bytes[wrapper_length..]);
+    }
+    wrapper_length += var_uint_length(&annotations_bytes[wrapper_length..]);
+    let wrapper_length = min(wrapper_length, annotations_bytes.len());
+    push_roles(roles, ByteRole::AnnotationWrapper, wrapper_length);
+    push_roles(
+        roles,
+        ByteRole::AnnotationSymbolId,
+        annotations_bytes.len() - wrapper_length,
+    );
+}
+
+// Returns the number of bytes in the VarUInt at the beginning of `bytes`. The final byte of a
+// VarUInt is the one with its high bit set.
+fn var_uint_length(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|byte| byte & 0x80 != 0)
+        .map(|index| index + 1)
+        .unwrap_or(bytes.len())
+}
+
+// The contents of the hex column. This is usually a string of hex bytes, each paired with the
+// role it plays in the encoding. It can also be some other text (like `...`) whose bytes have
+// no roles. Keeping the text and its styling separate allows `output` to break the text into
+// rows by its display length and apply colors just before writing.
+#[derive(Clone, Copy)]
+struct HexColumn<'a> {
+    text: &'a str,
+    // Either empty or one role per hex byte in `text`
+    roles: &'a [ByteRole],
+    // Whether each row of bytes should be followed by a row of role labels
+    show_labels: bool,
+}
+
+impl<'a> HexColumn<'a> {
+    fn new(text: &'a str, roles: &'a [ByteRole], show_labels: bool) -> Self {
+        HexColumn {
+            text,
+            roles,
+            show_labels,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        let row_count = if self.roles.is_empty() {
+            self.text.len().div_ceil(HEX_COLUMN_SIZE)
+        } else {
+            self.roles.len().div_ceil(HEX_BYTES_PER_ROW)
+        };
+        // Even an empty column occupies a row.
+        row_count.max(1)
+    }
+
+    // Writes the requested row of the column, padded to the full width of the column.
+    fn write_row(&self, output: &mut OutputRef, row: usize) -> IonResult<()> {
+        let written = if self.roles.is_empty() {
+            let start = min(row * HEX_COLUMN_SIZE, self.text.len());
+            let end = min(start + HEX_COLUMN_SIZE, self.text.len());
+            write!(output, "{}", &self.text[start..end])?;
+            end - start
+        } else {
+            let start = row * HEX_BYTES_PER_ROW;
+            let end = min(start + HEX_BYTES_PER_ROW, self.roles.len());
+            for (index, role) in self.roles[start..end].iter().enumerate() {
+                let hex_start = (start + index) * CHARS_PER_HEX_BYTE;
+                let hex_byte = &self.text[hex_start..hex_start + 2];
+                write!(output, "{} ", role.colorize(hex_byte))?;
+            }
+            (end - start) * CHARS_PER_HEX_BYTE
+        };
+        write!(output, "{:1$}", "", HEX_COLUMN_SIZE - written)?;
+        Ok(())
+    }
+
+    // If labels were requested, writes a row of labels for the bytes in the requested row.
+    fn write_labels(&self, output: &mut OutputRef, row: usize) -> IonResult<()> {
+        if !self.show_labels || self.roles.is_empty() {
+            return Ok(());
+        }
+        // Padding for offset and length columns
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+        let start = row * HEX_BYTES_PER_ROW;
+        let end = min(start + HEX_BYTES_PER_ROW, self.roles.len());
+        for role in &self.roles[start..end] {
+            write!(output, "{} ", role.colorize(role.label()))?;
+        }
+        let written = (end - start) * CHARS_PER_HEX_BYTE;
+        write!(output, "{:1$}", "", HEX_COLUMN_SIZE - written)?;
+        writeln!(output, "{}", COLUMN_DELIMITER)?;
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for HexColumn<'a> {
+    fn from(text: &'a str) -> Self {
+        HexColumn::new(text, &[], false)
+    }
+}
+
+// Accepting a `T` allows us to pass in `&str`, `&String`, `&ColoredString`, etc as out text_column
+// TODO: This could be a method on IonInspector
+fn output<'h, H: Into<HexColumn<'h>>, T: Display>(
+    output: &mut OutputRef,
+    offset: Option<usize>,
+    length: Option<usize>,
+    indentation: &str,
+    hex_column: H,
+    text_column: T,
+) -> IonResult<()> {
+    // The current implementation always writes a single line of output for the offset, length,
+    // and text columns. Only the hex column can span multiple rows.
+    let hex_column = hex_column.into();
+
+    // Write the offset column
+    if let Some(offset) = offset {
+        write!(output, "{:9}{}", offset, COLUMN_DELIMITER)?;
+    } else {
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+    }
+
+    // Write the length column
+    if let Some(length) = length {
+        write!(output, "{:9}{}", length, COLUMN_DELIMITER)?;
+    } else {
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+    }
+
+    // Write the first row's worth of the hex column.
+    hex_column.write_row(output, 0)?;
+    // Write a delimiter, the write the text Ion as the final column.
+    write!(output, "{}", COLUMN_DELIMITER)?;
+    write!(output, " ")?;
+    writeln!(output, "{}{}", indentation, text_column)?;
+    hex_column.write_labels(output, 0)?;
+
+    // Revisit our hex column. Write as many additional rows as needed.
+    for row in 1..hex_column.row_count() {
+        // Padding for offset column
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+        // Padding for length column
+        write!(output, "{:9}{}", "", COLUMN_DELIMITER)?;
+        hex_column.write_row(output, row)?;
+        writeln!(output, "{}", COLUMN_DELIMITER)?;
+        hex_column.write_labels(output, row)?;
+        // No need to write anything for the text column since it's the last one.
     }
     Ok(())
 }
@@ -714,6 +2097,117 @@ fn closing_delimiter_for(container_type: IonType) -> &'static str {
     }
 }
 
+fn hex_string(bytes: &[u8]) -> String {
+    let mut buffer = String::new();
+    to_hex(&mut buffer, bytes);
+    buffer
+}
+
+// Reads the `imports` and `symbols` fields of the binary local symbol table in `bytes`. Returns
+// whether the table appends to the current symbol table, the shared tables it imports, and the
+// symbols it declares.
+fn read_local_symbol_table(bytes: &[u8]) -> Result<(bool, Vec<SharedImport>, Vec<Option<String>>)> {
+    let mut reader = RawBinaryReader::new(io::Cursor::new(bytes));
+    let mut is_append = false;
+    let mut imports = Vec::new();
+    let mut symbols = Vec::new();
+    reader.next()?;
+    reader.step_in()?;
+    loop {
+        let ion_type = match reader.next()? {
+            RawStreamItem::Value(ion_type) => ion_type,
+            RawStreamItem::Nothing => break,
+            _ => continue,
+        };
+        match reader.field_name()?.local_sid() {
+            Some(IMPORTS_SID) if ion_type == IonType::Symbol => {
+                is_append = reader.read_symbol()?.local_sid() == Some(ION_SYMBOL_TABLE_SID);
+            }
+            Some(IMPORTS_SID) if ion_type == IonType::List => {
+                reader.step_in()?;
+                while let RawStreamItem::Value(ion_type) = reader.next()? {
+                    if ion_type == IonType::Struct {
+                        reader.step_in()?;
+                        imports.extend(read_shared_import(&mut reader)?);
+                        reader.step_out()?;
+                    }
+                }
+                reader.step_out()?;
+            }
+            Some(SYMBOLS_SID) if ion_type == IonType::List => {
+                reader.step_in()?;
+                loop {
+                    match reader.next()? {
+                        RawStreamItem::Value(IonType::String) => {
+                            symbols.push(Some(reader.read_string()?))
+                        }
+                        RawStreamItem::Nothing => break,
+                        // Nulls and non-string values are symbols with unknown text.
+                        _ => symbols.push(None),
+                    }
+                }
+                reader.step_out()?;
+            }
+            _ => {}
+        }
+    }
+    Ok((is_append, imports, symbols))
+}
+
+// Reads the `name`, `version`, and `max_id` fields of an import struct that `reader` has stepped
+// into. Imports without a name are ignored, as the spec requires, and a missing or invalid
+// version is treated as 1. There's no catalog to look up the table's size in, so its `max_id`
+// is required.
+fn read_shared_import(
+    reader: &mut RawBinaryReader<io::Cursor<&[u8]>>,
+) -> Result<Option<SharedImport>> {
+    let mut name = None;
+    let mut version = 1;
+    let mut max_id = None;
+    while let RawStreamItem::Value(ion_type) = reader.next()? {
+        match (reader.field_name()?.local_sid(), ion_type) {
+            (Some(NAME_SID), IonType::String) => name = Some(reader.read_string()?),
+            (Some(VERSION_SID), IonType::Integer) => version = reader.read_i64()?.max(1),
+            (Some(MAX_ID_SID), IonType::Integer) => max_id = Some(reader.read_i64()?),
+            _ => {}
+        }
+    }
+    let name = match name {
+        Some(name) if !name.is_empty() && name != "$ion" => name,
+        _ => return Ok(None),
+    };
+    match max_id.map(usize::try_from) {
+        Some(Ok(max_id)) => Ok(Some(SharedImport {
+            name,
+            version,
+            max_id,
+        })),
+        _ => bail!(
+            "The import of the shared symbol table '{}' version {} has no valid max_id",
+            name,
+            version
+        ),
+    }
+}
+
+// Looks up `sid` in the current symbol table. Returns None if the ID isn't defined, or Some(None)
+// if it is but its text is unknown, as for symbols imported from a shared table.
+fn symbol_text(symbol_sources: &[SymbolSource], sid: SymbolId) -> Option<Option<&str>> {
+    symbol_sources
+        .iter()
+        .find(|source| source.ids().contains(&sid))
+        .map(|source| source.text_for(sid))
+}
+
+// Formats a range of symbol IDs as `$first-$last`, or just `$first` if it contains a single ID.
+fn id_range_text(ids: &Range<SymbolId>) -> String {
+    if ids.len() == 1 {
+        format!("${}", ids.start)
+    } else {
+        format!("${}-${}", ids.start, ids.end - 1)
+    }
+}
+
 fn to_hex(buffer: &mut String, bytes: &[u8]) {
     if bytes.is_empty() {
         return;
@@ -4,6 +4,7 @@ pub mod head;
 pub mod inspect;
 pub mod primitive;
 pub mod schema;
+pub mod tail;
 pub mod to;
 
 use crate::commands::CommandRunner;
@@ -21,6 +22,7 @@ pub fn beta_subcommands() -> Vec<Command> {
         primitive::app(),
         schema::app(),
         head::app(),
+        tail::app(),
         from::app(),
         to::app(),
     ]
@@ -35,6 +37,7 @@ pub fn runner_for_beta_subcommand(command_name: &str) -> Option<CommandRunner> {
         "from" => from::run,
         "to" => to::run,
         "head" => head::run,
+        "tail" => tail::run,
         _ => return None,
     };
     Some(runner)
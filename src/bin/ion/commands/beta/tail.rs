@@ -0,0 +1,86 @@
+use crate::commands::beta::head::for_each_input;
+use crate::commands::dump::{transcribe_n_values, write_in_format};
+use anyhow::Result;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use ion_rs::*;
+use std::collections::VecDeque;
+use std::io::Write;
+
+pub fn app() -> Command {
+    Command::new("tail")
+        .about("Prints the specified number of top-level values at the end of the input stream.")
+        .arg(
+            Arg::new("values")
+                .long("values")
+                .short('n')
+                .value_parser(value_parser!(usize))
+                .allow_negative_numbers(false)
+                .default_value("10")
+                .help("Specifies the number of output top-level values."),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("lines")
+                .value_parser(["binary", "text", "pretty", "lines"])
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            // All argv entries after the program name (argv[0])
+            // and any `clap`-managed options are considered input files.
+            Arg::new("input")
+                .index(1)
+                .help("Input file [default: STDIN]")
+                .action(ArgAction::Append)
+                .trailing_var_arg(true),
+        )
+}
+
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    for_each_input(matches, write_last_values)
+}
+
+/// Writes the last `count` top-level values in the Reader to `output` in the requested format.
+/// Only the most recent `count` values are held in memory while the stream is read.
+fn write_last_values(
+    reader: &mut Reader,
+    output: &mut Box<dyn Write>,
+    format: &str,
+    count: usize,
+) -> Result<()> {
+    // Each value is re-encoded as a standalone binary Ion stream with its own symbol table.
+    // Concatenating these streams produces a valid stream, so we can read them back as one.
+    let mut last_values: VecDeque<Vec<u8>> = VecDeque::with_capacity(count);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let mut writer = BinaryWriterBuilder::new().build(&mut buffer)?;
+        let values_written = transcribe_n_values(reader, &mut writer, Some(1), None)?;
+        drop(writer);
+        if values_written == 0 {
+            break;
+        }
+        if count == 0 {
+            continue;
+        }
+        // Once we're holding `count` values, evict the oldest one and reuse its buffer.
+        let next_buffer = if last_values.len() == count {
+            last_values.pop_front().unwrap()
+        } else {
+            Vec::new()
+        };
+        last_values.push_back(std::mem::replace(&mut buffer, next_buffer));
+    }
+
+    let last_values: Vec<u8> = last_values.into_iter().flatten().collect();
+    let mut reader = ReaderBuilder::new().build(last_values)?;
+    write_in_format(&mut reader, output, format, None, None)?;
+    Ok(())
+}
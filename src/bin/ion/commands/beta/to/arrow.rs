@@ -0,0 +1,865 @@
+use crate::commands::beta::to::avro::unscaled_decimal;
+use crate::commands::beta::to::{decimal_text, epoch_seconds};
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{anyhow, bail, Context, Result};
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Decimal128Array, Float64Array, Int64Array, ListArray,
+    RecordBatch, StringArray, StructArray, TimestampMicrosecondArray,
+};
+use arrow_buffer::{NullBuffer, OffsetBuffer};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use clap::{value_parser, Arg, ArgMatches};
+use ion_rs::types::integer::IntAccess;
+use ion_rs::value::native_writer::NativeElementWriter;
+use ion_rs::value::owned::Element;
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::writer::ElementWriter;
+use ion_rs::value::{IonElement, IonSequence, IonStruct};
+use ion_rs::{Integer, IonResult, IonType, IonWriter, TextWriter, TextWriterBuilder};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::Write;
+use std::iter;
+use std::sync::Arc;
+
+const HEADING: &str = "Parquet and Arrow options";
+
+/// The field metadata key that records which Ion type a column's values were converted from, when
+/// the Arrow type alone doesn't say.
+pub(crate) const ION_TYPE_KEY: &str = "ion.type";
+
+/// The suffix of the name of a column that holds the annotations of another column's values. A
+/// column with just this name holds the annotations of the rows themselves.
+pub(crate) const ANNOTATIONS_SUFFIX: &str = "$ion_annotations";
+
+/// The most digits a Decimal128 can hold.
+const MAX_DECIMAL_PRECISION: u8 = 38;
+
+// Format-specific options for the `to` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("batch-size")
+            .long("batch-size")
+            .default_value("8192")
+            .value_parser(value_parser!(u64).range(1..))
+            .help_heading(HEADING)
+            .help("The number of rows to convert and write at a time, which is also the row group size"),
+        Arg::new("infer-rows")
+            .long("infer-rows")
+            .default_value("1000")
+            .value_parser(value_parser!(u64).range(1..))
+            .help_heading(HEADING)
+            .help("Without --schema, the number of leading rows to infer the column schema from"),
+        Arg::new("rejects")
+            .long("rejects")
+            .help_heading(HEADING)
+            .help(
+                "A file to write the values that don't fit the column schema to, as Ion text, \
+                 instead of failing",
+            ),
+        Arg::new("annotations")
+            .long("annotations")
+            .default_value("drop")
+            .value_parser(["drop", "column", "error"])
+            .help_heading(HEADING)
+            .help(
+                "Whether annotations are dropped, written to a list column next to each column \
+                 (and one for the rows' own), or don't fit the column schema",
+            ),
+        Arg::new("ipc")
+            .long("ipc")
+            .default_value("file")
+            .value_parser(["file", "stream"])
+            .help_heading(HEADING)
+            .help("Whether arrow output uses the IPC file format or the IPC streaming format"),
+    ]
+}
+
+/// The type of a column, or of a value nested in one.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ColumnType {
+    Bool,
+    Int,
+    Float,
+    Decimal {
+        precision: u8,
+        scale: i8,
+    },
+    Timestamp,
+    String,
+    Symbol,
+    Blob,
+    List(Box<ColumnType>),
+    Struct(Vec<(String, ColumnType)>),
+    /// Any value, written as Ion text.
+    Ion,
+    /// Only nulls have been seen while inferring the schema.
+    Null,
+}
+
+impl ColumnType {
+    /// Reads a column type written in a schema file: one of the symbols `bool`, `int`, `float`,
+    /// `timestamp`, `string`, `symbol`, `blob`, or `ion`; `(decimal <precision> <scale>)`; a list holding the
+    /// type of its values, like `[string]`; or a struct of field types.
+    fn from_schema(element: &Element, path: &str) -> Result<Self> {
+        let invalid = || -> Result<Self> {
+            bail!(
+                "Invalid column type {} at {}; expected bool, int, float, timestamp, string, \
+                 symbol, blob, ion, (decimal <precision> <scale>), [<type>], or a struct of types",
+                element_to_string(element)?,
+                path
+            )
+        };
+        if element.is_null() {
+            return invalid();
+        }
+        let column_type = match element.ion_type() {
+            IonType::Symbol => match element.as_str().unwrap_or_default() {
+                "bool" => ColumnType::Bool,
+                "int" => ColumnType::Int,
+                "float" => ColumnType::Float,
+                "timestamp" => ColumnType::Timestamp,
+                "string" => ColumnType::String,
+                "symbol" => ColumnType::Symbol,
+                "blob" => ColumnType::Blob,
+                "ion" => ColumnType::Ion,
+                _ => return invalid(),
+            },
+            IonType::SExpression => {
+                let values: Vec<&Element> = element.as_sequence().unwrap().iter().collect();
+                let number = |value: &Element| -> Option<i64> {
+                    match value.as_integer()? {
+                        Integer::I64(value) => Some(*value),
+                        Integer::BigInt(_) => None,
+                    }
+                };
+                match values.as_slice() {
+                    [name, precision, scale] if name.as_str() == Some("decimal") => {
+                        match (number(precision), number(scale)) {
+                            (Some(precision @ 1..=38), Some(scale)) if scale.abs() <= precision => {
+                                ColumnType::Decimal {
+                                    precision: precision as u8,
+                                    scale: scale as i8,
+                                }
+                            }
+                            _ => return invalid(),
+                        }
+                    }
+                    _ => return invalid(),
+                }
+            }
+            IonType::List => {
+                let values: Vec<&Element> = element.as_sequence().unwrap().iter().collect();
+                match values.as_slice() {
+                    [item] => ColumnType::List(Box::new(ColumnType::from_schema(
+                        item,
+                        &index_path(path, 0),
+                    )?)),
+                    _ => return invalid(),
+                }
+            }
+            IonType::Struct => {
+                let fields = struct_schema(element, path)?;
+                if fields.is_empty() {
+                    return invalid();
+                }
+                ColumnType::Struct(fields)
+            }
+            _ => return invalid(),
+        };
+        Ok(column_type)
+    }
+
+    /// Returns the narrowest type of the value, which has `Null`s wherever only nulls were seen.
+    fn infer(element: &Element) -> Self {
+        if element.is_null() {
+            return ColumnType::Null;
+        }
+        match element.ion_type() {
+            IonType::Null => ColumnType::Null,
+            IonType::Boolean => ColumnType::Bool,
+            IonType::Integer => match element.as_integer().unwrap() {
+                Integer::I64(_) => ColumnType::Int,
+                Integer::BigInt(_) => ColumnType::Decimal {
+                    precision: MAX_DECIMAL_PRECISION,
+                    scale: 0,
+                },
+            },
+            IonType::Float => ColumnType::Float,
+            IonType::Decimal => {
+                // Decimal's `Display` implementation writes the coefficient and exponent as
+                // `<c>d<e>`; a negative exponent is the number of digits after the point.
+                let text = element.as_decimal().unwrap().to_string();
+                let exponent: i64 = text.split_once('d').unwrap().1.parse().unwrap();
+                ColumnType::Decimal {
+                    precision: MAX_DECIMAL_PRECISION,
+                    scale: (-exponent).clamp(0, i64::from(MAX_DECIMAL_PRECISION)) as i8,
+                }
+            }
+            IonType::Timestamp => ColumnType::Timestamp,
+            IonType::String => ColumnType::String,
+            IonType::Symbol => ColumnType::Symbol,
+            IonType::Blob | IonType::Clob => ColumnType::Blob,
+            IonType::List | IonType::SExpression => ColumnType::List(Box::new(
+                element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .map(ColumnType::infer)
+                    .fold(ColumnType::Null, ColumnType::merge),
+            )),
+            IonType::Struct => {
+                let mut fields: Vec<(String, ColumnType)> = Vec::new();
+                for (name, value) in element.as_struct().unwrap().fields() {
+                    merge_field(
+                        &mut fields,
+                        name.text().unwrap_or_default(),
+                        ColumnType::infer(value),
+                    );
+                }
+                ColumnType::Struct(fields)
+            }
+        }
+    }
+
+    /// Returns a type that can hold values of both types. Types that can't be reconciled become
+    /// `Ion`.
+    fn merge(self, other: ColumnType) -> ColumnType {
+        use ColumnType::*;
+        match (self, other) {
+            (Null, other) | (other, Null) => other,
+            (left, right) if left == right => left,
+            (Int, Float) | (Float, Int) | (Float, Decimal { .. }) | (Decimal { .. }, Float) => {
+                Float
+            }
+            (Int, decimal @ Decimal { .. }) | (decimal @ Decimal { .. }, Int) => decimal,
+            (String, Symbol) | (Symbol, String) => String,
+            (Decimal { scale: left, .. }, Decimal { scale: right, .. }) => Decimal {
+                precision: MAX_DECIMAL_PRECISION,
+                scale: left.max(right),
+            },
+            (List(left), List(right)) => List(Box::new(left.merge(*right))),
+            (Struct(mut left), Struct(right)) => {
+                for (name, column_type) in right {
+                    merge_field(&mut left, &name, column_type);
+                }
+                Struct(left)
+            }
+            _ => Ion,
+        }
+    }
+
+    /// Replaces the parts of an inferred type that no value has settled with `Ion`.
+    fn finish(self) -> ColumnType {
+        match self {
+            ColumnType::Null => ColumnType::Ion,
+            ColumnType::List(item) => ColumnType::List(Box::new(item.finish())),
+            // Parquet can't write a struct without any fields.
+            ColumnType::Struct(fields) if fields.is_empty() => ColumnType::Ion,
+            ColumnType::Struct(fields) => ColumnType::Struct(
+                fields
+                    .into_iter()
+                    .map(|(name, column_type)| (name, column_type.finish()))
+                    .collect(),
+            ),
+            column_type => column_type,
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Bool => DataType::Boolean,
+            ColumnType::Int => DataType::Int64,
+            ColumnType::Float => DataType::Float64,
+            ColumnType::Decimal { precision, scale } => DataType::Decimal128(*precision, *scale),
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            ColumnType::String | ColumnType::Symbol | ColumnType::Ion | ColumnType::Null => {
+                DataType::Utf8
+            }
+            ColumnType::Blob => DataType::Binary,
+            ColumnType::List(item) => DataType::List(Arc::new(item.field("item"))),
+            ColumnType::Struct(fields) => DataType::Struct(arrow_fields(fields)),
+        }
+    }
+
+    /// Returns a nullable field of this type. Fields of symbols and of Ion values are marked as
+    /// such in their metadata, so they can be told apart from strings.
+    fn field(&self, name: &str) -> Field {
+        let field = Field::new(name, self.data_type(), true);
+        let ion_type = match self {
+            ColumnType::Symbol => "symbol",
+            ColumnType::Ion | ColumnType::Null => "ion",
+            _ => return field,
+        };
+        field.with_metadata(HashMap::from([(
+            ION_TYPE_KEY.to_string(),
+            ion_type.to_string(),
+        )]))
+    }
+
+    /// Fails with the path of the first part of the value that doesn't fit this type.
+    fn check(&self, element: &Element, path: &str, target: &Target) -> Result<()> {
+        let mismatch = || -> Result<()> {
+            bail!(
+                "Cannot convert {} to {}: {} does not fit the column type {}",
+                path,
+                target.format,
+                element_to_string(element)?,
+                self
+            )
+        };
+        if target.annotations == Annotations::Error && element.annotations().next().is_some() {
+            bail!(
+                "Cannot convert {} to {}: annotations are not allowed (see --annotations)",
+                path,
+                target.format
+            );
+        }
+        if element.is_null() {
+            return Ok(());
+        }
+        match (self, element.ion_type()) {
+            (ColumnType::Ion, _)
+            | (ColumnType::Bool, IonType::Boolean)
+            | (ColumnType::Float, IonType::Float | IonType::Decimal | IonType::Integer)
+            | (ColumnType::String | ColumnType::Symbol, IonType::String | IonType::Symbol)
+            | (ColumnType::Blob, IonType::Blob | IonType::Clob) => Ok(()),
+            (ColumnType::Int, IonType::Integer) => match element.as_integer().unwrap() {
+                Integer::I64(_) => Ok(()),
+                Integer::BigInt(_) => mismatch(),
+            },
+            (ColumnType::Decimal { precision, scale }, IonType::Decimal | IonType::Integer) => {
+                match decimal_value(element, *precision, *scale)? {
+                    Some(_) => Ok(()),
+                    None => mismatch(),
+                }
+            }
+            (ColumnType::Timestamp, IonType::Timestamp) => {
+                // Digits beyond microseconds would be lost.
+                let (_, nanoseconds) = epoch_seconds(element.as_timestamp().unwrap());
+                match nanoseconds % 1_000 {
+                    0 => Ok(()),
+                    _ => mismatch(),
+                }
+            }
+            (ColumnType::List(item), IonType::List | IonType::SExpression) => {
+                for (index, value) in element.as_sequence().unwrap().iter().enumerate() {
+                    item.check(value, &index_path(path, index), target)?;
+                }
+                Ok(())
+            }
+            (ColumnType::Struct(fields), IonType::Struct) => {
+                check_fields(element, fields, path, target)
+            }
+            _ => mismatch(),
+        }
+    }
+
+    /// Builds an array of the values, which have already been checked against this type.
+    fn array(&self, values: &[Option<&Element>]) -> Result<ArrayRef> {
+        let array: ArrayRef = match self {
+            ColumnType::Bool => Arc::new(BooleanArray::from(
+                values
+                    .iter()
+                    .map(|value| present(value).map(|value| value.as_bool().unwrap()))
+                    .collect::<Vec<_>>(),
+            )),
+            ColumnType::Int => Arc::new(Int64Array::from(
+                values
+                    .iter()
+                    .map(|value| present(value).and_then(|value| value.as_i64()))
+                    .collect::<Vec<_>>(),
+            )),
+            ColumnType::Float => Arc::new(Float64Array::from(
+                values
+                    .iter()
+                    .map(|value| present(value).map(float_value).transpose())
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            ColumnType::Decimal { precision, scale } => Arc::new(
+                Decimal128Array::from(
+                    values
+                        .iter()
+                        .map(|value| match present(value) {
+                            Some(value) => decimal_value(value, *precision, *scale),
+                            None => Ok(None),
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                )
+                .with_precision_and_scale(*precision, *scale)?,
+            ),
+            ColumnType::Timestamp => Arc::new(
+                TimestampMicrosecondArray::from(
+                    values
+                        .iter()
+                        .map(|value| {
+                            present(value).map(|value| {
+                                let (seconds, nanoseconds) =
+                                    epoch_seconds(value.as_timestamp().unwrap());
+                                seconds * 1_000_000 + i64::from(nanoseconds / 1_000)
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .with_timezone("UTC"),
+            ),
+            ColumnType::String | ColumnType::Symbol => Arc::new(StringArray::from(
+                values
+                    .iter()
+                    .map(|value| present(value).and_then(|value| value.as_str()))
+                    .collect::<Vec<_>>(),
+            )),
+            ColumnType::Ion | ColumnType::Null => Arc::new(StringArray::from(
+                values
+                    .iter()
+                    .map(|value| value.map(element_to_string).transpose())
+                    .collect::<IonResult<Vec<_>>>()?,
+            )),
+            ColumnType::Blob => Arc::new(BinaryArray::from(
+                values
+                    .iter()
+                    .map(|value| present(value).and_then(|value| value.as_bytes()))
+                    .collect::<Vec<_>>(),
+            )),
+            ColumnType::List(item) => {
+                let mut offsets = Vec::with_capacity(values.len());
+                let mut items = Vec::new();
+                for value in values {
+                    if let Some(value) = present(value) {
+                        items.extend(value.as_sequence().unwrap().iter().map(Some));
+                    }
+                    offsets.push(items.len());
+                }
+                Arc::new(ListArray::new(
+                    Arc::new(item.field("item")),
+                    OffsetBuffer::from_lengths(lengths(&offsets)),
+                    item.array(&items)?,
+                    nulls(values),
+                ))
+            }
+            ColumnType::Struct(fields) => {
+                let mut arrays = Vec::with_capacity(fields.len());
+                for (name, column_type) in fields {
+                    let children: Vec<Option<&Element>> = values
+                        .iter()
+                        .map(|value| {
+                            present(value).and_then(|value| value.as_struct().unwrap().get(name))
+                        })
+                        .collect();
+                    arrays.push(column_type.array(&children)?);
+                }
+                Arc::new(StructArray::new(
+                    arrow_fields(fields),
+                    arrays,
+                    nulls(values),
+                ))
+            }
+        };
+        Ok(array)
+    }
+}
+
+impl Display for ColumnType {
+    /// Writes the type the way it's written in a schema file.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Bool => write!(f, "bool"),
+            ColumnType::Int => write!(f, "int"),
+            ColumnType::Float => write!(f, "float"),
+            ColumnType::Decimal { precision, scale } => {
+                write!(f, "(decimal {} {})", precision, scale)
+            }
+            ColumnType::Timestamp => write!(f, "timestamp"),
+            ColumnType::String => write!(f, "string"),
+            ColumnType::Symbol => write!(f, "symbol"),
+            ColumnType::Blob => write!(f, "blob"),
+            ColumnType::List(item) => write!(f, "[{}]", item),
+            ColumnType::Struct(fields) => {
+                write!(f, "{{")?;
+                for (index, (name, column_type)) in fields.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { "," };
+                    write!(
+                        f,
+                        "{} {}: {}",
+                        separator,
+                        field_path("", name).trim_start_matches('.'),
+                        column_type
+                    )?;
+                }
+                write!(f, " }}")
+            }
+            ColumnType::Ion | ColumnType::Null => write!(f, "ion"),
+        }
+    }
+}
+
+fn merge_field(fields: &mut Vec<(String, ColumnType)>, name: &str, column_type: ColumnType) {
+    match fields.iter_mut().find(|(existing, _)| existing == name) {
+        Some((_, existing)) => {
+            *existing = std::mem::replace(existing, ColumnType::Null).merge(column_type)
+        }
+        None => fields.push((name.to_string(), column_type)),
+    }
+}
+
+fn struct_schema(element: &Element, path: &str) -> Result<Vec<(String, ColumnType)>> {
+    element
+        .as_struct()
+        .unwrap()
+        .iter()
+        .map(|(name, value)| {
+            let name = name.text().unwrap_or_default();
+            Ok((
+                name.to_string(),
+                ColumnType::from_schema(value, &field_path(path, name))?,
+            ))
+        })
+        .collect()
+}
+
+fn arrow_fields(fields: &[(String, ColumnType)]) -> Fields {
+    fields
+        .iter()
+        .map(|(name, column_type)| column_type.field(name))
+        .collect()
+}
+
+/// Fails unless every field of the struct is one of `fields` and fits its type.
+fn check_fields(
+    element: &Element,
+    fields: &[(String, ColumnType)],
+    path: &str,
+    target: &Target,
+) -> Result<()> {
+    let format = target.format;
+    let mut seen = Vec::new();
+    for (name, value) in element.as_struct().unwrap().fields() {
+        let name = name.text().unwrap_or_default();
+        let value_path = field_path(path, name);
+        if seen.contains(&name) {
+            bail!(
+                "Cannot convert {} to {}: the field is repeated",
+                value_path,
+                format
+            );
+        }
+        seen.push(name);
+        match fields.iter().find(|(field, _)| field == name) {
+            Some((_, column_type)) => column_type.check(value, &value_path, target)?,
+            None => bail!(
+                "Cannot convert {} to {}: there is no column for the field",
+                value_path,
+                format
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn float_value(element: &Element) -> Result<f64> {
+    match element.ion_type() {
+        IonType::Float => Ok(element.as_f64().unwrap()),
+        IonType::Decimal => Ok(decimal_text(element.as_decimal().unwrap()).parse()?),
+        _ => Ok(element.as_integer().unwrap().to_string().parse()?),
+    }
+}
+
+/// Returns the decimal (or int) as a count of `10^-scale`s, or `None` if it has more digits after
+/// the point than `scale` or more digits in all than `precision`.
+fn decimal_value(element: &Element, precision: u8, scale: i8) -> Result<Option<i128>> {
+    let unscaled = match scale {
+        0.. => unscaled_decimal(element, scale as usize)?,
+        _ => {
+            let unscaled = unscaled_decimal(element, 0)?;
+            let divisor = num_bigint::BigInt::from(10).pow(u32::from(scale.unsigned_abs()));
+            unscaled
+                .filter(|unscaled| unscaled % &divisor == num_bigint::BigInt::from(0))
+                .map(|unscaled| unscaled / divisor)
+        }
+    };
+    Ok(unscaled
+        .filter(|unscaled| unscaled.magnitude().to_string().len() <= usize::from(precision))
+        .and_then(|unscaled| i128::try_from(&unscaled).ok()))
+}
+
+/// Returns the value unless it's missing or null.
+fn present<'a>(value: &Option<&'a Element>) -> Option<&'a Element> {
+    value.filter(|value| !value.is_null())
+}
+
+fn lengths(offsets: &[usize]) -> Vec<usize> {
+    offsets
+        .iter()
+        .scan(0, |previous, &offset| {
+            let length = offset - *previous;
+            *previous = offset;
+            Some(length)
+        })
+        .collect()
+}
+
+fn nulls(values: &[Option<&Element>]) -> Option<NullBuffer> {
+    let validity: Vec<bool> = values
+        .iter()
+        .map(|value| value.is_some_and(|value| !value.is_null()))
+        .collect();
+    match validity.iter().all(|valid| *valid) {
+        true => None,
+        false => Some(NullBuffer::from(validity)),
+    }
+}
+
+/// Writes a stream of structs as Arrow record batches of `--batch-size` rows, in the IPC file or
+/// streaming format. See `write_record_batches` for how the columns are chosen.
+pub(crate) fn write_arrow<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    match matches.get_one::<String>("ipc").unwrap().as_str() {
+        "stream" => write_record_batches(
+            elements,
+            "Arrow",
+            matches,
+            |schema| Ok(StreamWriter::try_new(output, &schema)?),
+            |writer, batch| Ok(writer.write(&batch)?),
+        )?
+        .finish()?,
+        _ => write_record_batches(
+            elements,
+            "Arrow",
+            matches,
+            |schema| Ok(FileWriter::try_new(output, &schema)?),
+            |writer, batch| Ok(writer.write(&batch)?),
+        )?
+        .finish()?,
+    }
+    Ok(())
+}
+
+/// What to do with annotations, which Arrow has no place for.
+#[derive(Clone, Copy, PartialEq)]
+enum Annotations {
+    Drop,
+    Column,
+    Error,
+}
+
+/// The format being converted to, and how to convert it.
+struct Target<'a> {
+    format: &'a str,
+    annotations: Annotations,
+}
+
+/// Converts a stream of structs to Arrow record batches of `--batch-size` rows. The column schema
+/// comes from `--schema`, or is inferred from the first `--infer-rows` rows; `open` is given the
+/// schema once it's known, and its result is passed to `write` with each batch and returned.
+/// Values that don't fit the schema are written to `--rejects`, or fail the conversion.
+pub(crate) fn write_record_batches<I, W>(
+    elements: I,
+    format: &str,
+    matches: &ArgMatches,
+    open: impl FnOnce(SchemaRef) -> Result<W>,
+    mut write: impl FnMut(&mut W, RecordBatch) -> Result<()>,
+) -> Result<W>
+where
+    I: Iterator<Item = IonResult<Element>>,
+{
+    let batch_size = *matches.get_one::<u64>("batch-size").unwrap() as usize;
+    let target = Target {
+        format,
+        annotations: match matches.get_one::<String>("annotations").unwrap().as_str() {
+            "column" => Annotations::Column,
+            "error" => Annotations::Error,
+            _ => Annotations::Drop,
+        },
+    };
+    let infer_rows = *matches.get_one::<u64>("infer-rows").unwrap() as usize;
+    let mut elements = elements.enumerate();
+
+    let mut leading_rows = Vec::new();
+    let columns = match matches.get_one::<String>("schema") {
+        Some(schema_file) => {
+            let schema = std::fs::read(schema_file)
+                .with_context(|| format!("Could not open file '{}'", schema_file))?;
+            let schema = element_reader()
+                .read_one(&schema)
+                .with_context(|| format!("Could not read column schema '{}'", schema_file))?;
+            if schema.ion_type() != IonType::Struct || schema.is_null() {
+                bail!("The column schema in '{}' must be a struct", schema_file);
+            }
+            struct_schema(&schema, "")?
+        }
+        None => {
+            for (index, element) in elements.by_ref().take(infer_rows) {
+                leading_rows.push((index, element?));
+            }
+            let inferred = leading_rows
+                .iter()
+                .filter(|(_, element)| element.ion_type() == IonType::Struct && !element.is_null())
+                .map(|(_, element)| ColumnType::infer(element))
+                .fold(ColumnType::Null, ColumnType::merge)
+                .finish();
+            match inferred {
+                ColumnType::Struct(fields) => fields,
+                _ => bail!("Could not infer a column schema: none of the leading rows is a struct with fields"),
+            }
+        }
+    };
+
+    let mut fields = arrow_fields(&columns).to_vec();
+    if target.annotations == Annotations::Column {
+        // Each column is followed by the annotations of its values, and the rows' own come first.
+        let annotations = ColumnType::List(Box::new(ColumnType::String));
+        fields = iter::once(Arc::new(annotations.field(ANNOTATIONS_SUFFIX)))
+            .chain(fields.into_iter().flat_map(|field| {
+                let name = format!("{}{}", field.name(), ANNOTATIONS_SUFFIX);
+                [field, Arc::new(annotations.field(&name))]
+            }))
+            .collect();
+    }
+    let schema: SchemaRef = Arc::new(Schema::new(fields));
+    let mut output = open(schema.clone())?;
+    let mut rejects = Rejects::new(matches)?;
+    let mut rows = Vec::with_capacity(batch_size);
+    let elements = leading_rows
+        .into_iter()
+        .map(|(index, element)| (index, Ok(element)))
+        .chain(elements);
+    for (index, element) in elements {
+        let element = element?;
+        let path = index_path("", index);
+        let fits = match element.ion_type() {
+            _ if target.annotations == Annotations::Error
+                && element.annotations().next().is_some() =>
+            {
+                Err(anyhow!(
+                    "Cannot convert {} to {}: annotations are not allowed (see --annotations)",
+                    path,
+                    format
+                ))
+            }
+            IonType::Struct if !element.is_null() => {
+                check_fields(&element, &columns, &path, &target)
+            }
+            _ => Err(anyhow!(
+                "Cannot convert {} to {}: only structs can be written as rows",
+                path,
+                format
+            )),
+        };
+        if let Err(error) = fits {
+            rejects.reject(&element, error)?;
+            continue;
+        }
+        rows.push(element);
+        if rows.len() == batch_size {
+            write(
+                &mut output,
+                record_batch(&rows, &columns, &schema, &target)?,
+            )?;
+            rows.clear();
+        }
+    }
+    if !rows.is_empty() {
+        write(
+            &mut output,
+            record_batch(&rows, &columns, &schema, &target)?,
+        )?;
+    }
+    rejects.finish()?;
+    Ok(output)
+}
+
+fn record_batch(
+    rows: &[Element],
+    columns: &[(String, ColumnType)],
+    schema: &SchemaRef,
+    target: &Target,
+) -> Result<RecordBatch> {
+    let with_annotations = target.annotations == Annotations::Column;
+    let mut arrays = Vec::with_capacity(schema.fields().len());
+    if with_annotations {
+        arrays.push(annotations_array(rows.iter().map(Some)));
+    }
+    for (name, column_type) in columns {
+        let values: Vec<Option<&Element>> = rows
+            .iter()
+            .map(|row| row.as_struct().unwrap().get(name))
+            .collect();
+        arrays.push(column_type.array(&values)?);
+        if with_annotations {
+            arrays.push(annotations_array(values.into_iter()));
+        }
+    }
+    Ok(RecordBatch::try_new(schema.clone(), arrays)?)
+}
+
+/// Builds a list of each value's annotations, which is empty for missing values.
+fn annotations_array<'a>(values: impl Iterator<Item = Option<&'a Element>>) -> ArrayRef {
+    let mut builder = ListBuilder::new(StringBuilder::new());
+    for value in values {
+        for annotation in value.into_iter().flat_map(|value| value.annotations()) {
+            builder.values().append_option(annotation.text());
+        }
+        builder.append(true);
+    }
+    Arc::new(builder.finish())
+}
+
+/// Where values that don't fit the column schema go.
+struct Rejects {
+    file_name: Option<String>,
+    writer: Option<NativeElementWriter<TextWriter<File>>>,
+    count: usize,
+    first_error: Option<anyhow::Error>,
+}
+
+impl Rejects {
+    fn new(matches: &ArgMatches) -> Result<Self> {
+        let file_name = matches.get_one::<String>("rejects").cloned();
+        let writer = match &file_name {
+            Some(file_name) => {
+                let file = File::create(file_name)
+                    .with_context(|| format!("could not open file '{}' for writing", file_name))?;
+                Some(NativeElementWriter::new(
+                    TextWriterBuilder::lines().build(file)?,
+                ))
+            }
+            None => None,
+        };
+        Ok(Rejects {
+            file_name,
+            writer,
+            count: 0,
+            first_error: None,
+        })
+    }
+
+    fn reject(&mut self, element: &Element, error: anyhow::Error) -> Result<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => return Err(error),
+        };
+        writer.write(element)?;
+        self.count += 1;
+        self.first_error.get_or_insert(error);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        if let (Some(writer), Some(file_name)) = (self.writer, self.file_name) {
+            writer.finish()?.flush()?;
+            if let Some(error) = self.first_error {
+                eprintln!(
+                    "Wrote {} value(s) that don't fit the column schema to '{}'; the first: {}",
+                    self.count, file_name, error
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use crate::commands::beta::to::{decimal_text, epoch_seconds, local_epoch_seconds};
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{bail, Context, Result};
+use apache_avro::schema::{Name, ResolvedSchema, SchemaKind};
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{Days, Duration, Millis, Months, Schema, Writer};
+use clap::ArgMatches;
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence, IonStruct};
+use ion_rs::{Integer, IonResult, IonType};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::io::Write;
+use std::str::FromStr;
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// The named types in a schema, which `Schema::Ref`s refer to.
+pub(crate) struct AvroNames(HashMap<Name, Schema>);
+
+impl AvroNames {
+    pub(crate) fn new(schema: &Schema) -> Result<Self> {
+        let resolved = ResolvedSchema::try_from(schema)?;
+        let names = resolved
+            .get_names()
+            .iter()
+            .map(|(name, schema)| (name.clone(), (*schema).clone()))
+            .collect();
+        Ok(AvroNames(names))
+    }
+
+    /// Returns the schema that a `Schema::Ref` refers to, or the schema itself otherwise.
+    pub(crate) fn resolve<'a>(&'a self, schema: &'a Schema) -> Result<&'a Schema> {
+        match schema {
+            Schema::Ref { name } => self
+                .0
+                .get(name)
+                .or_else(|| {
+                    self.0
+                        .iter()
+                        .find(|(known, _)| known.name == name.name)
+                        .map(|(_, schema)| schema)
+                })
+                .with_context(|| format!("The Avro schema refers to unknown type '{}'", name)),
+            schema => Ok(schema),
+        }
+    }
+}
+
+/// Writes each value as a record of an Avro object container file, converting it according to the
+/// `--schema` file. Values that don't fit the schema are rejected with an error naming their path.
+pub(crate) fn write_avro<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let schema_file = match matches.get_one::<String>("schema") {
+        Some(schema_file) => schema_file,
+        None => bail!("Converting to Avro requires an Avro schema file (see --schema)"),
+    };
+    let schema_text = std::fs::read_to_string(schema_file)
+        .with_context(|| format!("Could not open file '{}'", schema_file))?;
+    let schema = Schema::parse_str(&schema_text)
+        .with_context(|| format!("Could not read Avro schema '{}'", schema_file))?;
+    let converter = AvroConverter {
+        names: AvroNames::new(&schema)?,
+    };
+    let mut writer = Writer::new(&schema, output);
+    for (index, element) in elements.enumerate() {
+        let value = converter.to_avro_value(&element?, &schema, &index_path("", index))?;
+        writer.append(value)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+struct AvroConverter {
+    names: AvroNames,
+}
+
+impl AvroConverter {
+    /// Converts an Ion value to the Avro value that `schema` describes. Annotations are ignored.
+    fn to_avro_value(&self, element: &Element, schema: &Schema, path: &str) -> Result<AvroValue> {
+        let schema = self.names.resolve(schema)?;
+        let mismatch = || -> Result<AvroValue> {
+            bail!(
+                "Cannot convert {} to Avro: {} does not match the schema type {}",
+                path,
+                element_to_string(element)?,
+                schema_type(schema)
+            )
+        };
+        if let Schema::Union(union) = schema {
+            return self.to_union_value(element, union.variants(), path);
+        }
+        if element.is_null() {
+            return match schema {
+                Schema::Null => Ok(AvroValue::Null),
+                _ => mismatch(),
+            };
+        }
+        let value = match (schema, element.ion_type()) {
+            (Schema::Boolean, IonType::Boolean) => AvroValue::Boolean(element.as_bool().unwrap()),
+            (Schema::Int, IonType::Integer) => match integer_value(element) {
+                Some(value) if i32::try_from(value).is_ok() => AvroValue::Int(value as i32),
+                _ => return mismatch(),
+            },
+            (Schema::Long, IonType::Integer) => match integer_value(element) {
+                Some(value) => AvroValue::Long(value),
+                None => return mismatch(),
+            },
+            (
+                Schema::Float | Schema::Double,
+                IonType::Float | IonType::Decimal | IonType::Integer,
+            ) => {
+                let value = match element.ion_type() {
+                    IonType::Float => element.as_f64().unwrap(),
+                    IonType::Decimal => decimal_text(element.as_decimal().unwrap()).parse()?,
+                    _ => element.as_integer().unwrap().to_string().parse()?,
+                };
+                match schema {
+                    Schema::Float => AvroValue::Float(value as f32),
+                    _ => AvroValue::Double(value),
+                }
+            }
+            (Schema::String, IonType::String | IonType::Symbol) => match element.as_str() {
+                Some(text) => AvroValue::String(text.to_string()),
+                None => return mismatch(),
+            },
+            (Schema::Uuid, IonType::String | IonType::Symbol) => {
+                let text = element.as_str().unwrap_or_default().to_string();
+                match AvroValue::String(text).resolve(schema) {
+                    Ok(uuid) => uuid,
+                    Err(_) => return mismatch(),
+                }
+            }
+            (Schema::Bytes, IonType::Blob | IonType::Clob) => {
+                AvroValue::Bytes(element.as_bytes().unwrap().to_vec())
+            }
+            (Schema::Fixed(fixed), IonType::Blob | IonType::Clob) => {
+                let bytes = element.as_bytes().unwrap();
+                if bytes.len() != fixed.size {
+                    bail!(
+                        "Cannot convert {} to Avro: the fixed type '{}' holds {} bytes, not {}",
+                        path,
+                        fixed.name,
+                        fixed.size,
+                        bytes.len()
+                    );
+                }
+                AvroValue::Fixed(fixed.size, bytes.to_vec())
+            }
+            (Schema::Enum(enum_schema), IonType::Symbol | IonType::String) => {
+                let text = element.as_str().unwrap_or_default();
+                match enum_schema.symbols.iter().position(|symbol| symbol == text) {
+                    Some(index) => AvroValue::Enum(index as u32, text.to_string()),
+                    None => bail!(
+                        "Cannot convert {} to Avro: '{}' is not one of the enum '{}' symbols {:?}",
+                        path,
+                        text,
+                        enum_schema.name,
+                        enum_schema.symbols
+                    ),
+                }
+            }
+            (Schema::Array(items), IonType::List | IonType::SExpression) => AvroValue::Array(
+                element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.to_avro_value(item, items, &index_path(path, index)))
+                    .collect::<Result<Vec<AvroValue>>>()?,
+            ),
+            (Schema::Map(values), IonType::Struct) => AvroValue::Map(
+                element
+                    .as_struct()
+                    .unwrap()
+                    .iter()
+                    .map(|(name, value)| {
+                        let name = name.text().unwrap_or_default();
+                        let value = self.to_avro_value(value, values, &field_path(path, name))?;
+                        Ok((name.to_string(), value))
+                    })
+                    .collect::<Result<HashMap<String, AvroValue>>>()?,
+            ),
+            (Schema::Record(record), IonType::Struct) => {
+                let fields = element.as_struct().unwrap();
+                for (name, _) in fields.iter() {
+                    let name = name.text().unwrap_or_default();
+                    if !record.lookup.contains_key(name) {
+                        bail!(
+                            "Cannot convert {} to Avro: the record '{}' has no field '{}'",
+                            field_path(path, name),
+                            record.name,
+                            name
+                        );
+                    }
+                }
+                let mut values = Vec::with_capacity(record.fields.len());
+                for field in &record.fields {
+                    let field_path = field_path(path, &field.name);
+                    let value = match (fields.get(field.name.as_str()), &field.default) {
+                        (Some(value), _) => {
+                            self.to_avro_value(value, &field.schema, &field_path)?
+                        }
+                        (None, Some(default)) => AvroValue::from(default.clone())
+                            .resolve(&field.schema)
+                            .with_context(|| {
+                                format!("Invalid default for the Avro field '{}'", field.name)
+                            })?,
+                        // A missing field is null, if the field's type allows it.
+                        (None, None) => self
+                            .to_avro_value(
+                                &Element::from(IonType::Null),
+                                &field.schema,
+                                &field_path,
+                            )
+                            .or_else(|_| {
+                                bail!(
+                                    "Cannot convert {} to Avro: the record '{}' requires the \
+                                     field '{}'",
+                                    path,
+                                    record.name,
+                                    field.name
+                                )
+                            })?,
+                    };
+                    values.push((field.name.clone(), value));
+                }
+                AvroValue::Record(values)
+            }
+            (Schema::Decimal(decimal), IonType::Decimal | IonType::Integer) => {
+                let scale = decimal.scale;
+                let unscaled = match unscaled_decimal(element, scale)? {
+                    Some(unscaled) => unscaled,
+                    None => bail!(
+                        "Cannot convert {} to Avro: {} has more than {} digits after the decimal \
+                         point",
+                        path,
+                        element_to_string(element)?,
+                        scale
+                    ),
+                };
+                let digits = unscaled.magnitude().to_string().len();
+                if digits > decimal.precision {
+                    bail!(
+                        "Cannot convert {} to Avro: {} has more than the {} digits of precision \
+                         the schema allows",
+                        path,
+                        element_to_string(element)?,
+                        decimal.precision
+                    );
+                }
+                AvroValue::Decimal(apache_avro::Decimal::from(unscaled.to_signed_bytes_be()))
+            }
+            (Schema::Date, IonType::Timestamp) => {
+                let (seconds, _) = local_epoch_seconds(element.as_timestamp().unwrap());
+                AvroValue::Date(i32::try_from(seconds.div_euclid(86_400))?)
+            }
+            (
+                Schema::TimestampMillis
+                | Schema::TimestampMicros
+                | Schema::LocalTimestampMillis
+                | Schema::LocalTimestampMicros,
+                IonType::Timestamp,
+            ) => {
+                // Local timestamps keep the wall-clock time; the others are shifted to UTC.
+                let timestamp = element.as_timestamp().unwrap();
+                let (seconds, nanoseconds) = match schema {
+                    Schema::TimestampMillis | Schema::TimestampMicros => epoch_seconds(timestamp),
+                    _ => local_epoch_seconds(timestamp),
+                };
+                let millis = matches!(
+                    schema,
+                    Schema::TimestampMillis | Schema::LocalTimestampMillis
+                );
+                let units_per_second = if millis { 1_000 } else { 1_000_000 };
+                let nanoseconds_per_unit = 1_000_000_000 / units_per_second as u32;
+                if nanoseconds % nanoseconds_per_unit != 0 {
+                    bail!(
+                        "Cannot convert {} to Avro: {} is more precise than {}",
+                        path,
+                        element_to_string(element)?,
+                        schema_type(schema)
+                    );
+                }
+                let value =
+                    seconds * units_per_second + (nanoseconds / nanoseconds_per_unit) as i64;
+                match schema {
+                    Schema::TimestampMillis => AvroValue::TimestampMillis(value),
+                    Schema::TimestampMicros => AvroValue::TimestampMicros(value),
+                    Schema::LocalTimestampMillis => AvroValue::LocalTimestampMillis(value),
+                    _ => AvroValue::LocalTimestampMicros(value),
+                }
+            }
+            (Schema::TimeMillis | Schema::TimeMicros, IonType::String | IonType::Integer) => {
+                let micros_per_unit = match schema {
+                    Schema::TimeMillis => 1_000,
+                    _ => 1,
+                };
+                // Integers are already in the schema's unit.
+                let value = match element.ion_type() {
+                    IonType::Integer => integer_value(element),
+                    _ => time_of_day_micros(element.as_str().unwrap())
+                        .filter(|micros| micros % micros_per_unit == 0)
+                        .map(|micros| micros / micros_per_unit),
+                };
+                // A time of day must be before the next midnight.
+                match value.filter(|value| (0..MICROS_PER_DAY / micros_per_unit).contains(value)) {
+                    Some(millis) if micros_per_unit == 1_000 => {
+                        AvroValue::TimeMillis(millis as i32)
+                    }
+                    Some(micros) => AvroValue::TimeMicros(micros),
+                    None => return mismatch(),
+                }
+            }
+            (Schema::Duration, IonType::Struct) => {
+                let fields = element.as_struct().unwrap();
+                let mut parts = ["months", "days", "milliseconds"].into_iter().map(|name| {
+                    fields
+                        .get(name)
+                        .and_then(integer_value)
+                        .and_then(|value| u32::try_from(value).ok())
+                });
+                match (
+                    parts.next().unwrap(),
+                    parts.next().unwrap(),
+                    parts.next().unwrap(),
+                ) {
+                    (Some(months), Some(days), Some(millis)) => AvroValue::Duration(Duration::new(
+                        Months::new(months),
+                        Days::new(days),
+                        Millis::new(millis),
+                    )),
+                    _ => bail!(
+                        "Cannot convert {} to Avro: a duration must be a struct with unsigned \
+                         32-bit int fields months, days, and milliseconds",
+                        path
+                    ),
+                }
+            }
+            _ => return mismatch(),
+        };
+        Ok(value)
+    }
+
+    /// Converts the value to the first of the union's types that it fits.
+    fn to_union_value(
+        &self,
+        element: &Element,
+        variants: &[Schema],
+        path: &str,
+    ) -> Result<AvroValue> {
+        let non_null: Vec<&Schema> = variants
+            .iter()
+            .filter(|variant| !matches!(variant, Schema::Null))
+            .collect();
+        let mut first_error = None;
+        for (index, variant) in variants.iter().enumerate() {
+            match self.to_avro_value(element, variant, path) {
+                Ok(value) => return Ok(AvroValue::Union(index as u32, Box::new(value))),
+                // A union of null and one other type is how Avro spells "optional", so the
+                // other type's error is the most helpful one.
+                Err(error) if first_error.is_none() && !matches!(variant, Schema::Null) => {
+                    first_error = Some(error)
+                }
+                Err(_) => {}
+            }
+        }
+        match (non_null.len(), first_error) {
+            (1, Some(error)) => Err(error),
+            _ => bail!(
+                "Cannot convert {} to Avro: {} does not match any type in the union {}",
+                path,
+                element_to_string(element)?,
+                variants
+                    .iter()
+                    .map(schema_type)
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+        }
+    }
+}
+
+fn integer_value(element: &Element) -> Option<i64> {
+    match element.as_integer()? {
+        Integer::I64(value) => Some(*value),
+        Integer::BigInt(_) => None,
+    }
+}
+
+/// Returns the decimal (or int) as an integer count of `10^-scale`s, or `None` if that would
+/// lose digits.
+pub(crate) fn unscaled_decimal(element: &Element, scale: usize) -> Result<Option<BigInt>> {
+    // Decimal's `Display` implementation writes the coefficient and exponent as `<c>d<e>`. The
+    // value is written on its own so that any annotations are left out.
+    let text = match (element.as_decimal(), element.as_integer()) {
+        (Some(decimal), _) => decimal.to_string(),
+        (_, Some(integer)) => integer.to_string(),
+        _ => element_to_string(element)?,
+    };
+    let (coefficient, exponent) = text.split_once('d').unwrap_or((&text, "0"));
+    let coefficient = BigInt::from_str(coefficient)?;
+    let shift = i64::from_str(exponent)? + scale as i64;
+    if shift >= 0 {
+        return Ok(Some(coefficient * BigInt::from(10).pow(shift as u32)));
+    }
+    let divisor = BigInt::from(10).pow(shift.unsigned_abs() as u32);
+    if &coefficient % &divisor != BigInt::from(0) {
+        return Ok(None);
+    }
+    Ok(Some(coefficient / divisor))
+}
+
+/// Parses a time of day like `07:32:00.25` into microseconds since midnight, or returns `None` if
+/// it isn't one, like `25:99:99`.
+fn time_of_day_micros(text: &str) -> Option<i64> {
+    let (clock, fraction) = text.split_once('.').unwrap_or((text, ""));
+    // Unlike `parse` alone, this rejects fields with a sign, like `-1`.
+    let mut fields = clock.split(':').map(|field| {
+        if field.chars().all(|c| c.is_ascii_digit()) {
+            field.parse::<i64>().ok()
+        } else {
+            None
+        }
+    });
+    let (hours, minutes, seconds) = (
+        fields.next()??,
+        fields.next()??,
+        fields.next().unwrap_or(Some(0))?,
+    );
+    if fields.next().is_some()
+        || hours > 23
+        || minutes > 59
+        || seconds > 59
+        || fraction.len() > 6
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let fraction: i64 = format!("{:0<6}", fraction).parse().ok()?;
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1_000_000 + fraction)
+}
+
+/// Describes a schema for error messages.
+fn schema_type(schema: &Schema) -> String {
+    match schema {
+        Schema::Record(record) => format!("record '{}'", record.name),
+        Schema::Enum(enum_schema) => format!("enum '{}'", enum_schema.name),
+        Schema::Fixed(fixed) => format!("fixed '{}'", fixed.name),
+        Schema::Ref { name } => format!("'{}'", name),
+        schema => format!("{:?}", SchemaKind::from(schema)).to_lowercase(),
+    }
+}
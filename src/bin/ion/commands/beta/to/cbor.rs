@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use ciborium::value::{Integer as CborInteger, Value as CborValue};
+use ion_rs::types::timestamp::Precision;
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::{Integer, IonResult, IonType, Timestamp};
+use num_bigint::{BigInt, Sign};
+use std::io::Write;
+use std::str::FromStr;
+
+// Tags from the IANA CBOR tag registry (RFC 8949, section 3.4).
+pub(crate) const DATE_TIME_STRING_TAG: u64 = 0;
+pub(crate) const EPOCH_DATE_TIME_TAG: u64 = 1;
+pub(crate) const POSITIVE_BIGNUM_TAG: u64 = 2;
+pub(crate) const NEGATIVE_BIGNUM_TAG: u64 = 3;
+pub(crate) const DECIMAL_FRACTION_TAG: u64 = 4;
+/// A `full-date` string like `2020-05-06` (RFC 8943).
+pub(crate) const FULL_DATE_STRING_TAG: u64 = 1004;
+/// Wraps an annotated value as `[[annotation, ...], value]`. The number is "ION" in ASCII, from
+/// the first-come-first-served range of the registry.
+pub(crate) const ANNOTATIONS_TAG: u64 = 0x49_4F_4E;
+
+/// Writes each top-level value as a CBOR data item, producing a CBOR sequence (RFC 8742).
+pub(crate) fn write_cbor<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+) -> Result<()> {
+    for element in elements {
+        let value = to_cbor_value(&element?)?;
+        ciborium::ser::into_writer(&value, &mut *output).with_context(|| "Could not write CBOR")?;
+    }
+    Ok(())
+}
+
+/// Converts an Ion value to CBOR:
+///
+/// * timestamps become tag 0 RFC 3339 date/time strings, or tag 1004 dates if they have no time
+///   (see [timestamp_value]),
+/// * decimals become tag 4 decimal fractions,
+/// * ints that don't fit in CBOR's major types become tag 2 or 3 bignums,
+/// * blobs and clobs become byte strings,
+/// * symbols become text strings, and s-expressions become arrays, and
+/// * annotated values are wrapped in [ANNOTATIONS_TAG].
+fn to_cbor_value(element: &Element) -> Result<CborValue> {
+    let value = if element.is_null() {
+        CborValue::Null
+    } else {
+        match element.ion_type() {
+            IonType::Null => CborValue::Null,
+            IonType::Boolean => CborValue::Bool(element.as_bool().unwrap()),
+            IonType::Integer => match element.as_integer().unwrap() {
+                Integer::I64(value) => CborValue::Integer((*value).into()),
+                Integer::BigInt(value) => integer_value(value),
+            },
+            IonType::Float => CborValue::Float(element.as_f64().unwrap()),
+            IonType::Decimal => {
+                // Ion's decimal `Display` implementation writes the coefficient and exponent as
+                // `<c>d<e>`.
+                let text = element.as_decimal().unwrap().to_string();
+                let (coefficient, exponent) = text.split_once('d').unwrap();
+                let exponent = CborValue::Integer(i64::from_str(exponent)?.into());
+                let mantissa = integer_value(&BigInt::from_str(coefficient)?);
+                CborValue::Tag(
+                    DECIMAL_FRACTION_TAG,
+                    Box::new(CborValue::Array(vec![exponent, mantissa])),
+                )
+            }
+            IonType::Timestamp => timestamp_value(element.as_timestamp().unwrap()),
+            IonType::Symbol | IonType::String => {
+                CborValue::Text(element.as_str().unwrap_or_default().to_string())
+            }
+            IonType::Clob | IonType::Blob => CborValue::Bytes(element.as_bytes().unwrap().to_vec()),
+            IonType::List | IonType::SExpression => CborValue::Array(
+                element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .map(to_cbor_value)
+                    .collect::<Result<_>>()?,
+            ),
+            IonType::Struct => CborValue::Map(
+                element
+                    .as_struct()
+                    .unwrap()
+                    .fields()
+                    .map(|(name, value)| {
+                        let name = CborValue::Text(name.text().unwrap_or("$0").to_string());
+                        Ok((name, to_cbor_value(value)?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        }
+    };
+
+    let annotations: Vec<CborValue> = element
+        .annotations()
+        .map(|annotation| CborValue::Text(annotation.text().unwrap_or("$0").to_string()))
+        .collect();
+    if annotations.is_empty() {
+        return Ok(value);
+    }
+    Ok(CborValue::Tag(
+        ANNOTATIONS_TAG,
+        Box::new(CborValue::Array(vec![CborValue::Array(annotations), value])),
+    ))
+}
+
+/// Converts a timestamp to a tag 0 date/time string or a tag 1004 date string. Neither allows every
+/// Ion precision, so minute precision timestamps get zero seconds, and year and month precision
+/// timestamps become the first day of the year or month.
+fn timestamp_value(timestamp: &Timestamp) -> CborValue {
+    // Ion's text for a timestamp with seconds is already an RFC 3339 date-time, and the fields
+    // before the time are always `YYYY-MM-DDTHH:MM`.
+    let text = timestamp.to_string();
+    let (tag, text) = match timestamp.precision() {
+        Precision::Second => (DATE_TIME_STRING_TAG, text),
+        Precision::HourAndMinute => (
+            DATE_TIME_STRING_TAG,
+            format!("{}:00{}", &text[..16], &text[16..]),
+        ),
+        Precision::Day => (FULL_DATE_STRING_TAG, text[..10].to_string()),
+        Precision::Month => (FULL_DATE_STRING_TAG, format!("{}-01", &text[..7])),
+        Precision::Year => (FULL_DATE_STRING_TAG, format!("{}-01-01", &text[..4])),
+    };
+    CborValue::Tag(tag, Box::new(CborValue::Text(text)))
+}
+
+/// Returns a CBOR integer if the value is in range, or a bignum otherwise.
+fn integer_value(value: &BigInt) -> CborValue {
+    if let Some(integer) = i128::try_from(value)
+        .ok()
+        .and_then(|value| CborInteger::try_from(value).ok())
+    {
+        // ciborium writes integers beyond the 64-bit major types as bignums itself.
+        return CborValue::Integer(integer);
+    }
+    // A negative bignum n is encoded as the magnitude of -1 - n.
+    let (tag, magnitude) = match value.sign() {
+        Sign::Minus => (
+            NEGATIVE_BIGNUM_TAG,
+            (-value - 1u32).magnitude().to_bytes_be(),
+        ),
+        _ => (POSITIVE_BIGNUM_TAG, value.magnitude().to_bytes_be()),
+    };
+    CborValue::Tag(tag, Box::new(CborValue::Bytes(magnitude)))
+}
@@ -0,0 +1,142 @@
+use crate::commands::beta::to::decimal_text;
+use crate::commands::beta::to::json::to_json_value;
+use crate::commands::util::element_to_string;
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches};
+use ion_rs::value::owned::{Element, Struct};
+use ion_rs::value::IonElement;
+use ion_rs::{IonResult, IonType};
+use std::io::Write;
+
+const HEADING: &str = "CSV and TSV options";
+
+// Format-specific options for the `to` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("columns")
+            .long("columns")
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help_heading(HEADING)
+            .help(
+                "Comma-separated field names to write as columns, in order \
+                 [default: every field name, in the order each first appears]",
+            ),
+        Arg::new("nested")
+            .long("nested")
+            .default_value("ion")
+            .value_parser(["ion", "json", "error"])
+            .help_heading(HEADING)
+            .help("How to write list, s-expression, and struct values in a cell or SQL value"),
+        Arg::new("no-header")
+            .long("no-header")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Don't write a header row"),
+    ]
+}
+
+/// Writes a stream of structs as rows, one column per field name. Fields that a struct doesn't
+/// have are written as empty cells, as are nulls.
+pub(crate) fn write_csv<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    delimiter: u8,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let nested = matches.get_one::<String>("nested").unwrap().as_str();
+    let mut writer = ::csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(output);
+
+    let write_header = !matches.get_flag("no-header");
+    if let Some(columns) = matches.get_many::<String>("columns") {
+        // The columns are known up front, so each row can be written as soon as it's read.
+        let columns: Vec<&str> = columns.map(|column| column.as_str()).collect();
+        if write_header {
+            writer.write_record(&columns)?;
+        }
+        for element in elements {
+            writer.write_record(row(&element?, &columns, nested)?)?;
+        }
+    } else {
+        // The columns are the union of every struct's field names, so every row has to be read
+        // before the first one can be written.
+        let elements = elements.collect::<IonResult<Vec<Element>>>()?;
+        let mut columns: Vec<&str> = Vec::new();
+        for element in &elements {
+            for (name, _) in as_row(element)?.fields() {
+                let name = name.text().unwrap_or("$0");
+                if !columns.contains(&name) {
+                    columns.push(name);
+                }
+            }
+        }
+        if write_header {
+            writer.write_record(&columns)?;
+        }
+        for element in &elements {
+            writer.write_record(row(element, &columns, nested)?)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn as_row(element: &Element) -> Result<&Struct> {
+    match element.as_struct() {
+        Some(row) => Ok(row),
+        None => bail!(
+            "Only structs can be written as rows; found {}",
+            element_to_string(element)?
+        ),
+    }
+}
+
+/// Returns the struct's cells for each of the given columns. If a field name is repeated, the
+/// first value is used.
+fn row(element: &Element, columns: &[&str], nested: &str) -> Result<Vec<String>> {
+    let row = as_row(element)?;
+    columns
+        .iter()
+        .map(
+            |column| match row.fields().find(|(name, _)| name.text() == Some(*column)) {
+                Some((_, value)) => cell(value, nested)
+                    .with_context(|| format!("Could not write field '{}'", column)),
+                None => Ok(String::new()),
+            },
+        )
+        .collect()
+}
+
+fn cell(value: &Element, nested: &str) -> Result<String> {
+    if value.is_null() {
+        return Ok(String::new());
+    }
+    let text = match value.ion_type() {
+        IonType::Null => String::new(),
+        IonType::Boolean => value.as_bool().unwrap().to_string(),
+        IonType::Integer => value.as_integer().unwrap().to_string(),
+        IonType::Float => {
+            let float = value.as_f64().unwrap();
+            match float {
+                _ if float.is_nan() => "nan".to_string(),
+                f64::INFINITY => "+inf".to_string(),
+                f64::NEG_INFINITY => "-inf".to_string(),
+                _ => float.to_string(),
+            }
+        }
+        IonType::Decimal => decimal_text(value.as_decimal().unwrap()),
+        IonType::Timestamp => value.as_timestamp().unwrap().to_string(),
+        IonType::Symbol | IonType::String => value.as_str().unwrap_or_default().to_string(),
+        // Lobs are written as the base64 or string text between their braces.
+        IonType::Clob => value.to_string().replace("{{\"", "").replace("\"}}", ""),
+        IonType::Blob => value.to_string().replace("{{", "").replace("}}", ""),
+        IonType::List | IonType::SExpression | IonType::Struct => match nested {
+            "ion" => element_to_string(value)?,
+            "json" => to_json_value(value.clone())?.to_string(),
+            _ => bail!("nested values are not allowed (see --nested)"),
+        },
+    };
+    Ok(text)
+}
@@ -0,0 +1,449 @@
+use crate::commands::util::element_to_string;
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches};
+use ion_rs::value::owned::{Element, Value};
+use ion_rs::value::{IonElement, IonSequence, IonStruct};
+use ion_rs::{IonResult, IonType};
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+use serde_json::{Map, Number, Serializer, Value as JsonValue};
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+const HEADING: &str = "JSON options";
+
+// Each of the Ion values that JSON can't represent is written by `--preserve-types` as a JSON
+// object with one of these keys. `beta from json --preserve-types` reads them back.
+pub(crate) const ANNOTATIONS_KEY: &str = "$ion_annotations";
+pub(crate) const VALUE_KEY: &str = "$ion_value";
+pub(crate) const NULL_KEY: &str = "$ion_null";
+pub(crate) const FLOAT_KEY: &str = "$ion_float";
+pub(crate) const DECIMAL_KEY: &str = "$ion_decimal";
+pub(crate) const TIMESTAMP_KEY: &str = "$ion_timestamp";
+pub(crate) const SYMBOL_KEY: &str = "$ion_symbol";
+pub(crate) const BLOB_KEY: &str = "$ion_blob";
+pub(crate) const CLOB_KEY: &str = "$ion_clob";
+pub(crate) const SEXP_KEY: &str = "$ion_sexp";
+pub(crate) const STRUCT_KEY: &str = "$ion_struct";
+/// Field names with this prefix are reserved for the keys above.
+pub(crate) const KEY_PREFIX: &str = "$ion_";
+
+// Format-specific options for the `to` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("preserve-types")
+            .long("preserve-types")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help(
+                "Write annotations and the Ion types that JSON lacks as '$ion_' objects so that \
+                 `beta from json --preserve-types` can rebuild the original values",
+            ),
+        Arg::new("pretty")
+            .long("pretty")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Write each value across several indented lines"),
+        Arg::new("indent")
+            .long("indent")
+            .value_parser(value_parser!(usize))
+            .help_heading(HEADING)
+            .help("The number of spaces to indent each level by; implies --pretty [default: 2]"),
+        Arg::new("layout")
+            .long("layout")
+            .default_value("lines")
+            .value_parser(["lines", "array"])
+            .help_heading(HEADING)
+            .help("Write one JSON value per line, or a single JSON array holding every value"),
+        Arg::new("sort-keys")
+            .long("sort-keys")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Write object keys in sorted order instead of the order of the Ion fields"),
+        Arg::new("ascii")
+            .long("ascii")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help("Escape every non-ASCII character in strings as \\uXXXX"),
+    ]
+}
+
+/// Writes each value as JSON, either one value per line (JSON Lines) or as the elements of a
+/// single array.
+pub(crate) fn write_json<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let preserve_types = matches.get_flag("preserve-types");
+    let sort_keys = matches.get_flag("sort-keys");
+    let indent = match matches.get_one::<usize>("indent") {
+        Some(indent) => Some(*indent),
+        None if matches.get_flag("pretty") => Some(2),
+        None => None,
+    };
+    let ascii = matches.get_flag("ascii");
+    let array = matches.get_one::<String>("layout").unwrap() == "array";
+
+    if array {
+        write!(output, "[")?;
+    }
+    let mut count = 0;
+    for result in elements {
+        let element = result.with_context(|| "invalid input")?;
+        let mut value = if preserve_types {
+            to_typed_json_value(&element)?
+        } else {
+            to_json_value(element)?
+        };
+        if sort_keys {
+            sort_keys_of(&mut value);
+        }
+        let text = json_text(&value, indent, ascii)?;
+        if !array {
+            writeln!(output, "{}", text)?;
+            continue;
+        }
+        if count > 0 {
+            write!(output, ",")?;
+        }
+        count += 1;
+        match indent {
+            // Indent the whole value by one level so it lines up inside the array. JSON strings
+            // can't contain raw newlines, so every line break is between tokens.
+            Some(indent) => {
+                let padding = " ".repeat(indent);
+                write!(
+                    output,
+                    "\n{}{}",
+                    padding,
+                    text.replace('\n', &format!("\n{}", padding))
+                )?;
+            }
+            None => write!(output, "{}", text)?,
+        }
+    }
+    if array {
+        match indent {
+            Some(_) if count > 0 => writeln!(output, "\n]")?,
+            _ => writeln!(output, "]")?,
+        }
+    }
+    Ok(())
+}
+
+/// Serializes a value, indenting it by `indent` spaces per level if given.
+fn json_text(value: &JsonValue, indent: Option<usize>, ascii: bool) -> Result<String> {
+    let mut bytes = Vec::new();
+    match indent {
+        Some(indent) => {
+            let indent = " ".repeat(indent);
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            serialize(value, &mut bytes, formatter, ascii)?;
+        }
+        None => serialize(value, &mut bytes, CompactFormatter, ascii)?,
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn serialize<F: Formatter>(
+    value: &JsonValue,
+    bytes: &mut Vec<u8>,
+    formatter: F,
+    ascii: bool,
+) -> Result<()> {
+    if ascii {
+        value.serialize(&mut Serializer::with_formatter(
+            bytes,
+            AsciiFormatter(formatter),
+        ))?;
+    } else {
+        value.serialize(&mut Serializer::with_formatter(bytes, formatter))?;
+    }
+    Ok(())
+}
+
+fn sort_keys_of(value: &mut JsonValue) {
+    match value {
+        JsonValue::Array(values) => values.iter_mut().for_each(sort_keys_of),
+        JsonValue::Object(object) => {
+            let mut fields: Vec<(String, JsonValue)> = std::mem::take(object).into_iter().collect();
+            fields.sort_by(|(left, _), (right, _)| left.cmp(right));
+            for (name, mut value) in fields {
+                sort_keys_of(&mut value);
+                object.insert(name, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Wraps another formatter, escaping each non-ASCII character in strings as `\uXXXX` (or a
+/// surrogate pair of them) so that the output is plain ASCII.
+struct AsciiFormatter<F>(F);
+
+impl<F: Formatter> Formatter for AsciiFormatter<F> {
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        for c in fragment.chars() {
+            if c.is_ascii() {
+                writer.write_all(&[c as u8])?;
+            } else {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(writer, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Everything else, including layout, is left to the wrapped formatter.
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
+
+fn envelope(key: &str, value: JsonValue) -> JsonValue {
+    JsonValue::Object(Map::from_iter([(key.to_string(), value)]))
+}
+
+fn text_of<'a>(symbol: &'a ion_rs::Symbol, element: &Element) -> Result<&'a str> {
+    match symbol.text() {
+        Some(text) => Ok(text),
+        None => bail!(
+            "{} has a symbol with unknown text, which can't be written as JSON",
+            element_to_string(element)?
+        ),
+    }
+}
+
+/// Converts an Ion value to JSON without losing any information. Values that JSON can represent
+/// exactly are written as plain JSON; everything else is written as an object whose only key
+/// names the Ion type (see the `*_KEY` constants).
+pub(crate) fn to_typed_json_value(element: &Element) -> Result<JsonValue> {
+    let value = typed_json_value(element)?;
+    if element.annotations().next().is_none() {
+        return Ok(value);
+    }
+    let annotations = element
+        .annotations()
+        .map(|annotation| Ok(JsonValue::String(text_of(annotation, element)?.to_string())))
+        .collect::<Result<Vec<JsonValue>>>()?;
+    Ok(JsonValue::Object(Map::from_iter([
+        (ANNOTATIONS_KEY.to_string(), JsonValue::Array(annotations)),
+        (VALUE_KEY.to_string(), value),
+    ])))
+}
+
+fn typed_json_value(element: &Element) -> Result<JsonValue> {
+    if element.is_null() {
+        return match element.ion_type() {
+            IonType::Null => Ok(JsonValue::Null),
+            // Typed nulls are written as the type's name, like `int` for `null.int`.
+            _ => {
+                let text = element_to_string(&element.clone().with_annotations(vec![]))?;
+                Ok(envelope(NULL_KEY, text.trim_start_matches("null.").into()))
+            }
+        };
+    }
+    let value = match element.ion_type() {
+        IonType::Null => JsonValue::Null,
+        IonType::Boolean => JsonValue::Bool(element.as_bool().unwrap()),
+        IonType::Integer => JsonValue::Number(Number::from_str(
+            &element.as_integer().unwrap().to_string(),
+        )?),
+        IonType::Float => {
+            let value = element.as_f64().unwrap();
+            match Number::from_f64(value) {
+                Some(number) => JsonValue::Number(number),
+                None if value.is_nan() => envelope(FLOAT_KEY, "nan".into()),
+                None if value > 0.0 => envelope(FLOAT_KEY, "+inf".into()),
+                None => envelope(FLOAT_KEY, "-inf".into()),
+            }
+        }
+        // Decimals are written as Ion text, like `1750d-2`, which keeps their precision.
+        IonType::Decimal => envelope(
+            DECIMAL_KEY,
+            element.as_decimal().unwrap().to_string().into(),
+        ),
+        IonType::Timestamp => envelope(
+            TIMESTAMP_KEY,
+            element.as_timestamp().unwrap().to_string().into(),
+        ),
+        IonType::String => JsonValue::String(element.as_str().unwrap().to_string()),
+        IonType::Symbol => {
+            let text = text_of(element.as_sym().unwrap(), element)?;
+            envelope(SYMBOL_KEY, text.into())
+        }
+        IonType::Blob => envelope(BLOB_KEY, base64(element.as_bytes().unwrap()).into()),
+        IonType::Clob => envelope(CLOB_KEY, base64(element.as_bytes().unwrap()).into()),
+        IonType::List => JsonValue::Array(typed_json_values(element)?),
+        IonType::SExpression => envelope(SEXP_KEY, JsonValue::Array(typed_json_values(element)?)),
+        IonType::Struct => {
+            let fields = element
+                .as_struct()
+                .unwrap()
+                .iter()
+                .map(|(name, value)| Ok((text_of(name, element)?, to_typed_json_value(value)?)))
+                .collect::<Result<Vec<(&str, JsonValue)>>>()?;
+            let mut object = Map::new();
+            for (name, value) in &fields {
+                object.insert(name.to_string(), value.clone());
+            }
+            if object.len() == fields.len() && !object.keys().any(|k| k.starts_with(KEY_PREFIX)) {
+                JsonValue::Object(object)
+            } else {
+                // Repeated field names and names that could be mistaken for the keys above are
+                // written as a list of `[name, value]` pairs instead.
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, value)| JsonValue::Array(vec![name.into(), value]))
+                    .collect();
+                envelope(STRUCT_KEY, JsonValue::Array(fields))
+            }
+        }
+    };
+    Ok(value)
+}
+
+fn typed_json_values(element: &Element) -> Result<Vec<JsonValue>> {
+    element
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(to_typed_json_value)
+        .collect()
+}
+
+/// Encodes bytes as base64 text.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    // A blob's `Display` implementation writes its bytes as base64 text between braces.
+    Element::from(Value::Blob(bytes.to_vec()))
+        .to_string()
+        .replace("{{", "")
+        .replace("}}", "")
+}
+
+pub(crate) fn to_json_value(element: Element) -> Result<JsonValue> {
+    if element.is_null() {
+        Ok(JsonValue::Null)
+    } else {
+        let value = match element.ion_type() {
+            IonType::Null => JsonValue::Null,
+            IonType::Boolean => JsonValue::Bool(element.as_bool().unwrap()),
+            IonType::Integer => JsonValue::Number(
+                Number::from_str(&element.as_integer().unwrap().to_string())
+                    .with_context(|| format!("{element} could not be turned into a Number"))?,
+            ),
+            IonType::Float => {
+                let value = element.as_f64().unwrap();
+                if value.is_finite() {
+                    JsonValue::Number(
+                        Number::from_f64(value).with_context(|| {
+                            format!("{element} could not be turned into a Number")
+                        })?,
+                    )
+                } else {
+                    // +inf, -inf, and nan are not JSON numbers, and are written as null in
+                    // accordance with Ion's JSON down-conversion guidelines.
+                    JsonValue::Null
+                }
+            }
+            IonType::Decimal => JsonValue::Number(
+                Number::from_str(
+                    element
+                        .as_decimal()
+                        .unwrap()
+                        .to_string()
+                        .replace("d", "e")
+                        .as_str(),
+                )
+                .with_context(|| format!("{element} could not be turned into a Number"))?,
+            ),
+            IonType::Timestamp => JsonValue::String(element.as_timestamp().unwrap().to_string()),
+            IonType::Symbol | IonType::String => JsonValue::String(
+                element
+                    .as_str()
+                    .with_context(|| format!("{element} could not be turned into a String"))?
+                    .into(),
+            ),
+            IonType::Clob => {
+                JsonValue::String(element.to_string().replace("{{\"", "").replace("\"}}", ""))
+            }
+            IonType::Blob => {
+                JsonValue::String(element.to_string().replace("{{", "").replace("}}", ""))
+            }
+            IonType::List | IonType::SExpression => {
+                let result: Result<Vec<JsonValue>> = element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .map(|x| to_json_value(x.clone()))
+                    .collect();
+                JsonValue::Array(result?)
+            }
+            IonType::Struct => {
+                let result: Result<Map<String, JsonValue>> = element
+                    .as_struct()
+                    .unwrap()
+                    .fields()
+                    .map(|(k, v)| {
+                        to_json_value(v.clone()).map(|value| (k.text().unwrap().into(), value))
+                    })
+                    .collect();
+                JsonValue::Object(result?)
+            }
+        };
+        Ok(value)
+    }
+}
@@ -0,0 +1,212 @@
+use crate::commands::util::read_element;
+pub mod arrow;
+pub mod avro;
+pub mod cbor;
+pub mod csv;
+pub mod json;
+pub mod msgpack;
+pub mod parquet;
+pub mod protobuf;
+pub mod sql;
+pub mod toml;
+pub mod xml;
+pub mod yaml;
+
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::{Decimal, IonReader, Reader, ReaderBuilder, StreamItem, Timestamp};
+use std::fs::File;
+use std::io::{stdin, stdout, Write};
+use std::iter;
+
+const ABOUT: &str =
+    "Converts data from Ion into a requested format. Currently supports json, csv, tsv, \
+                     yaml, cbor, msgpack, toml, xml, avro, parquet, arrow, protobuf, and sql.";
+
+// Creates a `clap` (Command Line Arguments Parser) configuration for the `to` command.
+// This function is invoked by the `to` command's parent, `beta`, so it can describe its
+// child commands.
+pub fn app() -> Command {
+    Command::new("to")
+        .about(ABOUT)
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            Arg::new("format")
+                .index(1)
+                .required(true)
+                .value_parser([
+                    "json", "csv", "tsv", "yaml", "cbor", "msgpack", "toml", "xml", "avro",
+                    "parquet", "arrow", "protobuf", "sql",
+                ])
+                .help("Output format"),
+        )
+        .arg(
+            // All argv entries after the format and any `clap`-managed options are considered
+            // input files.
+            Arg::new("input")
+                .index(2)
+                .trailing_var_arg(true)
+                .action(ArgAction::Append)
+                .help("Input file"),
+        )
+        .arg(Arg::new("schema").long("schema").help(
+            "The schema file that each value is written with: an Avro schema (.avsc) for \
+                     avro, which requires one, or an Ion struct of column types for parquet and arrow",
+        ))
+        .args(csv::args())
+        .args(json::args())
+        .args(msgpack::args())
+        .args(xml::args())
+        .args(arrow::args())
+        .args(protobuf::args())
+        .args(sql::args())
+}
+
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    let format = matches
+        .get_one::<String>("format")
+        .with_context(|| "No `format` was specified.")?
+        .as_str();
+
+    // Parquet's writer may hand the output to another thread, so it has to be `Send`.
+    let output: Box<dyn Write + Send> = match matches.get_one::<String>("output") {
+        Some(output_file) => Box::new(File::create(output_file).with_context(|| {
+            format!(
+                "could not open file output file '{}' for writing",
+                output_file
+            )
+        })?),
+        None => Box::new(stdout()),
+    };
+
+    // Each input is read with a streaming reader, so the first value is converted as soon as it
+    // arrives and only one top-level value at a time is held in memory.
+    let mut readers = Vec::new();
+    if let Some(input_file_iter) = matches.get_many::<String>("input") {
+        for input_file in input_file_iter {
+            let file = File::open(input_file)
+                .with_context(|| format!("Could not open file '{}'", input_file))?;
+            readers.push(ReaderBuilder::new().build(file)?);
+        }
+    } else {
+        readers.push(ReaderBuilder::new().build(stdin().lock())?);
+    }
+    convert(readers, output, format, matches)
+}
+
+/// Reads the values in each of the readers as a single stream and writes them to the output in
+/// the requested format.
+pub fn convert(
+    readers: Vec<Reader<'static>>,
+    output: Box<dyn Write + Send>,
+    format: &str,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let elements = readers.into_iter().flat_map(|mut reader| {
+        iter::from_fn(move || match reader.next() {
+            Ok(StreamItem::Value(ion_type) | StreamItem::Null(ion_type)) => {
+                Some(read_element(&mut reader, ion_type))
+            }
+            Ok(StreamItem::Nothing) => None,
+            Err(error) => Some(Err(error)),
+        })
+    });
+    if format == "parquet" {
+        return parquet::write_parquet(elements, output, matches);
+    }
+    let mut output: Box<dyn Write> = output;
+    let output = &mut output;
+    match format {
+        "json" => json::write_json(elements, output, matches)?,
+        "csv" => csv::write_csv(elements, output, b',', matches)?,
+        "tsv" => csv::write_csv(elements, output, b'\t', matches)?,
+        "yaml" => yaml::write_yaml(elements, output)?,
+        "cbor" => cbor::write_cbor(elements, output)?,
+        "msgpack" => msgpack::write_msgpack(elements, output, matches)?,
+        "toml" => toml::write_toml(elements, output)?,
+        "xml" => xml::write_xml(elements, output, matches)?,
+        "avro" => avro::write_avro(elements, output, matches)?,
+        "arrow" => arrow::write_arrow(elements, output, matches)?,
+        "protobuf" => protobuf::write_protobuf(elements, output, matches)?,
+        "sql" => sql::write_sql(elements, output, matches)?,
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'json', 'csv', 'tsv', 'yaml', 'cbor', 'msgpack', 'toml', 'xml', 'avro', 'parquet', 'arrow', 'protobuf', or 'sql'",
+            unrecognized
+        ),
+    };
+    output.flush()?;
+    Ok(())
+}
+
+/// Writes a decimal in plain notation (for example, `17.50` rather than `1750d-2`) for formats
+/// that don't have a notation of their own for decimals.
+pub(crate) fn decimal_text(decimal: &Decimal) -> String {
+    // Ion's decimal `Display` implementation writes the coefficient and exponent as `<c>d<e>`.
+    let text = decimal.to_string();
+    let (coefficient, exponent) = text.split_once('d').unwrap();
+    let exponent: i64 = exponent.parse().unwrap();
+    let (sign, digits) = match coefficient.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", coefficient),
+    };
+    if exponent >= 0 {
+        return format!("{}{}{}", sign, digits, "0".repeat(exponent as usize));
+    }
+    let scale = exponent.unsigned_abs() as usize;
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+/// Returns the number of seconds and nanoseconds between 1970-01-01T00:00:00Z and the timestamp.
+/// Fields beyond the timestamp's precision are treated as their lowest value, and an unknown
+/// offset is treated as UTC.
+pub(crate) fn epoch_seconds(timestamp: &Timestamp) -> (i64, u32) {
+    let (seconds, nanoseconds) = local_epoch_seconds(timestamp);
+    let offset_minutes = timestamp.offset().unwrap_or(0) as i64;
+    (seconds - offset_minutes * 60, nanoseconds)
+}
+
+/// Like [epoch_seconds], but counts to the timestamp's local date and time, ignoring its offset,
+/// as Avro's dates and local timestamps do. `2020-05-06T07:00-07:00` is 07:00 on 2020-05-06.
+pub(crate) fn local_epoch_seconds(timestamp: &Timestamp) -> (i64, u32) {
+    // Timestamp's `Display` implementation writes Ion text, like `2020-05-06T07:08:09.1-07:30`.
+    let text = timestamp.to_string();
+    let (date, time) = text.split_once('T').unwrap();
+    let mut date_fields = date.split('-').map(|field| field.parse::<i64>().unwrap());
+    let year = date_fields.next().unwrap();
+    let month = date_fields.next().unwrap_or(1);
+    let day = date_fields.next().unwrap_or(1);
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+    let mut nanoseconds = 0;
+    if !time.is_empty() {
+        let offset_start = time.rfind(['+', '-', 'Z']).unwrap();
+        let mut clock_fields = time[..offset_start].split(':');
+        let hour: i64 = clock_fields.next().unwrap().parse().unwrap();
+        let minute: i64 = clock_fields.next().unwrap().parse().unwrap();
+        if let Some(second) = clock_fields.next() {
+            let (second, fraction) = second.split_once('.').unwrap_or((second, ""));
+            seconds += second.parse::<i64>().unwrap();
+            let fraction = format!("{:0<9}", &fraction[..fraction.len().min(9)]);
+            nanoseconds = fraction.parse().unwrap();
+        }
+        seconds += hour * 3600 + minute * 60;
+    }
+    (seconds, nanoseconds)
+}
+
+/// Returns the number of days between 1970-01-01 and the given proleptic Gregorian date, using
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
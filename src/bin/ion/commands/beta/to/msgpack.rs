@@ -0,0 +1,154 @@
+use crate::commands::beta::to::epoch_seconds;
+use anyhow::{bail, Context, Result};
+use clap::{value_parser, Arg, ArgMatches};
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::{Integer, IonResult, IonType};
+use rmpv::Value as MsgPackValue;
+use std::io::Write;
+
+/// The extension type that MessagePack reserves for timestamps.
+pub(crate) const TIMESTAMP_EXT_TYPE: i8 = -1;
+
+// Options shared by the `to` and `from` commands.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("decimal-ext-type")
+            .long("decimal-ext-type")
+            .default_value("1")
+            .value_parser(value_parser!(i8).range(0..=127))
+            .help_heading("MessagePack options")
+            .help("The extension type for decimals, whose data is the decimal as UTF-8 Ion text"),
+        Arg::new("annotations-ext-type")
+            .long("annotations-ext-type")
+            .default_value("2")
+            .value_parser(value_parser!(i8).range(0..=127))
+            .help_heading("MessagePack options")
+            .help(
+                "The extension type for annotated values, whose data is the MessagePack array \
+                 [[annotation, ...], value]",
+            ),
+    ]
+}
+
+/// The application-specific extension types chosen with the MessagePack options.
+pub(crate) struct ExtTypes {
+    pub decimal: i8,
+    pub annotations: i8,
+}
+
+impl ExtTypes {
+    pub(crate) fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        // Both options have default values, so we can unwrap these safely.
+        let ext_types = ExtTypes {
+            decimal: *matches.get_one::<i8>("decimal-ext-type").unwrap(),
+            annotations: *matches.get_one::<i8>("annotations-ext-type").unwrap(),
+        };
+        // A reader couldn't tell a decimal from an annotated value.
+        if ext_types.decimal == ext_types.annotations {
+            bail!(
+                "--decimal-ext-type and --annotations-ext-type must be different; both are {}",
+                ext_types.decimal
+            );
+        }
+        Ok(ext_types)
+    }
+}
+
+/// Writes each top-level value as a MessagePack object as soon as it has been read.
+pub(crate) fn write_msgpack<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let ext_types = ExtTypes::from_matches(matches)?;
+    for element in elements {
+        let value = to_msgpack_value(&element?, &ext_types)?;
+        rmpv::encode::write_value(output, &value).with_context(|| "Could not write MessagePack")?;
+    }
+    Ok(())
+}
+
+/// Converts an Ion value to MessagePack. Blobs and clobs become bin, timestamps use the
+/// timestamp extension type, and decimals and annotated values use the configured extension
+/// types. Ints too large for 64 bits become strings.
+fn to_msgpack_value(element: &Element, ext_types: &ExtTypes) -> Result<MsgPackValue> {
+    let value = if element.is_null() {
+        MsgPackValue::Nil
+    } else {
+        match element.ion_type() {
+            IonType::Null => MsgPackValue::Nil,
+            IonType::Boolean => MsgPackValue::Boolean(element.as_bool().unwrap()),
+            IonType::Integer => match element.as_integer().unwrap() {
+                Integer::I64(value) => MsgPackValue::from(*value),
+                // Only big ints between i64::MAX and u64::MAX fit in a MessagePack int.
+                Integer::BigInt(value) => match u64::try_from(value) {
+                    Ok(value) => MsgPackValue::from(value),
+                    Err(_) => MsgPackValue::from(value.to_string()),
+                },
+            },
+            IonType::Float => MsgPackValue::F64(element.as_f64().unwrap()),
+            IonType::Decimal => MsgPackValue::Ext(
+                ext_types.decimal,
+                // Ion text, like `2150d-2`, which keeps the exponent of any decimal.
+                element.as_decimal().unwrap().to_string().into_bytes(),
+            ),
+            IonType::Timestamp => {
+                let (seconds, nanoseconds) = epoch_seconds(element.as_timestamp().unwrap());
+                MsgPackValue::Ext(TIMESTAMP_EXT_TYPE, timestamp_data(seconds, nanoseconds))
+            }
+            IonType::Symbol | IonType::String => {
+                MsgPackValue::from(element.as_str().unwrap_or_default())
+            }
+            IonType::Clob | IonType::Blob => {
+                MsgPackValue::Binary(element.as_bytes().unwrap().to_vec())
+            }
+            IonType::List | IonType::SExpression => MsgPackValue::Array(
+                element
+                    .as_sequence()
+                    .unwrap()
+                    .iter()
+                    .map(|value| to_msgpack_value(value, ext_types))
+                    .collect::<Result<_>>()?,
+            ),
+            IonType::Struct => MsgPackValue::Map(
+                element
+                    .as_struct()
+                    .unwrap()
+                    .fields()
+                    .map(|(name, value)| {
+                        let name = MsgPackValue::from(name.text().unwrap_or("$0"));
+                        Ok((name, to_msgpack_value(value, ext_types)?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        }
+    };
+
+    let annotations: Vec<MsgPackValue> = element
+        .annotations()
+        .map(|annotation| MsgPackValue::from(annotation.text().unwrap_or("$0")))
+        .collect();
+    if annotations.is_empty() {
+        return Ok(value);
+    }
+    let mut data = Vec::new();
+    let wrapped = MsgPackValue::Array(vec![MsgPackValue::Array(annotations), value]);
+    rmpv::encode::write_value(&mut data, &wrapped)?;
+    Ok(MsgPackValue::Ext(ext_types.annotations, data))
+}
+
+/// Encodes an instant using the smallest of the timestamp extension's 32, 64, and 96-bit formats
+/// that can represent it.
+fn timestamp_data(seconds: i64, nanoseconds: u32) -> Vec<u8> {
+    if seconds >= 0 && seconds >> 34 == 0 {
+        let data = (u64::from(nanoseconds) << 34) | seconds as u64;
+        if data >> 32 == 0 {
+            return (data as u32).to_be_bytes().to_vec();
+        }
+        return data.to_be_bytes().to_vec();
+    }
+    let mut data = nanoseconds.to_be_bytes().to_vec();
+    data.extend_from_slice(&seconds.to_be_bytes());
+    data
+}
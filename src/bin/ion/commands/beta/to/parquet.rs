@@ -0,0 +1,32 @@
+use crate::commands::beta::to::arrow::write_record_batches;
+use anyhow::Result;
+use clap::ArgMatches;
+use ion_rs::value::owned::Element;
+use ion_rs::IonResult;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+
+/// Writes a stream of structs as the rows of a Parquet file, one row group per `--batch-size`
+/// rows. See `write_record_batches` for how the columns are chosen.
+pub(crate) fn write_parquet<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: Box<dyn Write + Send>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let batch_size = *matches.get_one::<u64>("batch-size").unwrap() as usize;
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(batch_size)
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let writer = write_record_batches(
+        elements,
+        "Parquet",
+        matches,
+        |schema| Ok(ArrowWriter::try_new(output, schema, Some(properties))?),
+        |writer, batch| Ok(writer.write(&batch)?),
+    )?;
+    writer.close()?;
+    Ok(())
+}
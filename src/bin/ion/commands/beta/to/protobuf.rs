@@ -0,0 +1,375 @@
+use crate::commands::beta::to::avro::unscaled_decimal;
+use crate::commands::beta::to::{decimal_text, epoch_seconds};
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgMatches};
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::{Integer, IonResult, IonType};
+use prost_reflect::prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MapKey, MessageDescriptor,
+    Value as ProtoValue,
+};
+use std::collections::HashMap;
+use std::io::Write;
+
+const HEADING: &str = "Protobuf options";
+
+/// The well-known types that hold a single field named `value`, which are converted to and from
+/// that field's value on its own.
+pub(crate) const WRAPPERS: &[&str] = &[
+    "google.protobuf.DoubleValue",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int64Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Int32Value",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.BoolValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.BytesValue",
+];
+
+// Format-specific options for the `to` and `from` commands.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("descriptor-set")
+            .long("descriptor-set")
+            .help_heading(HEADING)
+            .help(
+                "A compiled FileDescriptorSet (for example, from `protoc --include_imports \
+                 --descriptor_set_out`) that defines the message; required for protobuf",
+            ),
+        Arg::new("message")
+            .long("message")
+            .help_heading(HEADING)
+            .help(
+                "The fully qualified name of the message type, like pkg.Msg; required for protobuf",
+            ),
+    ]
+}
+
+/// Returns the `--message` type from the `--descriptor-set` file.
+pub(crate) fn message_descriptor(matches: &ArgMatches) -> Result<MessageDescriptor> {
+    let (descriptor_set_file, message) = match (
+        matches.get_one::<String>("descriptor-set"),
+        matches.get_one::<String>("message"),
+    ) {
+        (Some(descriptor_set_file), Some(message)) => (descriptor_set_file, message),
+        _ => bail!(
+            "Converting protobuf requires a descriptor set and a message type (see \
+             --descriptor-set and --message)"
+        ),
+    };
+    let bytes = std::fs::read(descriptor_set_file)
+        .with_context(|| format!("Could not open file '{}'", descriptor_set_file))?;
+    let pool = DescriptorPool::decode(bytes.as_slice())
+        .with_context(|| format!("Could not read descriptor set '{}'", descriptor_set_file))?;
+    pool.get_message_by_name(message.trim_start_matches('.'))
+        .with_context(|| {
+            format!(
+                "The descriptor set '{}' has no message '{}'",
+                descriptor_set_file, message
+            )
+        })
+}
+
+/// Writes each value as a length-delimited `--message` in the `--descriptor-set`. Values that
+/// don't fit the message are rejected with an error naming their path.
+pub(crate) fn write_protobuf<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let descriptor = message_descriptor(matches)?;
+    for (index, element) in elements.enumerate() {
+        let message = to_message(&element?, &descriptor, &index_path("", index))?;
+        output.write_all(&message.encode_length_delimited_to_vec())?;
+    }
+    Ok(())
+}
+
+/// Converts a struct to a message, looking its fields up by their names in the .proto file or by
+/// their JSON names. Timestamps, decimal seconds, and bare values are converted to the well-known
+/// `Timestamp`, `Duration`, and wrapper types.
+fn to_message(
+    element: &Element,
+    descriptor: &MessageDescriptor,
+    path: &str,
+) -> Result<DynamicMessage> {
+    let mut message = DynamicMessage::new(descriptor.clone());
+    match descriptor.full_name() {
+        "google.protobuf.Timestamp" if element.ion_type() == IonType::Timestamp => {
+            let (seconds, nanoseconds) = epoch_seconds(element.as_timestamp().unwrap());
+            message.set_field_by_name("seconds", ProtoValue::I64(seconds));
+            message.set_field_by_name("nanos", ProtoValue::I32(nanoseconds as i32));
+            return Ok(message);
+        }
+        "google.protobuf.Duration"
+            if matches!(element.ion_type(), IonType::Decimal | IonType::Integer) =>
+        {
+            let nanoseconds = unscaled_decimal(element, 9)?
+                .and_then(|nanoseconds| i128::try_from(&nanoseconds).ok())
+                .and_then(|nanoseconds| {
+                    i64::try_from(nanoseconds / 1_000_000_000)
+                        .ok()
+                        .map(|seconds| (seconds, (nanoseconds % 1_000_000_000) as i32))
+                });
+            let (seconds, nanoseconds) = match nanoseconds {
+                Some(duration) => duration,
+                None => bail!(
+                    "Cannot convert {} to Protobuf: {} is not a whole number of nanoseconds \
+                     that fits in a Duration",
+                    path,
+                    element_to_string(element)?
+                ),
+            };
+            message.set_field_by_name("seconds", ProtoValue::I64(seconds));
+            message.set_field_by_name("nanos", ProtoValue::I32(nanoseconds));
+            return Ok(message);
+        }
+        name if WRAPPERS.contains(&name) && !element.is_null() => {
+            let field = descriptor.get_field_by_name("value").unwrap();
+            message.set_field(&field, to_value(element, &field.kind(), path)?);
+            return Ok(message);
+        }
+        _ => {}
+    }
+
+    let fields = match element.as_struct() {
+        Some(fields) => fields,
+        None => return mismatch(element, &Kind::Message(descriptor.clone()), path),
+    };
+    for (name, value) in fields.fields() {
+        let name = name.text().unwrap_or_default();
+        let value_path = field_path(path, name);
+        let field = match descriptor
+            .get_field_by_name(name)
+            .or_else(|| descriptor.get_field_by_json_name(name))
+        {
+            Some(field) => field,
+            None => bail!(
+                "Cannot convert {} to Protobuf: the message '{}' has no field '{}'",
+                value_path,
+                descriptor.full_name(),
+                name
+            ),
+        };
+        if message.has_field(&field) {
+            bail!(
+                "Cannot convert {} to Protobuf: the field is repeated",
+                value_path
+            );
+        }
+        if let Some(oneof) = field.containing_oneof() {
+            if let Some(other) = oneof.fields().find(|other| message.has_field(other)) {
+                bail!(
+                    "Cannot convert {} to Protobuf: '{}' is already set, and only one field of \
+                     the oneof '{}' can be",
+                    value_path,
+                    other.name(),
+                    oneof.name()
+                );
+            }
+        }
+        // A null field is left unset.
+        if value.is_null() {
+            continue;
+        }
+        message.set_field(&field, to_field_value(value, &field, &value_path)?);
+    }
+    Ok(message)
+}
+
+/// Converts a field's value, which is a list for a repeated field and a struct for a map field.
+fn to_field_value(element: &Element, field: &FieldDescriptor, path: &str) -> Result<ProtoValue> {
+    if field.is_map() {
+        let entry = match field.kind() {
+            Kind::Message(entry) => entry,
+            _ => unreachable!("map fields are map entry messages"),
+        };
+        let (key_field, value_field) = (entry.map_entry_key_field(), entry.map_entry_value_field());
+        let fields = match element.as_struct() {
+            Some(fields) => fields,
+            None => bail!(
+                "Cannot convert {} to Protobuf: {} is not a struct, which the map field '{}' \
+                 requires",
+                path,
+                element_to_string(element)?,
+                field.name()
+            ),
+        };
+        let mut map = HashMap::new();
+        for (name, value) in fields.fields() {
+            let name = name.text().unwrap_or_default();
+            let value_path = field_path(path, name);
+            let key = map_key(name, &key_field.kind()).with_context(|| {
+                format!(
+                    "Cannot convert {} to Protobuf: '{}' is not a key of type {}",
+                    value_path,
+                    name,
+                    kind_name(&key_field.kind())
+                )
+            })?;
+            map.insert(key, to_value(value, &value_field.kind(), &value_path)?);
+        }
+        return Ok(ProtoValue::Map(map));
+    }
+    if field.is_list() {
+        let values = match element.ion_type() {
+            IonType::List | IonType::SExpression => element.as_sequence().unwrap(),
+            _ => bail!(
+                "Cannot convert {} to Protobuf: {} is not a list, which the repeated field \
+                 '{}' requires",
+                path,
+                element_to_string(element)?,
+                field.name()
+            ),
+        };
+        return Ok(ProtoValue::List(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| to_value(value, &field.kind(), &index_path(path, index)))
+                .collect::<Result<Vec<ProtoValue>>>()?,
+        ));
+    }
+    to_value(element, &field.kind(), path)
+}
+
+/// Parses a map key written as a struct field name.
+fn map_key(name: &str, kind: &Kind) -> Option<MapKey> {
+    let key = match kind {
+        Kind::String => MapKey::String(name.to_string()),
+        Kind::Bool => MapKey::Bool(name.parse().ok()?),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => MapKey::I32(name.parse().ok()?),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => MapKey::I64(name.parse().ok()?),
+        Kind::Uint32 | Kind::Fixed32 => MapKey::U32(name.parse().ok()?),
+        Kind::Uint64 | Kind::Fixed64 => MapKey::U64(name.parse().ok()?),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Converts a single value of the given type.
+fn to_value(element: &Element, kind: &Kind, path: &str) -> Result<ProtoValue> {
+    if let Kind::Message(descriptor) = kind {
+        return Ok(ProtoValue::Message(to_message(element, descriptor, path)?));
+    }
+    if element.is_null() {
+        return mismatch(element, kind, path);
+    }
+    let integer = || -> Option<i128> {
+        match element.as_integer()? {
+            Integer::I64(value) => Some(i128::from(*value)),
+            Integer::BigInt(value) => i128::try_from(value).ok(),
+        }
+    };
+    let value = match (kind, element.ion_type()) {
+        (Kind::Bool, IonType::Boolean) => ProtoValue::Bool(element.as_bool().unwrap()),
+        (Kind::Int32 | Kind::Sint32 | Kind::Sfixed32, IonType::Integer) => {
+            match integer().and_then(|value| i32::try_from(value).ok()) {
+                Some(value) => ProtoValue::I32(value),
+                None => return out_of_range(element, kind, path),
+            }
+        }
+        (Kind::Int64 | Kind::Sint64 | Kind::Sfixed64, IonType::Integer) => {
+            match integer().and_then(|value| i64::try_from(value).ok()) {
+                Some(value) => ProtoValue::I64(value),
+                None => return out_of_range(element, kind, path),
+            }
+        }
+        (Kind::Uint32 | Kind::Fixed32, IonType::Integer) => {
+            match integer().and_then(|value| u32::try_from(value).ok()) {
+                Some(value) => ProtoValue::U32(value),
+                None => return out_of_range(element, kind, path),
+            }
+        }
+        (Kind::Uint64 | Kind::Fixed64, IonType::Integer) => {
+            match integer().and_then(|value| u64::try_from(value).ok()) {
+                Some(value) => ProtoValue::U64(value),
+                None => return out_of_range(element, kind, path),
+            }
+        }
+        (Kind::Double | Kind::Float, IonType::Float | IonType::Decimal | IonType::Integer) => {
+            let value: f64 = match element.ion_type() {
+                IonType::Float => element.as_f64().unwrap(),
+                IonType::Decimal => decimal_text(element.as_decimal().unwrap()).parse()?,
+                _ => element.as_integer().unwrap().to_string().parse()?,
+            };
+            match kind {
+                Kind::Float => ProtoValue::F32(value as f32),
+                _ => ProtoValue::F64(value),
+            }
+        }
+        (Kind::String, IonType::String | IonType::Symbol) => {
+            ProtoValue::String(element.as_str().unwrap_or_default().to_string())
+        }
+        (Kind::Bytes, IonType::Blob | IonType::Clob) => {
+            ProtoValue::Bytes(element.as_bytes().unwrap().to_vec().into())
+        }
+        (Kind::Enum(descriptor), IonType::Symbol | IonType::String) => {
+            let name = element.as_str().unwrap_or_default();
+            match descriptor.get_value_by_name(name) {
+                Some(value) => ProtoValue::EnumNumber(value.number()),
+                None => bail!(
+                    "Cannot convert {} to Protobuf: '{}' is not one of the enum '{}' values",
+                    path,
+                    name,
+                    descriptor.full_name()
+                ),
+            }
+        }
+        // Enums can also be given as numbers, including ones the enum doesn't name.
+        (Kind::Enum(_), IonType::Integer) => {
+            match integer().and_then(|value| i32::try_from(value).ok()) {
+                Some(value) => ProtoValue::EnumNumber(value),
+                None => return out_of_range(element, kind, path),
+            }
+        }
+        _ => return mismatch(element, kind, path),
+    };
+    Ok(value)
+}
+
+fn mismatch<T>(element: &Element, kind: &Kind, path: &str) -> Result<T> {
+    bail!(
+        "Cannot convert {} to Protobuf: {} does not match the field type {}",
+        path,
+        element_to_string(element)?,
+        kind_name(kind)
+    )
+}
+
+fn out_of_range<T>(element: &Element, kind: &Kind, path: &str) -> Result<T> {
+    bail!(
+        "Cannot convert {} to Protobuf: {} is out of range for {}",
+        path,
+        element_to_string(element)?,
+        kind_name(kind)
+    )
+}
+
+/// Returns the name of the type as it's written in a .proto file.
+fn kind_name(kind: &Kind) -> String {
+    let name = match kind {
+        Kind::Double => "double",
+        Kind::Float => "float",
+        Kind::Int32 => "int32",
+        Kind::Int64 => "int64",
+        Kind::Uint32 => "uint32",
+        Kind::Uint64 => "uint64",
+        Kind::Sint32 => "sint32",
+        Kind::Sint64 => "sint64",
+        Kind::Fixed32 => "fixed32",
+        Kind::Fixed64 => "fixed64",
+        Kind::Sfixed32 => "sfixed32",
+        Kind::Sfixed64 => "sfixed64",
+        Kind::Bool => "bool",
+        Kind::String => "string",
+        Kind::Bytes => "bytes",
+        Kind::Message(descriptor) => descriptor.full_name(),
+        Kind::Enum(descriptor) => descriptor.full_name(),
+    };
+    name.to_string()
+}
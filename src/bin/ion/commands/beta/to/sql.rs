@@ -0,0 +1,275 @@
+use crate::commands::beta::to::decimal_text;
+use crate::commands::beta::to::json::to_json_value;
+use crate::commands::util::{element_to_string, index_path};
+use anyhow::{bail, Result};
+use clap::{Arg, ArgAction, ArgMatches};
+use ion_rs::types::timestamp::Precision;
+use ion_rs::value::owned::Element;
+use ion_rs::value::IonElement;
+use ion_rs::{IonResult, IonType, Timestamp};
+use serde_json::Value as JsonValue;
+use std::io::Write;
+
+const HEADING: &str = "SQL options";
+
+// Format-specific options for the `to` command.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("table")
+            .long("table")
+            .help_heading(HEADING)
+            .help("The table to insert each struct into; required for sql"),
+        Arg::new("dialect")
+            .long("dialect")
+            .default_value("sqlite")
+            .value_parser(["sqlite", "postgres"])
+            .help_heading(HEADING)
+            .help("The database the statements are written for"),
+        Arg::new("create-table")
+            .long("create-table")
+            .action(ArgAction::SetTrue)
+            .help_heading(HEADING)
+            .help(
+                "Write a CREATE TABLE statement first, with a column for every field name typed \
+                 from the field's values",
+            ),
+    ]
+}
+
+/// The type of a column, inferred from the Ion types of the values written to it.
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+    Boolean,
+    Integer,
+    Float,
+    Decimal,
+    Timestamp,
+    Text,
+    Binary,
+    Nested,
+}
+
+impl ColumnType {
+    fn of(element: &Element) -> Option<Self> {
+        if element.is_null() {
+            return None;
+        }
+        let column_type = match element.ion_type() {
+            IonType::Null => return None,
+            IonType::Boolean => ColumnType::Boolean,
+            IonType::Integer => ColumnType::Integer,
+            IonType::Float => ColumnType::Float,
+            IonType::Decimal => ColumnType::Decimal,
+            IonType::Timestamp => ColumnType::Timestamp,
+            IonType::String | IonType::Symbol => ColumnType::Text,
+            IonType::Blob | IonType::Clob => ColumnType::Binary,
+            IonType::List | IonType::SExpression | IonType::Struct => ColumnType::Nested,
+        };
+        Some(column_type)
+    }
+
+    /// Returns a type that can hold values of both types. Types that can't be reconciled become
+    /// text.
+    fn merge(self, other: ColumnType) -> ColumnType {
+        use ColumnType::*;
+        match (self, other) {
+            (left, right) if left == right => left,
+            (Integer, Float) | (Float, Integer) | (Float, Decimal) | (Decimal, Float) => Float,
+            (Integer, Decimal) | (Decimal, Integer) => Decimal,
+            _ => Text,
+        }
+    }
+
+    fn name(self, dialect: &str, nested: &str) -> &'static str {
+        let postgres = dialect == "postgres";
+        match self {
+            ColumnType::Boolean => "BOOLEAN",
+            ColumnType::Integer if postgres => "BIGINT",
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Float if postgres => "DOUBLE PRECISION",
+            ColumnType::Float => "REAL",
+            ColumnType::Decimal => "NUMERIC",
+            ColumnType::Timestamp if postgres => "TIMESTAMPTZ",
+            ColumnType::Binary if postgres => "BYTEA",
+            ColumnType::Binary => "BLOB",
+            ColumnType::Nested if postgres && nested == "json" => "JSONB",
+            ColumnType::Timestamp | ColumnType::Text | ColumnType::Nested => "TEXT",
+        }
+    }
+}
+
+/// Writes each struct as an INSERT statement of its fields into the `--table`, after a CREATE
+/// TABLE statement for the union of their field names if `--create-table` is given.
+pub(crate) fn write_sql<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let table = match matches.get_one::<String>("table") {
+        Some(table) => table
+            .split('.')
+            .map(identifier)
+            .collect::<Vec<String>>()
+            .join("."),
+        None => bail!("Converting to SQL requires a table name (see --table)"),
+    };
+    let dialect = matches.get_one::<String>("dialect").unwrap().as_str();
+    let nested = matches.get_one::<String>("nested").unwrap().as_str();
+    let rows = elements
+        .enumerate()
+        .map(|(index, element)| row(element?, &index_path("", index)));
+
+    if !matches.get_flag("create-table") {
+        // Each statement names its own columns, so it can be written as soon as its row is read.
+        for row in rows {
+            writeln!(output, "{}", insert(&table, &row?, dialect, nested)?)?;
+        }
+        return Ok(());
+    }
+
+    // The columns are the union of every struct's field names, so every row has to be read
+    // before the first statement can be written.
+    let rows = rows.collect::<Result<Vec<Element>>>()?;
+    let mut columns: Vec<(&str, Option<ColumnType>)> = Vec::new();
+    for row in &rows {
+        for (name, value) in row.as_struct().unwrap().fields() {
+            let name = name.text().unwrap_or_default();
+            let value_type = ColumnType::of(value);
+            match columns.iter_mut().find(|(column, _)| *column == name) {
+                Some((_, column_type)) => {
+                    *column_type = match (*column_type, value_type) {
+                        (Some(column_type), Some(value_type)) => {
+                            Some(column_type.merge(value_type))
+                        }
+                        (column_type, value_type) => column_type.or(value_type),
+                    }
+                }
+                None => columns.push((name, value_type)),
+            }
+        }
+    }
+    let definitions: Vec<String> = columns
+        .iter()
+        .map(|(name, column_type)| {
+            // A column that only ever held nulls is given the most accepting type.
+            let column_type = column_type.unwrap_or(ColumnType::Text);
+            format!("{} {}", identifier(name), column_type.name(dialect, nested))
+        })
+        .collect();
+    writeln!(
+        output,
+        "CREATE TABLE {} ({});",
+        table,
+        definitions.join(", ")
+    )?;
+    for row in &rows {
+        writeln!(output, "{}", insert(&table, row, dialect, nested)?)?;
+    }
+    Ok(())
+}
+
+/// Fails unless the value is a struct without repeated field names.
+fn row(element: Element, path: &str) -> Result<Element> {
+    let fields = match element.as_struct() {
+        Some(fields) => fields,
+        None => bail!(
+            "Cannot convert {} to SQL: only structs can be written as rows",
+            path
+        ),
+    };
+    let mut names = Vec::new();
+    for (name, _) in fields.fields() {
+        let name = name.text().unwrap_or_default();
+        if names.contains(&name) {
+            bail!(
+                "Cannot convert {} to SQL: the field '{}' is repeated",
+                path,
+                name
+            );
+        }
+        names.push(name);
+    }
+    Ok(element)
+}
+
+fn insert(table: &str, row: &Element, dialect: &str, nested: &str) -> Result<String> {
+    let (mut columns, mut values) = (Vec::new(), Vec::new());
+    for (name, value) in row.as_struct().unwrap().fields() {
+        columns.push(identifier(name.text().unwrap_or_default()));
+        values.push(literal(value, dialect, nested)?);
+    }
+    if columns.is_empty() {
+        return Ok(format!("INSERT INTO {} DEFAULT VALUES;", table));
+    }
+    Ok(format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        table,
+        columns.join(", "),
+        values.join(", ")
+    ))
+}
+
+/// Writes a value as a SQL literal. Lobs are written as byte strings and decimals in plain
+/// notation, so they aren't read as floats; other scalars are written the way they are in JSON.
+fn literal(value: &Element, dialect: &str, nested: &str) -> Result<String> {
+    if value.is_null() {
+        return Ok("NULL".to_string());
+    }
+    let text = match value.ion_type() {
+        IonType::Blob | IonType::Clob => {
+            let hex: String = value
+                .as_bytes()
+                .unwrap()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            match dialect {
+                "postgres" => format!("'\\x{}'", hex),
+                _ => format!("X'{}'", hex),
+            }
+        }
+        IonType::Decimal => decimal_text(value.as_decimal().unwrap()),
+        IonType::Timestamp => string(&date_time(value.as_timestamp().unwrap())),
+        IonType::List | IonType::SExpression | IonType::Struct => match nested {
+            "ion" => string(&element_to_string(value)?),
+            "json" => string(&to_json_value(value.clone())?.to_string()),
+            _ => bail!(
+                "Cannot convert {} to SQL: nested values are not allowed (see --nested)",
+                element_to_string(value)?
+            ),
+        },
+        _ => match to_json_value(value.clone())? {
+            // Includes floats that are infinite or not a number, which SQL has no literal for.
+            JsonValue::Null => "NULL".to_string(),
+            JsonValue::Bool(true) => "TRUE".to_string(),
+            JsonValue::Bool(false) => "FALSE".to_string(),
+            JsonValue::Number(number) => number.to_string(),
+            JsonValue::String(text) => string(&text),
+            JsonValue::Array(_) | JsonValue::Object(_) => unreachable!("scalars aren't nested"),
+        },
+    };
+    Ok(text)
+}
+
+/// Returns the timestamp as a full date-time like `2020-05-06T07:08:09+00:00`, since databases
+/// don't accept Ion's reduced precisions like `2020T`. As `epoch_seconds` does, this gives the
+/// fields beyond the timestamp's precision their lowest value and puts a date in UTC.
+fn date_time(timestamp: &Timestamp) -> String {
+    // Every precision's text starts with the fields up to its own, like `2020-05-06T07:08`.
+    let text = timestamp.to_string();
+    match timestamp.precision() {
+        Precision::Second => text,
+        Precision::HourAndMinute => format!("{}:00{}", &text[..16], &text[16..]),
+        Precision::Day => format!("{}T00:00:00+00:00", &text[..10]),
+        Precision::Month => format!("{}-01T00:00:00+00:00", &text[..7]),
+        Precision::Year => format!("{}-01-01T00:00:00+00:00", &text[..4]),
+    }
+}
+
+fn string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
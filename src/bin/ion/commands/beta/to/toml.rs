@@ -0,0 +1,132 @@
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{bail, Result};
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::{Integer, IonResult, IonType};
+use std::io::Write;
+use std::str::FromStr;
+use toml::value::{Datetime, Table};
+use toml::Value as TomlValue;
+
+/// Writes a stream holding a single struct as a TOML document. Values that TOML can't represent
+/// are rejected with an error naming their path rather than being converted lossily.
+pub(crate) fn write_toml<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+) -> Result<()> {
+    let mut table = None;
+    for (index, element) in elements.enumerate() {
+        let element = element?;
+        let path = index_path("", index);
+        if table.is_some() {
+            bail!(
+                "Cannot convert {} to TOML: a TOML document holds a single top-level table",
+                path
+            );
+        }
+        match to_toml_value(&element, &path)? {
+            TomlValue::Table(converted) => table = Some(converted),
+            _ => bail!(
+                "Cannot convert {} to TOML: only a struct can be a TOML document; found {}",
+                path,
+                element_to_string(&element)?
+            ),
+        }
+    }
+    let table = table.unwrap_or_default();
+    write!(output, "{}", ::toml::to_string(&table)?)?;
+    Ok(())
+}
+
+/// Converts an Ion value to TOML. Decimals become floats and symbols become strings. Nulls,
+/// annotations, lobs, ints too large for 64 bits, timestamps with less than day precision,
+/// arrays whose values have different TOML types, and repeated field names are rejected.
+fn to_toml_value(element: &Element, path: &str) -> Result<TomlValue> {
+    let unsupported = |reason: &str| -> Result<TomlValue> {
+        bail!("Cannot convert {} to TOML: {}", path, reason)
+    };
+    if element.annotations().next().is_some() {
+        return unsupported("TOML does not support annotations");
+    }
+    if element.is_null() {
+        return unsupported("TOML does not support nulls");
+    }
+    let value = match element.ion_type() {
+        IonType::Null => return unsupported("TOML does not support nulls"),
+        IonType::Boolean => TomlValue::Boolean(element.as_bool().unwrap()),
+        IonType::Integer => match element.as_integer().unwrap() {
+            Integer::I64(value) => TomlValue::Integer(*value),
+            Integer::BigInt(_) => return unsupported("TOML integers must fit in 64 bits"),
+        },
+        IonType::Float => TomlValue::Float(element.as_f64().unwrap()),
+        IonType::Decimal => {
+            let text = element.as_decimal().unwrap().to_string().replace('d', "e");
+            TomlValue::Float(text.parse()?)
+        }
+        IonType::Timestamp => {
+            // Timestamp's `Display` implementation writes Ion text, like `2020-05-06T07:08-07:30`.
+            let text = element.as_timestamp().unwrap().to_string();
+            let (date, time) = text.split_once('T').unwrap();
+            if date.len() < "YYYY-MM-DD".len() {
+                return unsupported("TOML dates must include a day");
+            }
+            let text = if time.is_empty() {
+                date.to_string()
+            } else {
+                let offset_start = time.rfind(['+', '-', 'Z']).unwrap();
+                let (clock, offset) = time.split_at(offset_start);
+                // TOML times must include seconds.
+                let clock = match clock.len() {
+                    5 => format!("{}:00", clock),
+                    _ => clock.to_string(),
+                };
+                // An unknown offset is written as a local date-time.
+                let offset = if offset == "-00:00" { "" } else { offset };
+                format!("{}T{}{}", date, clock, offset)
+            };
+            TomlValue::Datetime(Datetime::from_str(&text)?)
+        }
+        IonType::Symbol | IonType::String => match element.as_str() {
+            Some(text) => TomlValue::String(text.to_string()),
+            None => return unsupported("the symbol has unknown text"),
+        },
+        IonType::Clob | IonType::Blob => return unsupported("TOML does not support binary data"),
+        IonType::List | IonType::SExpression => {
+            let mut values = Vec::new();
+            for (index, value) in element.as_sequence().unwrap().iter().enumerate() {
+                let value = to_toml_value(value, &index_path(path, index))?;
+                if let Some(first) = values.first() {
+                    if !same_toml_type(first, &value) {
+                        return unsupported(&format!(
+                            "TOML arrays cannot mix {}s and {}s",
+                            first.type_str(),
+                            value.type_str()
+                        ));
+                    }
+                }
+                values.push(value);
+            }
+            TomlValue::Array(values)
+        }
+        IonType::Struct => {
+            let mut table = Table::new();
+            for (name, value) in element.as_struct().unwrap().fields() {
+                let name = match name.text() {
+                    Some(name) => name,
+                    None => return unsupported("a field name has unknown text"),
+                };
+                if table.contains_key(name) {
+                    return unsupported(&format!("TOML tables cannot repeat the key '{}'", name));
+                }
+                let value = to_toml_value(value, &field_path(path, name))?;
+                table.insert(name.to_string(), value);
+            }
+            TomlValue::Table(table)
+        }
+    };
+    Ok(value)
+}
+
+fn same_toml_type(left: &TomlValue, right: &TomlValue) -> bool {
+    std::mem::discriminant(left) == std::mem::discriminant(right)
+}
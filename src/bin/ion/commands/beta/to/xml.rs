@@ -0,0 +1,281 @@
+use crate::commands::beta::to::decimal_text;
+use crate::commands::beta::to::json::base64;
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{bail, Result};
+use clap::{Arg, ArgMatches};
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence, IonStruct};
+use ion_rs::{IonResult, IonType};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::Write;
+
+const HEADING: &str = "XML options";
+
+// Options shared by the `to` and `from` commands.
+pub(crate) fn args() -> Vec<Arg> {
+    vec![
+        Arg::new("mapping")
+            .long("mapping")
+            .default_value("struct")
+            .value_parser(["struct", "sexp"])
+            .help_heading(HEADING)
+            .help(
+                "Map each XML element to a struct whose fields are its attributes and children, \
+                 or to an s-expression (name {attributes} child...)",
+            ),
+        Arg::new("attribute-prefix")
+            .long("attribute-prefix")
+            .default_value("@")
+            .help_heading(HEADING)
+            .help("With --mapping struct, the prefix of the field names that hold attributes"),
+        Arg::new("text-key")
+            .long("text-key")
+            .default_value("#text")
+            .help_heading(HEADING)
+            .help(
+                "With --mapping struct, the field name that holds the text of an element that \
+                 also has attributes or children",
+            ),
+    ]
+}
+
+/// How XML elements are represented in Ion.
+pub(crate) struct XmlMapping {
+    /// Whether elements are s-expressions rather than structs.
+    pub(crate) sexp: bool,
+    pub(crate) attribute_prefix: String,
+    pub(crate) text_key: String,
+}
+
+impl XmlMapping {
+    pub(crate) fn from_matches(matches: &ArgMatches) -> Self {
+        XmlMapping {
+            sexp: matches.get_one::<String>("mapping").unwrap() == "sexp",
+            attribute_prefix: matches
+                .get_one::<String>("attribute-prefix")
+                .unwrap()
+                .clone(),
+            text_key: matches.get_one::<String>("text-key").unwrap().clone(),
+        }
+    }
+}
+
+/// Writes each value as an XML document, one per line. With `--mapping struct` each value must be
+/// a struct with a single field, which becomes the root element; with `--mapping sexp` each value
+/// must be an s-expression.
+pub(crate) fn write_xml<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let mapping = XmlMapping::from_matches(matches);
+    for (index, element) in elements.enumerate() {
+        let element = element?;
+        let path = index_path("", index);
+        // Each document is written to a buffer first so that nothing is written if it fails.
+        let mut writer = Writer::new(Vec::new());
+        XmlWriter {
+            mapping: &mapping,
+            writer: &mut writer,
+        }
+        .write_document(&element, &path)?;
+        output.write_all(&writer.into_inner())?;
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+struct XmlWriter<'a, W: Write> {
+    mapping: &'a XmlMapping,
+    writer: &'a mut Writer<W>,
+}
+
+impl<'a, W: Write> XmlWriter<'a, W> {
+    fn write_document(&mut self, element: &Element, path: &str) -> Result<()> {
+        check_unannotated(element, path)?;
+        if self.mapping.sexp {
+            return self.write_sexp(element, path);
+        }
+        match element.as_struct() {
+            Some(root) if root.iter().count() == 1 => {
+                let (name, value) = root.iter().next().unwrap();
+                let name = name.text().unwrap_or_default();
+                self.write_field(name, value, &field_path(path, name))
+            }
+            _ => bail!(
+                "Cannot convert {} to XML: with --mapping struct, each value must be a struct \
+                 with a single field for the root element",
+                path
+            ),
+        }
+    }
+
+    /// Writes a struct field as an element named after the field. A list is written as one
+    /// element per value.
+    fn write_field(&mut self, name: &str, value: &Element, path: &str) -> Result<()> {
+        check_unannotated(value, path)?;
+        check_name(name, path)?;
+        if value.is_null() {
+            self.writer
+                .write_event(Event::Empty(BytesStart::new(name)))?;
+            return Ok(());
+        }
+        match value.ion_type() {
+            IonType::List => {
+                for (index, child) in value.as_sequence().unwrap().iter().enumerate() {
+                    let child_path = index_path(path, index);
+                    if child.ion_type() == IonType::List && !child.is_null() {
+                        bail!(
+                            "Cannot convert {} to XML: a list can't be nested directly in a list",
+                            child_path
+                        );
+                    }
+                    self.write_field(name, child, &child_path)?;
+                }
+            }
+            IonType::Struct => {
+                let prefix = self.mapping.attribute_prefix.as_str();
+                let mut start = BytesStart::new(name);
+                let mut children = Vec::new();
+                for (field_name, field_value) in value.as_struct().unwrap().iter() {
+                    let field_name = field_name.text().unwrap_or_default();
+                    let child_path = field_path(path, field_name);
+                    match field_name.strip_prefix(prefix) {
+                        Some(attribute) if !prefix.is_empty() => {
+                            check_name(attribute, &child_path)?;
+                            let text = scalar_text(field_value, &child_path)?;
+                            start.push_attribute((attribute, text.as_str()));
+                        }
+                        _ => children.push((field_name, field_value, child_path)),
+                    }
+                }
+                self.writer.write_event(Event::Start(start))?;
+                for (field_name, field_value, child_path) in children {
+                    if field_name == self.mapping.text_key {
+                        let text = scalar_text(field_value, &child_path)?;
+                        self.writer
+                            .write_event(Event::Text(BytesText::new(&text)))?;
+                    } else {
+                        self.write_field(field_name, field_value, &child_path)?;
+                    }
+                }
+                self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+            _ => {
+                let text = scalar_text(value, path)?;
+                self.writer
+                    .write_event(Event::Start(BytesStart::new(name)))?;
+                self.writer
+                    .write_event(Event::Text(BytesText::new(&text)))?;
+                self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an s-expression `(name {attributes} child...)` as an element. The attributes struct
+    /// is optional; each child is either another s-expression or a scalar written as text.
+    fn write_sexp(&mut self, element: &Element, path: &str) -> Result<()> {
+        let values: Vec<&Element> = match element.ion_type() {
+            IonType::SExpression if !element.is_null() => {
+                element.as_sequence().unwrap().iter().collect()
+            }
+            _ => bail!(
+                "Cannot convert {} to XML: with --mapping sexp, each element must be an \
+                 s-expression (name {{attributes}} child...)",
+                path
+            ),
+        };
+        let name = match values.first().and_then(|name| name.as_str()) {
+            Some(name) => name,
+            None => bail!(
+                "Cannot convert {} to XML: an element's s-expression must start with its name",
+                path
+            ),
+        };
+        check_name(name, path)?;
+        let mut start = BytesStart::new(name);
+        let mut children = &values[1..];
+        if let Some(attributes) = children.first().and_then(|value| value.as_struct()) {
+            for (attribute, value) in attributes.iter() {
+                let attribute = attribute.text().unwrap_or_default();
+                let attribute_path = field_path(&index_path(path, 1), attribute);
+                check_name(attribute, &attribute_path)?;
+                let text = scalar_text(value, &attribute_path)?;
+                start.push_attribute((attribute, text.as_str()));
+            }
+            children = &children[1..];
+        }
+        if children.is_empty() {
+            self.writer.write_event(Event::Empty(start))?;
+            return Ok(());
+        }
+        self.writer.write_event(Event::Start(start))?;
+        let first_child = values.len() - children.len();
+        for (index, child) in children.iter().enumerate() {
+            let child_path = index_path(path, first_child + index);
+            check_unannotated(child, &child_path)?;
+            if child.ion_type() == IonType::SExpression {
+                self.write_sexp(child, &child_path)?;
+            } else {
+                let text = scalar_text(child, &child_path)?;
+                self.writer
+                    .write_event(Event::Text(BytesText::new(&text)))?;
+            }
+        }
+        self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+}
+
+fn check_unannotated(element: &Element, path: &str) -> Result<()> {
+    if element.annotations().next().is_some() {
+        bail!("Cannot convert {} to XML: XML has no annotations", path);
+    }
+    Ok(())
+}
+
+/// Fails unless `name` is a valid XML name, allowing for a namespace prefix.
+fn check_name(name: &str, path: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'));
+    if !valid {
+        bail!(
+            "Cannot convert {} to XML: '{}' is not a valid XML name",
+            path,
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Returns the text of a scalar for an attribute value or text content. Strings and symbols are
+/// written as-is, decimals in plain notation, lobs as base64, and other scalars as Ion text.
+fn scalar_text(element: &Element, path: &str) -> Result<String> {
+    check_unannotated(element, path)?;
+    if element.is_null() {
+        bail!("Cannot convert {} to XML: text can't be null", path);
+    }
+    let text = match element.ion_type() {
+        IonType::String | IonType::Symbol => match element.as_str() {
+            Some(text) => text.to_string(),
+            None => bail!(
+                "Cannot convert {} to XML: the symbol has unknown text",
+                path
+            ),
+        },
+        IonType::Decimal => decimal_text(element.as_decimal().unwrap()),
+        IonType::Blob | IonType::Clob => base64(element.as_bytes().unwrap()),
+        IonType::List | IonType::SExpression | IonType::Struct => bail!(
+            "Cannot convert {} to XML: expected text, but found a {}",
+            path,
+            element.ion_type()
+        ),
+        _ => element_to_string(element)?,
+    };
+    Ok(text)
+}
@@ -0,0 +1,231 @@
+use crate::commands::beta::from::yaml::YamlConverter;
+use crate::commands::beta::to::decimal_text;
+use anyhow::{bail, Result};
+use ion_rs::value::owned::Element;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::{Integer, IonResult, IonType};
+use std::io::Write;
+
+/// Writes each top-level value as a separate YAML document.
+pub(crate) fn write_yaml<I: Iterator<Item = IonResult<Element>>>(
+    elements: I,
+    output: &mut Box<dyn Write>,
+) -> Result<()> {
+    let writer = YamlWriter {
+        converter: YamlConverter::new(),
+    };
+    for (index, element) in elements.enumerate() {
+        let mut lines = match (writer.to_node(&element?)?, index) {
+            (Node::Inline(text), 0) => vec![text],
+            (Node::Inline(text), _) => vec![format!("--- {}", text)],
+            (Node::Block { tag, lines, .. }, index) => {
+                let start = match (tag, index) {
+                    (None, 0) => None,
+                    (Some(tag), 0) => Some(tag),
+                    (None, _) => Some("---".to_string()),
+                    (Some(tag), _) => Some(format!("--- {}", tag)),
+                };
+                start.into_iter().chain(lines).collect()
+            }
+        };
+        lines.push(String::new());
+        output.write_all(lines.join("\n").as_bytes())?;
+    }
+    Ok(())
+}
+
+/// A value written as YAML, before it's placed in its parent.
+enum Node {
+    /// A scalar or an empty collection, which goes on the same line as its key or `-`.
+    Inline(String),
+    /// The lines of a sequence or mapping, and its tag. Sequences aren't indented under a key.
+    Block {
+        tag: Option<String>,
+        lines: Vec<String>,
+        sequence: bool,
+    },
+}
+
+struct YamlWriter {
+    converter: YamlConverter,
+}
+
+impl YamlWriter {
+    /// Converts an Ion value to YAML. Annotations are written as a local tag like `!a::b`.
+    /// Timestamps are written as YAML timestamps and decimals as `!!decimal` scalars. Values that
+    /// YAML has no equivalent for (ints too large for 64 bits, clobs, and blobs) become strings.
+    fn to_node(&self, element: &Element) -> Result<Node> {
+        let tag = tag(element)?;
+        let text = if element.is_null() {
+            "null".to_string()
+        } else {
+            match element.ion_type() {
+                IonType::Null => "null".to_string(),
+                IonType::Boolean => element.as_bool().unwrap().to_string(),
+                IonType::Integer => match element.as_integer().unwrap() {
+                    Integer::I64(value) => value.to_string(),
+                    big_int => self.string(&big_int.to_string()),
+                },
+                IonType::Float => float_text(element.as_f64().unwrap()),
+                // YAML has no decimal type, so decimals are tagged `!!decimal` to keep all of their
+                // digits. Annotated decimals have their annotations as their tag instead.
+                IonType::Decimal => {
+                    let text = decimal_text(element.as_decimal().unwrap());
+                    let tag = tag.as_deref().unwrap_or("!!decimal");
+                    return Ok(Node::Inline(format!("{} {}", tag, text)));
+                }
+                // Ion's timestamp text is read back by `beta from yaml` at every precision.
+                IonType::Timestamp => element.as_timestamp().unwrap().to_string(),
+                IonType::Symbol | IonType::String => {
+                    self.string(element.as_str().unwrap_or_default())
+                }
+                // Lobs are written as the base64 or string text between their braces.
+                IonType::Clob => {
+                    self.string(&element.to_string().replace("{{\"", "").replace("\"}}", ""))
+                }
+                IonType::Blob => {
+                    self.string(&element.to_string().replace("{{", "").replace("}}", ""))
+                }
+                IonType::List | IonType::SExpression => {
+                    let mut lines = Vec::new();
+                    for value in element.as_sequence().unwrap().iter() {
+                        nest(&mut lines, "-", self.to_node(value)?);
+                    }
+                    return Ok(block(tag, lines, "[]", true));
+                }
+                IonType::Struct => {
+                    let mut lines = Vec::new();
+                    for (name, value) in element.as_struct().unwrap().fields() {
+                        let key = format!("{}:", self.string(name.text().unwrap_or("$0")));
+                        nest(&mut lines, &key, self.to_node(value)?);
+                    }
+                    return Ok(block(tag, lines, "{}", false));
+                }
+            }
+        };
+        Ok(Node::Inline(match tag {
+            Some(tag) => format!("{} {}", tag, text),
+            None => text,
+        }))
+    }
+
+    /// Writes `text` as a plain scalar if `beta from yaml` would read it back as the same string,
+    /// or quotes it if it would be read as another type (like `true`, `7`, or `2022-01-01`) or
+    /// isn't valid as a plain scalar.
+    fn string(&self, text: &str) -> String {
+        if text
+            .chars()
+            .any(|c| c.is_control() || "\u{2028}\u{2029}\u{feff}".contains(c))
+        {
+            return double_quoted(text);
+        }
+        let is_plain = !text.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`. ".contains(c))
+            && !text.ends_with([' ', ':'])
+            && !text.contains(": ")
+            && !text.contains(" #")
+            && matches!(
+                self.converter.resolve(text),
+                Ok(element) if element.ion_type() == IonType::String
+            );
+        if is_plain {
+            text.to_string()
+        } else {
+            format!("'{}'", text.replace('\'', "''"))
+        }
+    }
+}
+
+/// Returns an empty collection as `empty` on one line, or the lines of any other as a block.
+fn block(tag: Option<String>, lines: Vec<String>, empty: &str, sequence: bool) -> Node {
+    if !lines.is_empty() {
+        return Node::Block {
+            tag,
+            lines,
+            sequence,
+        };
+    }
+    Node::Inline(match tag {
+        Some(tag) => format!("{} {}", tag, empty),
+        None => empty.to_string(),
+    })
+}
+
+/// Adds a value to its parent's lines after `prefix`, which is a key like `name:` or `-`. A
+/// nested collection starts on the line after a key, but on the same line as a `-`.
+fn nest(lines: &mut Vec<String>, prefix: &str, node: Node) {
+    match node {
+        Node::Inline(text) => lines.push(format!("{} {}", prefix, text)),
+        Node::Block {
+            tag,
+            lines: nested,
+            sequence,
+        } => {
+            // As libyaml does, sequences aren't indented under a key.
+            let indent = if sequence && prefix != "-" { "" } else { "  " };
+            let mut nested = nested.into_iter();
+            match tag {
+                Some(tag) => lines.push(format!("{} {}", prefix, tag)),
+                None if prefix == "-" => lines.push(format!("- {}", nested.next().unwrap())),
+                None => lines.push(prefix.to_string()),
+            }
+            lines.extend(nested.map(|line| format!("{}{}", indent, line)));
+        }
+    }
+}
+
+/// Returns the value's annotations as a tag like `!a::b`, if it has any.
+fn tag(element: &Element) -> Result<Option<String>> {
+    let annotations: Vec<&str> = element
+        .annotations()
+        .map(|annotation| annotation.text().unwrap_or("$0"))
+        .collect();
+    if annotations.is_empty() {
+        return Ok(None);
+    }
+    if let Some(annotation) = annotations.iter().find(|a| !is_tag_text(a)) {
+        bail!(
+            "The annotation '{}' cannot be written as a YAML tag; tags may only contain \
+             letters, digits, '-', '_', '.', and '$'",
+            annotation
+        );
+    }
+    Ok(Some(format!("!{}", annotations.join("::"))))
+}
+
+fn is_tag_text(annotation: &str) -> bool {
+    !annotation.is_empty()
+        && annotation
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.$".contains(c))
+}
+
+fn float_text(value: f64) -> String {
+    if value.is_nan() {
+        ".nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { ".inf" } else { "-.inf" }.to_string()
+    } else {
+        // Unlike `Display`, `Debug` always writes a decimal point or an exponent, so the value
+        // is read back as a float.
+        format!("{:?}", value)
+    }
+}
+
+fn double_quoted(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() || "\u{2028}\u{2029}\u{feff}".contains(c) => {
+                quoted.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
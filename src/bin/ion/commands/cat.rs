@@ -0,0 +1,135 @@
+use crate::commands::dump::{open_output, transcribe_n_values};
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::*;
+use std::fs::File;
+use std::io::stdin;
+
+// The input file name that refers to STDIN
+const STDIN_FILE_NAME: &str = "-";
+
+pub fn app() -> Command {
+    Command::new("cat")
+        .about("Concatenates Ion streams into a single stream")
+        .long_about(
+            "Concatenates any mix of text and binary Ion streams into a single stream
+in the requested format. Each input's symbols are resolved using its own
+symbol tables and then re-encoded in the output's symbol context, so the
+output is valid even if the inputs' local symbol tables conflict.",
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("pretty")
+                .value_parser(["binary", "text", "pretty", "lines"])
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            Arg::new("separator")
+                .long("separator")
+                .short('s')
+                .help("Ion text to write between the values of consecutive input files"),
+        )
+        .arg(
+            Arg::new("annotate-file-names")
+                .long("annotate-file-names")
+                .short('a')
+                .action(ArgAction::SetTrue)
+                .help("Annotate each top-level value with the name of the file it came from"),
+        )
+        .arg(
+            // All argv entries after the program name (argv[0])
+            // and any `clap`-managed options are considered input files.
+            Arg::new("input")
+                .index(1)
+                .help("Input file; `-` reads STDIN [default: STDIN]")
+                .action(ArgAction::Append)
+                .trailing_var_arg(true),
+        )
+}
+
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    // --format pretty|text|lines|binary
+    // `clap` validates the specified format and provides a default otherwise.
+    let format = matches.get_one::<String>("format").unwrap();
+
+    let separator = matches.get_one::<String>("separator").map(String::as_str);
+    if let Some(separator) = separator {
+        // Make sure the separator is valid before any output is written.
+        element_reader()
+            .read_all(separator.as_bytes())
+            .with_context(|| format!("Invalid value for '--separator': '{}'", separator))?;
+    }
+
+    let annotate_file_names = matches.get_flag("annotate-file-names");
+
+    let input_files: Vec<&str> = match matches.get_many::<String>("input") {
+        Some(input_file_iter) => input_file_iter.map(String::as_str).collect(),
+        None => vec![STDIN_FILE_NAME],
+    };
+
+    let output = open_output(matches)?;
+
+    // A single writer is used for every input file so that all of the values share one
+    // output stream (and, for binary Ion, one symbol context).
+    match format.as_str() {
+        "pretty" => {
+            let mut writer = TextWriterBuilder::pretty().build(output)?;
+            cat(&mut writer, &input_files, separator, annotate_file_names)
+        }
+        "text" => {
+            let mut writer = TextWriterBuilder::default().build(output)?;
+            cat(&mut writer, &input_files, separator, annotate_file_names)
+        }
+        "lines" => {
+            let mut writer = TextWriterBuilder::lines().build(output)?;
+            cat(&mut writer, &input_files, separator, annotate_file_names)
+        }
+        "binary" => {
+            let mut writer = BinaryWriterBuilder::new().build(output)?;
+            cat(&mut writer, &input_files, separator, annotate_file_names)
+        }
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'pretty', 'text', 'lines', or 'binary'",
+            unrecognized
+        ),
+    }
+}
+
+/// Writes the values from each of the input files to `writer`, followed by the `separator`
+/// values (if any) when another file follows.
+fn cat<W: IonWriter>(
+    writer: &mut W,
+    input_files: &[&str],
+    separator: Option<&str>,
+    annotate_file_names: bool,
+) -> Result<()> {
+    for (index, &input_file) in input_files.iter().enumerate() {
+        if index > 0 {
+            if let Some(separator) = separator {
+                let mut reader = ReaderBuilder::new().build(separator.as_bytes())?;
+                transcribe_n_values(&mut reader, writer, None, None)?;
+            }
+        }
+        let file_name = Some(input_file).filter(|_| annotate_file_names);
+        let mut reader = if input_file == STDIN_FILE_NAME {
+            ReaderBuilder::new().build(stdin().lock())?
+        } else {
+            let file = File::open(input_file)
+                .with_context(|| format!("Could not open file '{}'", input_file))?;
+            ReaderBuilder::new().build(file)?
+        };
+        transcribe_n_values(&mut reader, writer, None, file_name)
+            .with_context(|| format!("Could not read '{}'", input_file))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,462 @@
+use crate::commands::dump::open_output;
+use crate::commands::util::{element_to_string, field_path, index_path};
+use anyhow::{Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::ion_eq::IonEq;
+use ion_rs::value::native_writer::NativeElementWriter;
+use ion_rs::value::owned::{Element, Struct};
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::writer::ElementWriter;
+use ion_rs::value::{IonElement, IonSequence};
+use ion_rs::*;
+use std::fs;
+use std::io::Write;
+
+// Sequences whose differing middle sections would need more cells than this in the table used to
+// align them are compared position by position instead.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 20;
+
+pub fn app() -> Command {
+    Command::new("diff")
+        .about("Compares two Ion streams and reports the values that differ")
+        .long_about(
+            "Compares two Ion streams using the Ion data model, so differences in
+encoding (text or binary), whitespace, and symbol IDs are ignored. Each
+added, removed, or changed value is reported along with its path. Top-level
+values and the children of lists and s-expressions are aligned so that an
+inserted or deleted value is reported on its own rather than as a change to
+every value that follows it.",
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("summary")
+                .value_parser(["summary", "binary", "text", "pretty", "lines"])
+                .help("Output format; `summary` prints one line per difference"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            Arg::new("ignore-annotations")
+                .long("ignore-annotations")
+                .short('a')
+                .action(ArgAction::SetTrue)
+                .help("Ignore differences in annotations"),
+        )
+        .arg(
+            Arg::new("unordered-fields")
+                .long("unordered-fields")
+                .short('u')
+                .action(ArgAction::SetTrue)
+                .help("Match struct fields by name regardless of the order in which they appear"),
+        )
+        .arg(
+            Arg::new("left")
+                .index(1)
+                .required(true)
+                .help("The original Ion file"),
+        )
+        .arg(
+            Arg::new("right")
+                .index(2)
+                .required(true)
+                .help("The Ion file to compare it to"),
+        )
+}
+
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    // Both inputs are required, so we can unwrap these safely.
+    let left = read_elements(matches.get_one::<String>("left").unwrap())?;
+    let right = read_elements(matches.get_one::<String>("right").unwrap())?;
+    let options = DiffOptions {
+        ignore_annotations: matches.get_flag("ignore-annotations"),
+        unordered_fields: matches.get_flag("unordered-fields"),
+    };
+
+    let mut differ = Differ::new(&options);
+    differ.diff_sequences("", left.iter().collect(), right.iter().collect());
+
+    let mut output = open_output(matches)?;
+    // `clap` validates the specified format and provides a default otherwise.
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "summary" => {
+            for difference in &differ.differences {
+                writeln!(output, "{}", difference)?;
+            }
+            output.flush()?;
+        }
+        "pretty" => write_differences(
+            TextWriterBuilder::pretty().build(output)?,
+            &differ.differences,
+        )?,
+        "text" => write_differences(
+            TextWriterBuilder::default().build(output)?,
+            &differ.differences,
+        )?,
+        "lines" => write_differences(
+            TextWriterBuilder::lines().build(output)?,
+            &differ.differences,
+        )?,
+        "binary" => write_differences(
+            BinaryWriterBuilder::new().build(output)?,
+            &differ.differences,
+        )?,
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'summary', 'pretty', 'text', 'lines', or 'binary'",
+            unrecognized
+        ),
+    }
+    Ok(())
+}
+
+fn read_elements(file_name: &str) -> Result<Vec<Element>> {
+    let bytes =
+        fs::read(file_name).with_context(|| format!("Could not open file '{}'", file_name))?;
+    element_reader()
+        .read_all(&bytes)
+        .with_context(|| format!("Could not read '{}'", file_name))
+}
+
+/// Writes each difference as a struct like `{kind: changed, path: "[0].a", left: 1, right: 2}`.
+fn write_differences<W: IonWriter>(writer: W, differences: &[Difference]) -> Result<()> {
+    let mut element_writer = NativeElementWriter::new(writer);
+    for difference in differences {
+        element_writer.write(&difference.to_element())?;
+    }
+    element_writer.finish()?.flush()?;
+    Ok(())
+}
+
+struct DiffOptions {
+    ignore_annotations: bool,
+    unordered_fields: bool,
+}
+
+/// A value that was added, removed, or changed. Removed and changed values are located by their
+/// path in the left stream; added values by their path in the right stream.
+struct Difference<'a> {
+    path: String,
+    left: Option<&'a Element>,
+    right: Option<&'a Element>,
+}
+
+impl<'a> Difference<'a> {
+    fn kind(&self) -> &'static str {
+        match (self.left, self.right) {
+            (Some(_), Some(_)) => "changed",
+            (Some(_), None) => "removed",
+            _ => "added",
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut fields = vec![
+            ("kind", Element::from(Symbol::owned(self.kind()))),
+            ("path", Element::from(self.path.clone())),
+        ];
+        if let Some(left) = self.left {
+            fields.push(("left", left.clone()));
+        }
+        if let Some(right) = self.right {
+            fields.push(("right", right.clone()));
+        }
+        fields.into_iter().collect::<Struct>().into()
+    }
+}
+
+impl<'a> std::fmt::Display for Difference<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = |element: &Element| element_to_string(element).map_err(|_| std::fmt::Error);
+        match (self.left, self.right) {
+            (Some(left), Some(right)) => {
+                write!(f, "~ {}: {} => {}", self.path, text(left)?, text(right)?)
+            }
+            (Some(left), None) => write!(f, "- {}: {}", self.path, text(left)?),
+            (None, Some(right)) => write!(f, "+ {}: {}", self.path, text(right)?),
+            (None, None) => unreachable!("a difference must have at least one value"),
+        }
+    }
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    differences: Vec<Difference<'a>>,
+}
+
+impl<'a> Differ<'a> {
+    fn new(options: &'a DiffOptions) -> Self {
+        Differ {
+            options,
+            differences: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, path: String, left: Option<&'a Element>, right: Option<&'a Element>) {
+        self.differences.push(Difference { path, left, right });
+    }
+
+    /// Records the differences between two values found at `path`. Containers of the same type
+    /// and annotations are compared child by child; anything else is reported as a change.
+    fn diff_values(&mut self, path: String, left: &'a Element, right: &'a Element) {
+        if self.equivalent(left, right) {
+            return;
+        }
+        if left.ion_type() != right.ion_type()
+            || left.is_null()
+            || right.is_null()
+            || !self.annotations_equivalent(left, right)
+        {
+            return self.record(path, Some(left), Some(right));
+        }
+        match left.ion_type() {
+            IonType::List | IonType::SExpression => self.diff_sequences(
+                &path,
+                left.as_sequence().unwrap().iter().collect(),
+                right.as_sequence().unwrap().iter().collect(),
+            ),
+            IonType::Struct => self.diff_structs(&path, fields(left), fields(right)),
+            _ => self.record(path, Some(left), Some(right)),
+        }
+    }
+
+    /// Aligns the two sequences on a longest common subsequence of equivalent values and records
+    /// the differences in each run of values between those anchors.
+    fn diff_sequences(&mut self, path: &str, left: Vec<&'a Element>, right: Vec<&'a Element>) {
+        let mut anchors = self.align(&left, &right, |l, r| self.equivalent(l, r));
+        // Treat the end of both sequences as a final anchor so the trailing run is compared too.
+        anchors.push((left.len(), right.len()));
+        let (mut l, mut r) = (0, 0);
+        for (next_l, next_r) in anchors {
+            self.diff_runs(path, (l, &left[l..next_l]), (r, &right[r..next_r]));
+            (l, r) = (next_l + 1, next_r + 1);
+        }
+    }
+
+    /// Records the differences between two runs of values that have no equivalent values in
+    /// common. Values of the same type are paired up and compared as changes; the rest were
+    /// removed or added. Each run is given with the index of its first value.
+    fn diff_runs(
+        &mut self,
+        path: &str,
+        (left_start, left): (usize, &[&'a Element]),
+        (right_start, right): (usize, &[&'a Element]),
+    ) {
+        let mut pairs = self.align(left, right, |l, r| l.ion_type() == r.ion_type());
+        pairs.push((left.len(), right.len()));
+        let (mut l, mut r) = (0, 0);
+        for (next_l, next_r) in pairs {
+            for (index, left_value) in left.iter().enumerate().take(next_l).skip(l) {
+                self.record(index_path(path, left_start + index), Some(left_value), None);
+            }
+            for (index, right_value) in right.iter().enumerate().take(next_r).skip(r) {
+                self.record(
+                    index_path(path, right_start + index),
+                    None,
+                    Some(right_value),
+                );
+            }
+            if next_l < left.len() {
+                let path = index_path(path, left_start + next_l);
+                self.diff_values(path, left[next_l], right[next_r]);
+            }
+            (l, r) = (next_l + 1, next_r + 1);
+        }
+    }
+
+    /// Returns the index pairs of a longest common subsequence of the two sequences, where two
+    /// values are considered the same if `related` returns true. If the sequences' differing
+    /// middle sections are too long to align, only their common prefix and suffix are returned.
+    fn align<F: Fn(&Element, &Element) -> bool>(
+        &self,
+        left: &[&Element],
+        right: &[&Element],
+        related: F,
+    ) -> Vec<(usize, usize)> {
+        let prefix = left
+            .iter()
+            .zip(right)
+            .take_while(|(l, r)| related(l, r))
+            .count();
+        let suffix = left[prefix..]
+            .iter()
+            .rev()
+            .zip(right[prefix..].iter().rev())
+            .take_while(|(l, r)| related(l, r))
+            .count();
+        let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+
+        let left_middle = &left[prefix..left.len() - suffix];
+        let right_middle = &right[prefix..right.len() - suffix];
+        if left_middle.len().saturating_mul(right_middle.len()) <= MAX_ALIGNMENT_CELLS {
+            // lengths[i][j] is the length of the longest common subsequence of left_middle[i..]
+            // and right_middle[j..]
+            let mut lengths = vec![vec![0usize; right_middle.len() + 1]; left_middle.len() + 1];
+            for i in (0..left_middle.len()).rev() {
+                for j in (0..right_middle.len()).rev() {
+                    lengths[i][j] = if related(left_middle[i], right_middle[j]) {
+                        lengths[i + 1][j + 1] + 1
+                    } else {
+                        lengths[i + 1][j].max(lengths[i][j + 1])
+                    };
+                }
+            }
+            let (mut i, mut j) = (0, 0);
+            while i < left_middle.len() && j < right_middle.len() {
+                if related(left_middle[i], right_middle[j]) {
+                    pairs.push((prefix + i, prefix + j));
+                    i += 1;
+                    j += 1;
+                } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+                    i += 1;
+                } else {
+                    j += 1;
+                }
+            }
+        }
+
+        let left_suffix_start = left.len() - suffix;
+        let right_suffix_start = right.len() - suffix;
+        pairs.extend((0..suffix).map(|i| (left_suffix_start + i, right_suffix_start + i)));
+        pairs
+    }
+
+    fn diff_structs(
+        &mut self,
+        path: &str,
+        left: Vec<(&'a str, &'a Element)>,
+        right: Vec<(&'a str, &'a Element)>,
+    ) {
+        if !self.options.unordered_fields {
+            // Compare the fields position by position.
+            for (&(left_name, left_value), &(right_name, right_value)) in left.iter().zip(&right) {
+                if left_name == right_name {
+                    self.diff_values(field_path(path, left_name), left_value, right_value);
+                } else {
+                    self.record(field_path(path, left_name), Some(left_value), None);
+                    self.record(field_path(path, right_name), None, Some(right_value));
+                }
+            }
+            for &(name, value) in left.iter().skip(right.len()) {
+                self.record(field_path(path, name), Some(value), None);
+            }
+            for &(name, value) in right.iter().skip(left.len()) {
+                self.record(field_path(path, name), None, Some(value));
+            }
+            return;
+        }
+
+        // Visit each field name once, in the order in which it first appears.
+        let mut names: Vec<&str> = Vec::new();
+        for &(name, _) in left.iter().chain(&right) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        for name in names {
+            let left_values = values_named(&left, name);
+            let mut right_values = values_named(&right, name);
+            // Set aside the right values that are equivalent to one of the left values, then
+            // pair up whatever is left over.
+            let mut unmatched_left = Vec::new();
+            for left_value in left_values {
+                match right_values
+                    .iter()
+                    .position(|right_value| self.equivalent(left_value, right_value))
+                {
+                    Some(index) => {
+                        right_values.remove(index);
+                    }
+                    None => unmatched_left.push(left_value),
+                }
+            }
+            let paired = unmatched_left.len().min(right_values.len());
+            for (left_value, right_value) in unmatched_left.iter().zip(&right_values) {
+                self.diff_values(field_path(path, name), left_value, right_value);
+            }
+            for left_value in unmatched_left.iter().skip(paired) {
+                self.record(field_path(path, name), Some(left_value), None);
+            }
+            for right_value in right_values.iter().skip(paired) {
+                self.record(field_path(path, name), None, Some(right_value));
+            }
+        }
+    }
+
+    fn annotations_equivalent(&self, left: &Element, right: &Element) -> bool {
+        self.options.ignore_annotations || left.annotations().eq(right.annotations())
+    }
+
+    /// Tests two values for equivalence in the Ion data model, subject to the diff options.
+    fn equivalent(&self, left: &Element, right: &Element) -> bool {
+        if left.ion_type() != right.ion_type() || !self.annotations_equivalent(left, right) {
+            return false;
+        }
+        if left.is_null() || right.is_null() {
+            return left.is_null() && right.is_null();
+        }
+        match left.ion_type() {
+            IonType::List | IonType::SExpression => {
+                let (left, right) = (left.as_sequence().unwrap(), right.as_sequence().unwrap());
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(l, r)| self.equivalent(l, r))
+            }
+            IonType::Struct => {
+                let (left, mut right) = (fields(left), fields(right));
+                if left.len() != right.len() {
+                    return false;
+                }
+                if !self.options.unordered_fields {
+                    return left
+                        .iter()
+                        .zip(&right)
+                        .all(|((ln, lv), (rn, rv))| ln == rn && self.equivalent(lv, rv));
+                }
+                // Each left field must be matched by a distinct right field.
+                left.iter().all(|(name, value)| {
+                    match right.iter().position(|(right_name, right_value)| {
+                        name == right_name && self.equivalent(value, right_value)
+                    }) {
+                        Some(index) => {
+                            right.remove(index);
+                            true
+                        }
+                        None => false,
+                    }
+                })
+            }
+            // Scalars are compared without their annotations, which were tested above.
+            _ => without_annotations(left).ion_eq(&without_annotations(right)),
+        }
+    }
+}
+
+fn without_annotations(element: &Element) -> Element {
+    element.clone().with_annotations(Vec::new())
+}
+
+/// Returns the struct's fields in order. Fields whose names have unknown text are given the name
+/// `$0`.
+fn fields(element: &Element) -> Vec<(&str, &Element)> {
+    element
+        .as_struct()
+        .unwrap()
+        .fields()
+        .map(|(name, value)| (name.text().unwrap_or("$0"), value))
+        .collect()
+}
+
+fn values_named<'a>(fields: &[(&str, &'a Element)], name: &str) -> Vec<&'a Element> {
+    fields
+        .iter()
+        .filter(|(field_name, _)| *field_name == name)
+        .map(|(_, value)| *value)
+        .collect()
+}
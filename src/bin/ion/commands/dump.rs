@@ -1,22 +1,13 @@
 use anyhow::{Context, Result};
-use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use ion_rs::*;
 use std::fs::File;
 use std::io::{stdin, stdout, StdinLock, Write};
+use std::str::FromStr;
 
 pub fn app() -> Command {
     Command::new("dump")
         .about("Prints Ion in the requested format")
-        //TODO: Remove `values` after https://github.com/amazon-ion/ion-cli/issues/49
-        .arg(
-            Arg::new("values")
-                .long("values")
-                .short('n')
-                .value_parser(value_parser!(usize))
-                .allow_negative_numbers(false)
-                .hide(true)
-                .help("Specifies the number of output top-level values."),
-        )
         .arg(
             Arg::new("format")
                 .long("format")
@@ -31,6 +22,27 @@ pub fn app() -> Command {
                 .short('o')
                 .help("Output file [default: STDOUT]"),
         )
+        .arg(
+            Arg::new("select")
+                .long("select")
+                .short('s')
+                .help("Only print the values at the given path, like `orders[*].items[0].sku`")
+                .long_help(
+                    "Only print the values that the given path selects from each top-level
+value. A path is a sequence of steps:
+
+  .name or .'quoted name'   a struct field
+  .*                        every struct field
+  [n]                       the nth value in a list or s-expression
+  [*]                       every value in a list or s-expression
+
+The `.` before the first field name may be omitted. Any step can also
+require the value it selects to have annotations, written as in text
+Ion: `orders[*].usd::price`. Annotations at the beginning of the path
+that aren't attached to a field name filter the top-level values:
+`order::.items[0]`.",
+                ),
+        )
         .arg(
             // All argv entries after the program name (argv[0])
             // and any `clap`-managed options are considered input files.
@@ -47,24 +59,18 @@ pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
     // `clap` validates the specified format and provides a default otherwise.
     let format = matches.get_one::<String>("format").unwrap();
 
-    // --values <n>
-    // this value is supplied when `dump` is invoked as `head`
-    let values: Option<usize> = matches.get_one::<usize>("values").copied();
-
-    // -o filename
-    let mut output: Box<dyn Write> = if let Some(output_file) = matches.get_one::<String>("output")
-    {
-        let file = File::create(output_file).with_context(|| {
-            format!(
-                "could not open file output file '{}' for writing",
-                output_file
-            )
-        })?;
-        Box::new(file)
-    } else {
-        Box::new(stdout().lock())
+    // --select <path>
+    // `beta from` reuses this function but doesn't accept --select, so it may not be defined.
+    let path = match matches.try_get_one::<String>("select").ok().flatten() {
+        Some(text) => Some(
+            IonPath::from_str(text)
+                .with_context(|| format!("Invalid value for '--select': '{}'", text))?,
+        ),
+        None => None,
     };
 
+    let mut output = open_output(matches)?;
+
     if let Some(input_file_iter) = matches.get_many::<String>("input") {
         //TODO: Hack around newline issue, append newline after `pretty` and `lines`, single newline
         // at the end of *all* `text` value output (if handling multiple files)
@@ -75,42 +81,61 @@ pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
             let file = File::open(input_file)
                 .with_context(|| format!("Could not open file '{}'", input_file))?;
             let mut reader = ReaderBuilder::new().build(file)?;
-            write_in_format(&mut reader, &mut output, format, values)?;
+            write_in_format(&mut reader, &mut output, format, None, path.as_ref())?;
         }
     } else {
         let input: StdinLock = stdin().lock();
         let mut reader = ReaderBuilder::new().build(input)?;
-        write_in_format(&mut reader, &mut output, format, values)?;
+        write_in_format(&mut reader, &mut output, format, None, path.as_ref())?;
     }
 
     output.flush()?;
     Ok(())
 }
 
+/// Opens the file named by the `output` argument for writing, or STDOUT if it was not specified.
+pub(crate) fn open_output(matches: &ArgMatches) -> Result<Box<dyn Write>> {
+    // -o filename
+    let output: Box<dyn Write> = if let Some(output_file) = matches.get_one::<String>("output") {
+        let file = File::create(output_file).with_context(|| {
+            format!(
+                "could not open file output file '{}' for writing",
+                output_file
+            )
+        })?;
+        Box::new(file)
+    } else {
+        Box::new(stdout().lock())
+    };
+    Ok(output)
+}
+
 /// Constructs the appropriate writer for the given format, then writes all values found in the
-/// Reader to the new Writer. If `count` is specified will write at most `count` values.
+/// Reader to the new Writer. If `count` is specified will write at most `count` values. If `path`
+/// is specified, only the values it selects will be written.
 pub(crate) fn write_in_format(
     reader: &mut Reader,
     output: &mut Box<dyn Write>,
     format: &str,
     count: Option<usize>,
+    path: Option<&IonPath>,
 ) -> IonResult<usize> {
     match format {
         "pretty" => {
             let mut writer = TextWriterBuilder::pretty().build(output)?;
-            transcribe_n_values(reader, &mut writer, count)
+            transcribe_selected_values(reader, &mut writer, count, path)
         }
         "text" => {
             let mut writer = TextWriterBuilder::default().build(output)?;
-            transcribe_n_values(reader, &mut writer, count)
+            transcribe_selected_values(reader, &mut writer, count, path)
         }
         "lines" => {
             let mut writer = TextWriterBuilder::lines().build(output)?;
-            transcribe_n_values(reader, &mut writer, count)
+            transcribe_selected_values(reader, &mut writer, count, path)
         }
         "binary" => {
             let mut writer = BinaryWriterBuilder::new().build(output)?;
-            transcribe_n_values(reader, &mut writer, count)
+            transcribe_selected_values(reader, &mut writer, count, path)
         }
         unrecognized => unreachable!(
             "'format' was '{}' instead of 'pretty', 'text', 'lines', or 'binary'",
@@ -119,92 +144,417 @@ pub(crate) fn write_in_format(
     }
 }
 
+const FLUSH_EVERY_N: usize = 100;
+
 /// Writes each value encountered in the Reader to the provided IonWriter. If `count` is specified
-/// will write at most `count` values.
-fn transcribe_n_values<W: IonWriter>(
+/// will write at most `count` values. If `top_level_annotation` is specified, it will be added
+/// to each top-level value ahead of any annotations the value already has.
+pub(crate) fn transcribe_n_values<W: IonWriter>(
     reader: &mut Reader,
     writer: &mut W,
     count: Option<usize>,
+    top_level_annotation: Option<&str>,
 ) -> IonResult<usize> {
-    const FLUSH_EVERY_N: usize = 100;
     let mut values_since_flush: usize = 0;
-    let mut annotations = vec![];
     let mut index = 0;
     loop {
         // Could use Option::is_some_and if that reaches stable
-        if reader.depth() == 0 && matches!(count, Some(n) if n <= index) {
+        if matches!(count, Some(n) if n <= index) {
             break;
         }
 
         match reader.next()? {
             StreamItem::Value(ion_type) | StreamItem::Null(ion_type) => {
-                if reader.has_annotations() {
-                    annotations.clear();
-                    for annotation in reader.annotations() {
-                        annotations.push(annotation?);
-                    }
-                    writer.set_annotations(&annotations);
-                }
+                transcribe_value(reader, writer, ion_type, top_level_annotation)?;
+            }
+            StreamItem::Nothing => break,
+        }
+        index += 1;
+        values_since_flush += 1;
+        if values_since_flush == FLUSH_EVERY_N {
+            writer.flush()?;
+            values_since_flush = 0;
+        }
+    }
+    writer.flush()?;
+    Ok(index)
+}
 
-                if reader.parent_type() == Some(IonType::Struct) {
-                    writer.set_field_name(reader.field_name()?);
-                }
+/// Writes the value on which the Reader is positioned to the provided IonWriter, including its
+/// annotations and any values nested inside of it. If the writer is in a struct, the value's field
+/// name is written too. If `extra_annotation` is specified, it will be written ahead of any
+/// annotations the value already has.
+pub(crate) fn transcribe_value<W: IonWriter>(
+    reader: &mut Reader,
+    writer: &mut W,
+    ion_type: IonType,
+    extra_annotation: Option<&str>,
+) -> IonResult<()> {
+    if reader.has_annotations() || extra_annotation.is_some() {
+        let mut annotations = vec![];
+        if let Some(text) = extra_annotation {
+            annotations.push(ion_rs::Symbol::owned(text));
+        }
+        for annotation in reader.annotations() {
+            annotations.push(annotation?);
+        }
+        writer.set_annotations(&annotations);
+    }
+
+    if writer.parent_type() == Some(IonType::Struct) {
+        writer.set_field_name(reader.field_name()?);
+    }
+
+    if reader.is_null() {
+        return writer.write_null(ion_type);
+    }
+
+    use IonType::*;
+    match ion_type {
+        Null => unreachable!("null values are handled prior to this match"),
+        Boolean => writer.write_bool(reader.read_bool()?)?,
+        Integer => writer.write_integer(&reader.read_integer()?)?,
+        Float => {
+            let float64 = reader.read_f64()?;
+            let float32 = float64 as f32;
+            if float32 as f64 == float64 {
+                // No data lost during cast; write it as an f32
+                writer.write_f32(float32)?;
+            } else {
+                writer.write_f64(float64)?;
+            }
+        }
+        Decimal => writer.write_decimal(&reader.read_decimal()?)?,
+        Timestamp => writer.write_timestamp(&reader.read_timestamp()?)?,
+        Symbol => writer.write_symbol(reader.read_symbol()?)?,
+        String => writer.write_string(reader.read_string()?)?,
+        Clob => writer.write_clob(reader.read_clob()?)?,
+        Blob => writer.write_blob(reader.read_blob()?)?,
+        List | SExpression | Struct => {
+            reader.step_in()?;
+            writer.step_in(ion_type)?;
+            while let StreamItem::Value(child_type) | StreamItem::Null(child_type) =
+                reader.next()?
+            {
+                transcribe_value(reader, writer, child_type, None)?;
+            }
+            reader.step_out()?;
+            writer.step_out()?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes each value that `path` selects from the Reader to the provided IonWriter. Values that
+/// cannot contain a match are skipped without being read. If `count` is specified will write at
+/// most `count` values. If `path` is not specified, every top-level value is written.
+fn transcribe_selected_values<W: IonWriter>(
+    reader: &mut Reader,
+    writer: &mut W,
+    count: Option<usize>,
+    path: Option<&IonPath>,
+) -> IonResult<usize> {
+    let path = match path {
+        Some(path) => path,
+        None => return transcribe_n_values(reader, writer, count, None),
+    };
+    let mut values_since_flush: usize = 0;
+    let mut selected = 0;
+    loop {
+        if matches!(count, Some(n) if n <= selected) {
+            break;
+        }
+
+        match reader.next()? {
+            StreamItem::Value(ion_type) | StreamItem::Null(ion_type) => {
+                path.visit_selected_values(reader, ion_type, &mut |reader, ion_type| {
+                    transcribe_value(reader, writer, ion_type, None)?;
+                    selected += 1;
+                    Ok(!matches!(count, Some(n) if n <= selected))
+                })?;
+            }
+            StreamItem::Nothing => break,
+        }
+        values_since_flush += 1;
+        if values_since_flush == FLUSH_EVERY_N {
+            writer.flush()?;
+            values_since_flush = 0;
+        }
+    }
+    writer.flush()?;
+    Ok(selected)
+}
+
+/// Calls `visit` with the Reader positioned on each value that `steps` select from the value on
+/// which the Reader is currently positioned. If `visit` returns false, no more values are visited
+/// and this function returns false too.
+fn visit_selected_values<F>(
+    reader: &mut Reader,
+    ion_type: IonType,
+    steps: &[PathStep],
+    visit: &mut F,
+) -> IonResult<bool>
+where
+    F: FnMut(&mut Reader, IonType) -> IonResult<bool>,
+{
+    let (step, remaining_steps) = match steps.split_first() {
+        Some(split) => split,
+        // We've matched every step in the path; this is one of the values being selected.
+        None => return visit(reader, ion_type),
+    };
+    if reader.is_null() || !step.applies_to(ion_type) {
+        return Ok(true);
+    }
+
+    reader.step_in()?;
+    let mut keep_going = true;
+    let mut index = 0;
+    while let StreamItem::Value(child_type) | StreamItem::Null(child_type) = reader.next()? {
+        if step.is_past(index) {
+            break;
+        }
+        if step.matches(reader, index)? {
+            keep_going = visit_selected_values(reader, child_type, remaining_steps, visit)?;
+            if !keep_going {
+                break;
+            }
+        }
+        index += 1;
+    }
+    reader.step_out()?;
+    Ok(keep_going)
+}
+
+/// Returns true if the value on which the Reader is positioned has all of the given annotations.
+fn has_annotations(reader: &Reader, annotations: &[String]) -> IonResult<bool> {
+    if annotations.is_empty() {
+        return Ok(true);
+    }
+    let value_annotations: Vec<Symbol> = reader.annotations().collect::<IonResult<_>>()?;
+    Ok(annotations.iter().all(|annotation| {
+        value_annotations
+            .iter()
+            .any(|value_annotation| value_annotation.text() == Some(annotation.as_str()))
+    }))
+}
+
+/// A path expression that selects values nested inside each top-level value, like
+/// `orders[*].items[0].sku`. Each step may also require the value it selects to have certain
+/// annotations, written the way they are in text Ion: `orders[*].usd::price` or
+/// `order::.items[usd::*]`. Annotations at the very beginning of the path that aren't attached to a
+/// field name apply to the top-level values themselves.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct IonPath {
+    root_annotations: Vec<String>,
+    steps: Vec<PathStep>,
+}
+
+impl IonPath {
+    /// Calls `visit` with the Reader positioned on each value that this path selects from the
+    /// top-level value on which the Reader is currently positioned. If `visit` returns false, no
+    /// more values are visited and this method returns false too.
+    pub(crate) fn visit_selected_values<F>(
+        &self,
+        reader: &mut Reader,
+        ion_type: IonType,
+        visit: &mut F,
+    ) -> IonResult<bool>
+    where
+        F: FnMut(&mut Reader, IonType) -> IonResult<bool>,
+    {
+        if !has_annotations(reader, &self.root_annotations)? {
+            return Ok(true);
+        }
+        visit_selected_values(reader, ion_type, &self.steps, visit)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PathStep {
+    kind: PathStepKind,
+    annotations: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum PathStepKind {
+    Field(String),
+    AnyField,
+    Index(usize),
+    AnyIndex,
+}
+
+impl PathStep {
+    /// Returns true if this step can select values nested inside a container of the given type.
+    fn applies_to(&self, ion_type: IonType) -> bool {
+        use PathStepKind::*;
+        match self.kind {
+            Field(_) | AnyField => ion_type == IonType::Struct,
+            Index(_) | AnyIndex => matches!(ion_type, IonType::List | IonType::SExpression),
+        }
+    }
+
+    /// Returns true if no value at or after `index` can match this step.
+    fn is_past(&self, index: usize) -> bool {
+        matches!(self.kind, PathStepKind::Index(i) if index > i)
+    }
 
-                if reader.is_null() {
-                    writer.write_null(ion_type)?;
-                    continue;
+    /// Returns true if the value on which the Reader is positioned, found at `index` in its
+    /// container, matches this step.
+    fn matches(&self, reader: &Reader, index: usize) -> IonResult<bool> {
+        use PathStepKind::*;
+        let position_matches = match &self.kind {
+            Field(name) => reader.field_name()?.text() == Some(name.as_str()),
+            AnyField | AnyIndex => true,
+            Index(i) => *i == index,
+        };
+        Ok(position_matches && has_annotations(reader, &self.annotations)?)
+    }
+}
+
+impl FromStr for IonPath {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut parser = PathParser { text, position: 0 };
+        let mut path = IonPath::default();
+
+        let leading_annotations = parser.parse_annotations()?;
+        match parser.peek() {
+            None | Some('.') | Some('[') => path.root_annotations = leading_annotations,
+            // The leading `.` of the first field name may be omitted.
+            Some(_) => path.steps.push(parser.parse_field(leading_annotations)?),
+        }
+        while let Some(c) = parser.peek() {
+            parser.position += c.len_utf8();
+            let step = match c {
+                '.' => {
+                    let annotations = parser.parse_annotations()?;
+                    parser.parse_field(annotations)?
+                }
+                '[' => {
+                    let annotations = parser.parse_annotations()?;
+                    let step = parser.parse_index(annotations)?;
+                    parser.expect(']')?;
+                    step
                 }
+                _ => return parser.error("expected '.' or '['"),
+            };
+            path.steps.push(step);
+        }
+        Ok(path)
+    }
+}
 
-                use IonType::*;
-                match ion_type {
-                    Null => unreachable!("null values are handled prior to this match"),
-                    Boolean => writer.write_bool(reader.read_bool()?)?,
-                    Integer => writer.write_integer(&reader.read_integer()?)?,
-                    Float => {
-                        let float64 = reader.read_f64()?;
-                        let float32 = float64 as f32;
-                        if float32 as f64 == float64 {
-                            // No data lost during cast; write it as an f32
-                            writer.write_f32(float32)?;
-                        } else {
-                            writer.write_f64(float64)?;
-                        }
-                    }
-                    Decimal => writer.write_decimal(&reader.read_decimal()?)?,
-                    Timestamp => writer.write_timestamp(&reader.read_timestamp()?)?,
-                    Symbol => writer.write_symbol(reader.read_symbol()?)?,
-                    String => writer.write_string(reader.read_string()?)?,
-                    Clob => writer.write_clob(reader.read_clob()?)?,
-                    Blob => writer.write_blob(reader.read_blob()?)?,
-                    List => {
-                        reader.step_in()?;
-                        writer.step_in(List)?;
-                    }
-                    SExpression => {
-                        reader.step_in()?;
-                        writer.step_in(SExpression)?;
-                    }
-                    Struct => {
-                        reader.step_in()?;
-                        writer.step_in(Struct)?;
-                    }
+// A cursor over the text of an `IonPath` that is being parsed.
+struct PathParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T> {
+        anyhow::bail!("{} at offset {} of '{}'", message, self.position, self.text)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.peek() != Some(expected) {
+            return self.error(&format!("expected '{}'", expected));
+        }
+        self.position += expected.len_utf8();
+        Ok(())
+    }
+
+    // Parses as many `name::` annotations as appear at the current position.
+    fn parse_annotations(&mut self) -> Result<Vec<String>> {
+        let mut annotations = vec![];
+        loop {
+            let start = self.position;
+            match self.parse_name()? {
+                Some(name) if self.text[self.position..].starts_with("::") => {
+                    self.position += 2;
+                    annotations.push(name);
+                }
+                _ => {
+                    // This wasn't an annotation; rewind so it can be parsed as something else.
+                    self.position = start;
+                    return Ok(annotations);
                 }
             }
-            StreamItem::Nothing if reader.depth() > 0 => {
-                reader.step_out()?;
-                writer.step_out()?;
+        }
+    }
+
+    // Parses a field name (or `*`) at the current position.
+    fn parse_field(&mut self, annotations: Vec<String>) -> Result<PathStep> {
+        let kind = if self.peek() == Some('*') {
+            self.position += 1;
+            PathStepKind::AnyField
+        } else {
+            match self.parse_name()? {
+                Some(name) => PathStepKind::Field(name),
+                None => return self.error("expected a field name or '*'"),
             }
-            StreamItem::Nothing => break,
+        };
+        Ok(PathStep { kind, annotations })
+    }
+
+    // Parses an index (or `*`) at the current position.
+    fn parse_index(&mut self, annotations: Vec<String>) -> Result<PathStep> {
+        if self.peek() == Some('*') {
+            self.position += 1;
+            return Ok(PathStep {
+                kind: PathStepKind::AnyIndex,
+                annotations,
+            });
         }
-        if reader.depth() == 0 {
-            index += 1;
-            values_since_flush += 1;
-            if values_since_flush == FLUSH_EVERY_N {
-                writer.flush()?;
-                values_since_flush = 0;
+        let digits: &str = &self.text[self.position..];
+        let length = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        let index = match digits[..length].parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => return self.error("expected an index or '*'"),
+        };
+        self.position += length;
+        Ok(PathStep {
+            kind: PathStepKind::Index(index),
+            annotations,
+        })
+    }
+
+    // Parses an identifier (letters, digits, `_`, and `$`) or a 'quoted' name. Returns None if
+    // there isn't a name at the current position.
+    fn parse_name(&mut self) -> Result<Option<String>> {
+        let rest = &self.text[self.position..];
+        if let Some(quoted) = rest.strip_prefix('\'') {
+            let mut name = String::new();
+            let mut chars = quoted.char_indices();
+            while let Some((offset, c)) = chars.next() {
+                match c {
+                    '\'' => {
+                        // Skip both quotes and the text between them.
+                        self.position += offset + 2;
+                        return Ok(Some(name));
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, escaped)) => name.push(escaped),
+                        None => break,
+                    },
+                    _ => name.push(c),
+                }
             }
+            return self.error("unterminated quoted name");
+        }
+        let length = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Ok(None);
         }
+        self.position += length;
+        Ok(Some(rest[..length].to_string()))
     }
-    writer.flush()?;
-    Ok(index)
 }
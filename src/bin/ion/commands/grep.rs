@@ -0,0 +1,359 @@
+use crate::commands::dump::{open_output, transcribe_n_values, transcribe_value, IonPath};
+use crate::commands::util::read_element;
+use anyhow::{bail, Context, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use ion_rs::value::owned::Element;
+use ion_rs::value::reader::{element_reader, ElementReader};
+use ion_rs::value::IonElement;
+use ion_rs::*;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{stdin, Write};
+use std::str::FromStr;
+
+pub fn app() -> Command {
+    Command::new("grep")
+        .about("Prints the top-level values that match a predicate")
+        .arg(
+            Arg::new("predicate")
+                .index(1)
+                .required(true)
+                .help("A path, an operator, and an operand, like `orders[*].sku =~ ^abc`")
+                .long_help(
+                    "A path (in the syntax used by `dump --select`), an operator, and an
+operand. A top-level value matches if any of the values at the path
+satisfy the test. The supported operators are:
+
+  =~ <regex>    a string or symbol matches the regular expression
+  == <ion>      the value equals the given Ion value
+  != <ion>      the value does not equal the given Ion value
+  < <= > >=     the value is a number and compares to the given
+                int, float, or decimal as specified
+
+For example: `status == shipped`, `items[*].sku =~ ^ab`, or
+`total >= 100.5`. An empty path tests the top-level value itself.",
+                ),
+        )
+        .arg(
+            Arg::new("invert-match")
+                .long("invert-match")
+                .short('v')
+                .action(ArgAction::SetTrue)
+                .help("Print the values that do not match"),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .short('c')
+                .action(ArgAction::SetTrue)
+                .help("Print the number of matching values instead of the values themselves"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .default_value("pretty")
+                .value_parser(["binary", "text", "pretty", "lines"])
+                .help("Output format"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Output file [default: STDOUT]"),
+        )
+        .arg(
+            // All argv entries after the predicate and any `clap`-managed options are considered
+            // input files.
+            Arg::new("input")
+                .index(2)
+                .help("Input file [default: STDIN]")
+                .action(ArgAction::Append)
+                .trailing_var_arg(true),
+        )
+}
+
+pub fn run(_command_name: &str, matches: &ArgMatches) -> Result<()> {
+    // The predicate is required, so we can unwrap this safely.
+    let predicate_text = matches.get_one::<String>("predicate").unwrap();
+    let predicate = Predicate::from_str(predicate_text)
+        .with_context(|| format!("Invalid predicate: '{}'", predicate_text))?;
+    let invert_match = matches.get_flag("invert-match");
+    let count_only = matches.get_flag("count");
+    // --format pretty|text|lines|binary
+    // `clap` validates the specified format and provides a default otherwise.
+    let format = matches.get_one::<String>("format").unwrap();
+
+    let mut output = open_output(matches)?;
+    let grep = Grep {
+        predicate: &predicate,
+        invert_match,
+        count_only,
+    };
+
+    if count_only {
+        // Like coreutils' `grep -c`, prefix each count with its file name if there's more than one.
+        match matches.get_many::<String>("input") {
+            Some(input_file_iter) => {
+                let input_files: Vec<&String> = input_file_iter.collect();
+                for input_file in &input_files {
+                    let mut reader = open_input(input_file)?;
+                    let count = grep.run(&mut reader, &mut NullWriter)?;
+                    if input_files.len() > 1 {
+                        write!(output, "{}:", input_file)?;
+                    }
+                    writeln!(output, "{}", count)?;
+                }
+            }
+            None => {
+                let mut reader = ReaderBuilder::new().build(stdin().lock())?;
+                writeln!(output, "{}", grep.run(&mut reader, &mut NullWriter)?)?;
+            }
+        }
+        output.flush()?;
+        return Ok(());
+    }
+
+    // A single writer is used for every input so the matches form one output stream.
+    match format.as_str() {
+        "pretty" => {
+            let mut writer = TextWriterBuilder::pretty().build(output)?;
+            grep.run_on_inputs(matches, &mut writer)
+        }
+        "text" => {
+            let mut writer = TextWriterBuilder::default().build(output)?;
+            grep.run_on_inputs(matches, &mut writer)
+        }
+        "lines" => {
+            let mut writer = TextWriterBuilder::lines().build(output)?;
+            grep.run_on_inputs(matches, &mut writer)
+        }
+        "binary" => {
+            let mut writer = BinaryWriterBuilder::new().build(output)?;
+            grep.run_on_inputs(matches, &mut writer)
+        }
+        unrecognized => unreachable!(
+            "'format' was '{}' instead of 'pretty', 'text', 'lines', or 'binary'",
+            unrecognized
+        ),
+    }
+}
+
+fn open_input(input_file: &str) -> Result<Reader<'static>> {
+    let file =
+        File::open(input_file).with_context(|| format!("Could not open file '{}'", input_file))?;
+    Ok(ReaderBuilder::new().build(file)?)
+}
+
+// Stands in for an output writer when only the number of matches is needed.
+struct NullWriter;
+
+struct Grep<'a> {
+    predicate: &'a Predicate,
+    invert_match: bool,
+    count_only: bool,
+}
+
+impl<'a> Grep<'a> {
+    fn run_on_inputs<W: IonWriter>(&self, matches: &ArgMatches, writer: &mut W) -> Result<()> {
+        if let Some(input_file_iter) = matches.get_many::<String>("input") {
+            for input_file in input_file_iter {
+                let mut reader = open_input(input_file)?;
+                self.run(&mut reader, &mut Some(&mut *writer))
+                    .with_context(|| format!("Could not read '{}'", input_file))?;
+            }
+        } else {
+            let mut reader = ReaderBuilder::new().build(stdin().lock())?;
+            self.run(&mut reader, &mut Some(&mut *writer))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Tests each top-level value in the Reader against the predicate, writing the ones that
+    /// match to `writer` unless only a count was requested. Returns the number of matches.
+    fn run<O: MatchOutput>(&self, reader: &mut Reader, output: &mut O) -> Result<usize> {
+        let mut matches = 0;
+        // Each value is copied to a buffer so it can be read once to test it and again to write it.
+        let mut buffer = Vec::new();
+        while let StreamItem::Value(ion_type) | StreamItem::Null(ion_type) = reader.next()? {
+            buffer.clear();
+            let mut buffer_writer = BinaryWriterBuilder::new().build(&mut buffer)?;
+            transcribe_value(reader, &mut buffer_writer, ion_type, None)?;
+            buffer_writer.flush()?;
+            drop(buffer_writer);
+
+            if self.predicate.matches(&buffer)? == self.invert_match {
+                continue;
+            }
+            matches += 1;
+            if !self.count_only {
+                output.write_match(&buffer)?;
+            }
+        }
+        Ok(matches)
+    }
+}
+
+// Where `Grep` sends the values that match.
+trait MatchOutput {
+    fn write_match(&mut self, value: &[u8]) -> Result<()>;
+}
+
+impl MatchOutput for NullWriter {
+    fn write_match(&mut self, _value: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: IonWriter> MatchOutput for Option<&mut W> {
+    fn write_match(&mut self, value: &[u8]) -> Result<()> {
+        if let Some(writer) = self {
+            let mut reader = ReaderBuilder::new().build(value)?;
+            transcribe_n_values(&mut reader, *writer, None, None)?;
+        }
+        Ok(())
+    }
+}
+
+/// A path and a test to apply to each of the values that the path selects.
+struct Predicate {
+    path: IonPath,
+    test: Test,
+}
+
+enum Test {
+    Matches(Regex),
+    Equals(Element),
+    NotEquals(Element),
+    Compare(Vec<Ordering>, Decimal),
+}
+
+// Operators are listed so that none is a prefix of one that follows it.
+const OPERATORS: &[&str] = &["=~", "==", "!=", "<=", ">=", "<", ">"];
+
+impl FromStr for Predicate {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        // Find the first operator character that isn't inside of a quoted field name.
+        let mut in_quotes = false;
+        let mut escaped = false;
+        let operator_start = text.char_indices().find_map(|(index, c)| {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_quotes => escaped = true,
+                '\'' => in_quotes = !in_quotes,
+                '=' | '!' | '<' | '>' if !in_quotes => return Some(index),
+                _ => {}
+            }
+            None
+        });
+        let (operator_start, operator) = match operator_start.and_then(|start| {
+            OPERATORS
+                .iter()
+                .find(|operator| text[start..].starts_with(*operator))
+                .map(|operator| (start, *operator))
+        }) {
+            Some(found) => found,
+            None => bail!("expected one of {}", OPERATORS.join(", ")),
+        };
+
+        let path = IonPath::from_str(text[..operator_start].trim())?;
+        let operand = text[operator_start + operator.len()..].trim();
+        let test = match operator {
+            "=~" => Test::Matches(Regex::new(operand)?),
+            "==" => Test::Equals(read_operand(operand)?),
+            "!=" => Test::NotEquals(read_operand(operand)?),
+            comparison => {
+                let orderings = match comparison {
+                    "<" => vec![Ordering::Less],
+                    "<=" => vec![Ordering::Less, Ordering::Equal],
+                    ">" => vec![Ordering::Greater],
+                    ">=" => vec![Ordering::Greater, Ordering::Equal],
+                    _ => unreachable!("'{}' is not a comparison operator", comparison),
+                };
+                let number = read_operand(operand)?;
+                let number = match number.ion_type() {
+                    IonType::Integer => number.as_integer().and_then(integer_to_decimal),
+                    IonType::Float => number.as_f64().and_then(|f| Decimal::try_from(f).ok()),
+                    IonType::Decimal => number.as_decimal().cloned(),
+                    _ => None,
+                };
+                match number {
+                    Some(number) => Test::Compare(orderings, number),
+                    None => bail!("'{}' is not a number that can be compared", operand),
+                }
+            }
+        };
+        Ok(Predicate { path, test })
+    }
+}
+
+fn read_operand(operand: &str) -> Result<Element> {
+    element_reader()
+        .read_one(operand.as_bytes())
+        .with_context(|| format!("'{}' is not a single Ion value", operand))
+}
+
+fn integer_to_decimal(integer: &Integer) -> Option<Decimal> {
+    match integer {
+        Integer::I64(value) => Some(Decimal::from(*value)),
+        // Arbitrarily large integers are not supported in comparisons.
+        Integer::BigInt(_) => None,
+    }
+}
+
+impl Predicate {
+    /// Returns true if any value that the path selects from the binary Ion value in `value`
+    /// passes the test.
+    fn matches(&self, value: &[u8]) -> IonResult<bool> {
+        let mut reader = ReaderBuilder::new().build(value)?;
+        let ion_type = match reader.next()? {
+            StreamItem::Value(ion_type) | StreamItem::Null(ion_type) => ion_type,
+            StreamItem::Nothing => return Ok(false),
+        };
+        let mut found = false;
+        self.path
+            .visit_selected_values(&mut reader, ion_type, &mut |reader, ion_type| {
+                found = self.test.passes(reader, ion_type)?;
+                // Stop looking once we've found a match.
+                Ok(!found)
+            })?;
+        Ok(found)
+    }
+}
+
+impl Test {
+    /// Returns true if the value on which the Reader is positioned passes this test.
+    fn passes(&self, reader: &mut Reader, ion_type: IonType) -> IonResult<bool> {
+        if let Test::Equals(expected) | Test::NotEquals(expected) = self {
+            let equal = read_element(reader, ion_type)? == *expected;
+            return Ok(equal == matches!(self, Test::Equals(_)));
+        }
+        if reader.is_null() {
+            return Ok(false);
+        }
+        match self {
+            Test::Matches(regex) => match ion_type {
+                IonType::String => Ok(regex.is_match(&reader.read_string()?)),
+                IonType::Symbol => Ok(reader
+                    .read_symbol()?
+                    .text()
+                    .is_some_and(|text| regex.is_match(text))),
+                _ => Ok(false),
+            },
+            Test::Compare(orderings, expected) => {
+                let number = match ion_type {
+                    IonType::Integer => integer_to_decimal(&reader.read_integer()?),
+                    IonType::Float => Decimal::try_from(reader.read_f64()?).ok(),
+                    IonType::Decimal => Some(reader.read_decimal()?),
+                    _ => None,
+                };
+                Ok(number.is_some_and(|number| orderings.contains(&number.cmp(expected))))
+            }
+            Test::Equals(_) | Test::NotEquals(_) => unreachable!("handled above"),
+        }
+    }
+}
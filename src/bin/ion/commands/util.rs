@@ -0,0 +1,88 @@
+use ion_rs::value::native_writer::NativeElementWriter;
+use ion_rs::value::owned::{Element, Value};
+use ion_rs::value::writer::ElementWriter;
+use ion_rs::*;
+
+/// Writes the element as compact Ion text. Unlike Element's implementation of `Display`, this
+/// supports typed nulls like `null.int`.
+pub(crate) fn element_to_string(element: &Element) -> IonResult<String> {
+    let mut buffer = Vec::new();
+    let text_writer = TextWriterBuilder::new().build(&mut buffer)?;
+    let mut element_writer = NativeElementWriter::new(text_writer);
+    element_writer.write(element)?;
+    element_writer.finish()?.flush()?;
+    Ok(String::from_utf8(buffer).unwrap())
+}
+
+/// Reads the value on which the Reader is positioned, including its annotations and any values
+/// nested inside of it, into an Element.
+pub(crate) fn read_element(reader: &mut Reader, ion_type: IonType) -> IonResult<Element> {
+    let annotations = reader.annotations().collect::<IonResult<Vec<_>>>()?;
+    if reader.is_null() {
+        return Ok(Element::new(annotations, Value::Null(ion_type)));
+    }
+
+    use IonType::*;
+    let value = match ion_type {
+        Null => unreachable!("null values are handled prior to this match"),
+        Boolean => Value::Boolean(reader.read_bool()?),
+        Integer => Value::Integer(reader.read_integer()?),
+        Float => Value::Float(reader.read_f64()?),
+        Decimal => Value::Decimal(reader.read_decimal()?),
+        Timestamp => Value::Timestamp(reader.read_timestamp()?),
+        Symbol => Value::Symbol(reader.read_symbol()?),
+        String => Value::String(reader.read_string()?),
+        Clob => Value::Clob(reader.read_clob()?),
+        Blob => Value::Blob(reader.read_blob()?),
+        List | SExpression => {
+            let mut values = Vec::new();
+            reader.step_in()?;
+            while let StreamItem::Value(child_type) | StreamItem::Null(child_type) =
+                reader.next()?
+            {
+                values.push(read_element(reader, child_type)?);
+            }
+            reader.step_out()?;
+            let sequence = values.into_iter().collect();
+            match ion_type {
+                List => Value::List(sequence),
+                _ => Value::SExpression(sequence),
+            }
+        }
+        Struct => {
+            let mut fields = Vec::new();
+            reader.step_in()?;
+            while let StreamItem::Value(child_type) | StreamItem::Null(child_type) =
+                reader.next()?
+            {
+                fields.push((reader.field_name()?, read_element(reader, child_type)?));
+            }
+            reader.step_out()?;
+            Value::Struct(fields.into_iter().collect())
+        }
+    };
+    Ok(Element::new(annotations, value))
+}
+
+/// Appends an index step like `[0]` to `path`.
+pub(crate) fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+/// Appends a field step to `path`, quoting the name if it isn't a plain identifier so the result
+/// can be used with `dump --select`.
+pub(crate) fn field_path(path: &str, name: &str) -> String {
+    let is_identifier = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        format!("{}.{}", path, name)
+    } else {
+        format!(
+            "{}.'{}'",
+            path,
+            name.replace('\\', "\\\\").replace('\'', "\\'")
+        )
+    }
+}
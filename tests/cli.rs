@@ -91,7 +91,7 @@ fn run_it<S: AsRef<str>>(
 
     let mut cmd = Command::cargo_bin("ion")?;
     cmd.arg("dump").timeout(Duration::new(5, 0));
-    if format_flag != "" {
+    if !format_flag.is_empty() {
         cmd.arg("-f");
         cmd.arg(format_flag);
     }
@@ -114,7 +114,7 @@ fn run_it<S: AsRef<str>>(
         FileMode::Named => {
             // dump our test data to input file
             let mut input_file = File::create(&input_path)?;
-            input_file.write(ion_text.as_ref().as_bytes())?;
+            input_file.write_all(ion_text.as_ref().as_bytes())?;
             input_file.flush()?;
 
             // TODO: test multiple input files
@@ -172,9 +172,9 @@ fn test_write_all_values(#[case] number: i32, #[case] expected_output: &str) ->
     let temp_dir = TempDir::new()?;
     let input_path = temp_dir.path().join("test.ion");
     let mut input_file = File::create(&input_path)?;
-    input_file.write(test_data.as_bytes())?;
+    input_file.write_all(test_data.as_bytes())?;
     input_file.flush()?;
-    cmd.args(&[
+    cmd.args([
         "beta",
         "head",
         "--values",
@@ -189,3 +189,1661 @@ fn test_write_all_values(#[case] number: i32, #[case] expected_output: &str) ->
     assert_eq!(stdout.trim_end(), expected_output);
     Ok(())
 }
+
+#[test]
+/// Calls ion-cli beta inspect on a text Ion file and checks that each value is reported alongside
+/// its byte offset, length, and line/column span.
+fn test_inspect_text_ion() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    let test_data = "// comment\n{foo: [1, 2]}\nbar::baz\n";
+    let temp_dir = TempDir::new()?;
+    let input_path = temp_dir.path().join("test.ion");
+    let mut input_file = File::create(&input_path)?;
+    input_file.write_all(test_data.as_bytes())?;
+    input_file.flush()?;
+    cmd.args(["beta", "inspect", input_path.to_str().unwrap()]);
+    let command_assert = cmd.assert().success();
+    let output = command_assert.get_output();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<&str> = stdout.lines().skip(3).collect();
+    let expected_rows = [
+        ("11", "13", "2:1-2:13", "{"),
+        ("12", "11", "2:2-2:12", "'foo': ["),
+        ("18", "1", "2:8", "1,"),
+        ("21", "1", "2:11", "2,"),
+        ("22", "1", "2:12", "],"),
+        ("23", "1", "2:13", "}"),
+        ("25", "8", "3:1-3:8", "'bar'::baz"),
+    ];
+    assert_eq!(rows.len(), expected_rows.len());
+    for (row, (offset, length, span, text)) in rows.iter().zip(expected_rows) {
+        let columns: Vec<&str> = row.split(" | ").map(str::trim).collect();
+        assert_eq!(columns, vec![offset, length, span, text]);
+    }
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta inspect with --skip-values and --limit-values and checks that only the
+/// requested top-level values are displayed.
+fn test_inspect_skip_and_limit_values() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "inspect",
+        "--skip-values",
+        "1",
+        "--limit-values",
+        "2",
+    ])
+    .write_stdin("{a: 1} [2] three (4) 5");
+    let command_assert = cmd.assert().success();
+    let output = command_assert.get_output();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let text_column: Vec<&str> = stdout
+        .lines()
+        .skip(3)
+        .map(|row| row.rsplit(" | ").next().unwrap().trim())
+        .collect();
+    assert_eq!(
+        text_column,
+        vec![
+            "// Skipped 6 bytes of user-level data",
+            "[",
+            "2,",
+            "]",
+            "three",
+            "// --limit-values reached, ending."
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta inspect with --format json on binary Ion and checks the encoding details
+/// reported for each item.
+fn test_inspect_json_records() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    // A binary IVM followed by the annotated integer `$4::5`
+    cmd.args(["beta", "inspect", "--format", "json"])
+        .write_stdin([0xE0, 0x01, 0x00, 0xEA, 0xE4, 0x81, 0x84, 0x21, 0x05].as_slice());
+    let command_assert = cmd.assert().success();
+    let output = command_assert.get_output();
+    let records: Vec<serde_json::Value> = output
+        .stdout
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_slice)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["kind"], "version_marker");
+    assert_eq!(records[1]["offset"], 4);
+    assert_eq!(records[1]["length"], 5);
+    assert_eq!(records[1]["type_descriptor"], 0x21);
+    assert_eq!(records[1]["annotation_wrapper_bytes"], "e4 81 84");
+    assert_eq!(records[1]["annotation_ids"], serde_json::json!([4]));
+    assert_eq!(records[1]["annotations"], serde_json::json!(["name"]));
+    assert_eq!(records[1]["value"], "5");
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta inspect with --label-bytes and checks that each row of hex bytes is followed
+/// by the role of each byte.
+fn test_inspect_label_bytes() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    // A binary IVM followed by the annotated integer `$4::5`
+    cmd.args(["beta", "inspect", "--label-bytes"])
+        .write_stdin([0xE0, 0x01, 0x00, 0xEA, 0xE4, 0x81, 0x84, 0x21, 0x05].as_slice());
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    // Each row is split into its offset, length, binary, and text columns.
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .filter(|line| line.contains(" | "))
+        .map(|line| line.split(" | ").map(str::trim).collect())
+        .collect();
+    let annotations_row = rows.iter().position(|row| row[2] == "e4 81 84").unwrap();
+    assert_eq!(rows[annotations_row + 1][2], "aw aw as");
+    let value_row = rows.iter().position(|row| row[2] == "21 05").unwrap();
+    assert_eq!(rows[value_row + 1][2], "td bd");
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta inspect with --show-symbols on a stream with three local symbol tables, the
+/// last of which imports a shared symbol table, and checks the rendered tables and the origin
+/// reported for each symbol ID.
+fn test_inspect_show_symbols() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    // $ion_symbol_table::{symbols: ["foo", "bar"]}
+    // foo::bar
+    // $ion_symbol_table::{imports: $ion_symbol_table, symbols: ["baz", "foo", null]}
+    // {baz: foo} // The second 'foo', $13
+    // $ion_symbol_table::{imports: [{name: "com.x", version: 2, max_id: 3}], symbols: ["qux"]}
+    // $11 // From com.x, with unknown text
+    // qux
+    #[rustfmt::skip]
+    let input: &[u8] = &[
+        0xE0, 0x01, 0x00, 0xEA,
+        0xED, 0x81, 0x83, 0xDA, 0x87, 0xB8, 0x83, 0x66, 0x6F, 0x6F, 0x83, 0x62, 0x61, 0x72,
+        0xE4, 0x81, 0x8A, 0x71, 0x0B,
+        0xEE, 0x92, 0x81, 0x83, 0xDE, 0x8E, 0x86, 0x71, 0x03, 0x87, 0xB9, 0x83, 0x62, 0x61,
+        0x7A, 0x83, 0x66, 0x6F, 0x6F, 0x0F,
+        0xD3, 0x8C, 0x71, 0x0D,
+        0xEE, 0x9B, 0x81, 0x83, 0xDE, 0x97, 0x86, 0xBE, 0x8E, 0xDD, 0x84, 0x85, 0x63, 0x6F,
+        0x6D, 0x2E, 0x78, 0x85, 0x21, 0x02, 0x88, 0x21, 0x03, 0x87, 0xB4, 0x83, 0x71, 0x75,
+        0x78,
+        0x71, 0x0B,
+        0x71, 0x0D,
+    ];
+    cmd.args(["beta", "inspect", "--show-symbols"])
+        .write_stdin(input);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    // Collect the text column of each row.
+    let text: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.splitn(4, " | ").nth(3))
+        .map(str::trim)
+        .collect();
+    let expected = [
+        "// Local symbol table #2: 11 imported, 3 appended",
+        "//   $1-$9       imported from system",
+        "//   $10-$11     imported from table #1",
+        "//   $12         'baz'",
+        "//   $13         'foo' (duplicate of $10)",
+        "//   $14         <unknown text>",
+    ];
+    let table_start = text.iter().position(|row| *row == expected[0]).unwrap();
+    assert_eq!(&text[table_start..table_start + expected.len()], &expected);
+    assert!(text.contains(&"bar // $11 (table #1)"));
+    assert!(text.contains(&"foo, // $13 (table #2)"));
+
+    let expected = [
+        "// Local symbol table #3: 12 imported, 1 appended",
+        "//   $1-$9       imported from system",
+        "//   $10-$12     imported from 'com.x' version 2",
+        "//   $13         'qux'",
+    ];
+    let table_start = text.iter().position(|row| *row == expected[0]).unwrap();
+    assert_eq!(&text[table_start..table_start + expected.len()], &expected);
+    assert!(text.contains(&"$11 // $11 ('com.x' version 2)"));
+    assert!(text.contains(&"qux // $13 (table #3)"));
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli cat on binary and text Ion files with different symbol tables and checks that the
+/// binary output contains every value, annotated with its file name and separated as requested.
+fn test_cat_mixed_inputs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let binary_path = temp_dir.path().join("first.10n");
+    let text_path = temp_dir.path().join("second.ion");
+
+    // Each file is encoded with its own symbol table.
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "dump",
+        "--format",
+        "binary",
+        "-o",
+        binary_path.to_str().unwrap(),
+    ])
+    .write_stdin("{foo: bar} baz");
+    cmd.assert().success();
+    File::create(&text_path)?.write_all(b"quux::{zed: [bar, foo]}")?;
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "cat",
+        "--format",
+        "binary",
+        "--annotate-file-names",
+        "--separator",
+        "end_of_file",
+        binary_path.to_str().unwrap(),
+        text_path.to_str().unwrap(),
+    ]);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+
+    let binary_name = binary_path.to_str().unwrap();
+    let text_name = text_path.to_str().unwrap();
+    let expected_text = format!(
+        "'{0}'::{{foo: bar}} '{0}'::baz end_of_file '{1}'::quux::{{zed: [bar, foo]}}",
+        binary_name, text_name
+    );
+    let expected = element_reader().read_all(expected_text.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta head on multiple files and checks that each file's values are preceded by a
+/// `==> file <==` header.
+fn test_head_multiple_files() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let first_path = temp_dir.path().join("first.ion");
+    let second_path = temp_dir.path().join("second.ion");
+    File::create(&first_path)?.write_all(b"1 2 3")?;
+    File::create(&second_path)?.write_all(b"a b c")?;
+    let first_name = first_path.to_str().unwrap();
+    let second_name = second_path.to_str().unwrap();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "head", "-n", "2", first_name, second_name]);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    let expected = format!(
+        "==> {} <==\n1\n2\n\n==> {} <==\na\nb",
+        first_name, second_name
+    );
+    assert_eq!(stdout.trim_end(), expected);
+    Ok(())
+}
+
+#[rstest]
+#[case(0, "")]
+#[case(2, "{n: 4, s: d}\n{n: 5, s: e}")]
+#[case(
+    10,
+    "{n: 1, s: a}\n{n: 2, s: b}\n{n: 3, s: c}\n{n: 4, s: d}\n{n: 5, s: e}"
+)]
+/// Calls ion-cli beta tail on binary Ion with different requested numbers and checks that only
+/// the last values in the stream are written.
+fn test_tail(#[case] number: usize, #[case] expected_output: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["dump", "--format", "binary"])
+        .write_stdin("{n: 1, s: a} {n: 2, s: b} {n: 3, s: c} {n: 4, s: d} {n: 5, s: e}");
+    let binary_ion = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "tail", "-n", &number.to_string()])
+        .write_stdin(binary_ion);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), expected_output);
+    Ok(())
+}
+
+#[rstest]
+#[case("orders[*].items[0].sku", r#""a1" "b1" "c1""#)]
+#[case("orders[*].items[*].usd::price", "usd::3 usd::5")]
+#[case("order::.orders[1].'odd name'", "7")]
+#[case("[2][*]", "1 (2 3)")]
+/// Calls ion-cli dump with --select and checks that only the values at the given path are written.
+fn test_dump_select(#[case] path: &str, #[case] expected_output: &str) -> Result<()> {
+    let test_data = r#"
+    {orders: [{items: [{sku: "a1", price: usd::3}, {sku: "a2"}]}, {items: [{sku: "b1"}]}]}
+    order::{orders: [{items: []}, {items: [{sku: "c1", price: usd::5}], 'odd name': 7}]}
+    [[0], [], [1, (2 3)]]
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["dump", "--format", "binary", "--select", path])
+        .write_stdin(test_data);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(expected_output.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case(&["age >= 18"], "{name: \"alice\", age: 30}\n{name: bob, age: 180d-1}")]
+#[case(&["-v", "age >= 18"], "{name: carol, age: 1.2e1}\n{name: dan}")]
+#[case(&["name =~ ^[a-c]"], "{name: \"alice\", age: 30}\n{name: bob, age: 180d-1}\n{name: carol, age: 1.2e1}")]
+#[case(&["name == bob"], "{name: bob, age: 180d-1}")]
+#[case(&["-c", "age < 20"], "2")]
+/// Calls ion-cli grep with different predicates and options and checks which values are written.
+fn test_grep(#[case] args: &[&str], #[case] expected_output: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["grep", "--format", "lines"])
+        .args(args)
+        .write_stdin(
+        "{name: \"alice\", age: 30} {name: bob, age: 180d-1} {name: carol, age: 1.2e1} {name: dan}",
+    );
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), expected_output);
+    Ok(())
+}
+
+#[rstest]
+#[case(&[], "+ [0]: new\n~ [0].n: 1 => 2\n- [1].a: 1\n+ [1].b: 2\n- [1].b: 2\n+ [1].a: 1\n~ [2]: x::3 => 3")]
+#[case(&["--unordered-fields"], "+ [0]: new\n~ [0].n: 1 => 2\n~ [2]: x::3 => 3")]
+#[case(&["--ignore-annotations", "--unordered-fields"], "+ [0]: new\n~ [0].n: 1 => 2")]
+/// Calls ion-cli diff on a text file and a binary file and checks the reported differences.
+fn test_diff(#[case] args: &[&str], #[case] expected_output: &str) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let left_path = temp_dir.path().join("left.ion");
+    let right_path = temp_dir.path().join("right.10n");
+    File::create(&left_path)?.write_all(b"{n: 1, s: [a, b]} {a: 1, b: 2} x::3 end")?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "dump",
+        "--format",
+        "binary",
+        "-o",
+        right_path.to_str().unwrap(),
+    ])
+    .write_stdin("new {n: 2, s: [a, b]} {b: 2, a: 1} 3 end");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.arg("diff")
+        .args(args)
+        .args([left_path.to_str().unwrap(), right_path.to_str().unwrap()]);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), expected_output);
+    Ok(())
+}
+
+#[rstest]
+#[case("csv", &[], "a,b,c\n1,\"x,y\",\n,,\"[2, null.int]\"")]
+#[case("tsv", &["--columns", "c,a", "--nested", "json"], "c\ta\n\t1\n[2,null]")]
+#[case("csv", &["--no-header", "--columns", "b"], "\"x,y\"\n\"\"")]
+/// Calls ion-cli beta to csv/tsv with different options and checks the rows that are written.
+fn test_to_csv(
+    #[case] format: &str,
+    #[case] args: &[&str],
+    #[case] expected_output: &str,
+) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", format])
+        .args(args)
+        .write_stdin("{a: 1, b: \"x,y\"} {c: [2, null.int], a: null}");
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), expected_output);
+    Ok(())
+}
+
+#[rstest]
+#[case(&[], r#"{n: "1", d: "2.50", t: "2022-01-01T", b: "true", s: "007"} {n: "", d: "x"}"#)]
+#[case(&["--infer-types"], r#"{n: 1, d: 2.50, t: 2022-01-01T, b: true, s: "007"} {n: null, d: "x"}"#)]
+/// Calls ion-cli beta from csv with and without type inference and checks the structs it writes.
+fn test_from_csv(#[case] args: &[&str], #[case] expected_output: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "csv", "--format", "binary"])
+        .args(args)
+        .write_stdin("n,d,t,b,s\n1,2.50,2022-01-01T,true,007\n,x\n");
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(expected_output.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta to yaml and checks that annotations become tags, timestamps stay
+/// timestamps at any precision, strings that look like timestamps are quoted, and each top-level
+/// value becomes its own document.
+fn test_to_yaml() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "yaml"]).write_stdin(concat!(
+        "point::{x: 1, at: 2022-01-01T00:00:00Z, tags: [a, b], ",
+        "day: 2022-01-01, minute: 2022-01-01T00:00Z, label: \"2022-01-01\"} \"7\""
+    ));
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(
+        stdout.trim_end(),
+        concat!(
+            "!point\nx: 1\nat: 2022-01-01T00:00:00+00:00\ntags:\n- a\n- b\n",
+            "day: 2022-01-01T\nminute: 2022-01-01T00:00+00:00\nlabel: '2022-01-01'\n--- '7'"
+        )
+    );
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta from yaml on a multi-document stream with tags, aliases, and timestamps
+/// and checks the Ion values that it writes. Only plain scalars become timestamps; quoted ones
+/// stay strings.
+fn test_from_yaml() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "yaml", "--format", "binary"])
+        .write_stdin(concat!(
+            "defaults: &defaults {retries: 3}\n",
+            "service:\n  <<: *defaults\n  name: !id::v2 api\n",
+            "deployed: 2022-01-02 03:04:05 +1\n",
+            "checked: [2022-01-02 03:04, 2022T, 2022-01T, 2022-01-02T]\n",
+            "labels: ['2022-01-02', \"2022-01-02 03:04:05\", !!str 2022-01-02]\n",
+            "---\n",
+            "- 1.5\n",
+        ));
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        br#"{
+            defaults: {retries: 3},
+            service: {name: id::v2::"api", retries: 3},
+            deployed: 2022-01-02T03:04:05+01:00,
+            checked: [2022-01-02T03:04Z, 2022T, 2022-01T, 2022-01-02T],
+            labels: ["2022-01-02", "2022-01-02 03:04:05", "2022-01-02"]
+        }
+        [1.5e0]"#,
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+/// Converts decimals to YAML and back and checks that they keep every digit, even ones that
+/// don't fit in a 64-bit float.
+fn test_yaml_decimal_round_trip() -> Result<()> {
+    let ion = "{big: 123456789012345678901234567890.123456789, price: 17.50, small: -0.001}";
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "yaml"]).write_stdin(ion);
+    let yaml = cmd.assert().success().get_output().stdout.clone();
+    assert_eq!(
+        String::from_utf8(yaml.clone())?,
+        "big: !!decimal 123456789012345678901234567890.123456789\n\
+         price: !!decimal 17.50\nsmall: !!decimal -0.001\n"
+    );
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "yaml", "--format", "binary"])
+        .write_stdin(yaml);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[test]
+/// Converts Ion to CBOR and back and checks that timestamps, decimals, big ints, blobs, and
+/// annotations survive the round trip.
+fn test_cbor_round_trip() -> Result<()> {
+    let ion = r#"
+    reading::{
+        at: 2022-01-02T03:04:05.5Z,
+        celsius: 21.50,
+        count: 123456789012345678901234567890,
+        raw: {{aGVsbG8=}},
+        tags: ["indoor", "north"]
+    }
+    -7
+    15d2
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "cbor"]).write_stdin(ion);
+    let cbor = cmd.assert().success().get_output().stdout.clone();
+    // The first value starts with the annotations tag, "ION" in ASCII.
+    assert_eq!(&cbor[..4], &[0xda, 0x00, 0x49, 0x4f]);
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "cbor", "--format", "binary"])
+        .write_stdin(cbor);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case("2020T", 1004, "2020-01-01")]
+#[case("2020-05T", 1004, "2020-05-01")]
+#[case("2020-05-06", 1004, "2020-05-06")]
+#[case("2020-05-06T07:08Z", 0, "2020-05-06T07:08:00Z")]
+#[case("2020-05-06T07:08:09.5+01:00", 0, "2020-05-06T07:08:09.5+01:00")]
+/// Converts timestamps of each precision to CBOR and back, and checks that each is written with
+/// the expected tag and that precisions CBOR lacks are filled in with the start of the period.
+fn test_cbor_timestamps(#[case] ion: &str, #[case] tag: u16, #[case] expected: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "cbor"]).write_stdin(ion);
+    let cbor = cmd.assert().success().get_output().stdout.clone();
+    let expected_head = match tag {
+        0 => vec![0xc0],
+        tag => vec![0xd9, (tag >> 8) as u8, tag as u8],
+    };
+    assert_eq!(&cbor[..expected_head.len()], expected_head.as_slice());
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "cbor", "--format", "binary"])
+        .write_stdin(cbor);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    assert_eq!(element_reader().read_all(expected.as_bytes())?, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case(b"\xc0\x61\x31", "found integer")]
+#[case(
+    b"\xc0\x65\x32\x30\x32\x30\x54",
+    "found a timestamp with Year precision"
+)]
+#[case(b"\xd9\x03\xec\x61\x78", "'x' is not a valid date string")]
+/// Calls ion-cli beta from cbor with date/time and date strings that aren't timestamps of the
+/// precision their tag requires, and checks that they're rejected.
+fn test_from_cbor_invalid_timestamp(#[case] cbor: &[u8], #[case] expected: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "cbor"])
+        .write_stdin(cbor.to_vec());
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(stderr.contains(expected), "{}", stderr);
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli diff on values that differ only in the type of a null and checks that both
+/// nulls are printed.
+fn test_diff_typed_nulls() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let left_path = temp_dir.path().join("left.ion");
+    let right_path = temp_dir.path().join("right.ion");
+    File::create(&left_path)?.write_all(b"{a: null.int}")?;
+    File::create(&right_path)?.write_all(b"{a: null.string}")?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "diff",
+        left_path.to_str().unwrap(),
+        right_path.to_str().unwrap(),
+    ]);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), "~ [0].a: null.int => null.string");
+    Ok(())
+}
+
+#[rstest]
+#[case(&[])]
+#[case(&["--decimal-ext-type", "9", "--annotations-ext-type", "10"])]
+/// Converts Ion to MessagePack and back with default and custom extension types and checks that
+/// blobs, timestamps, decimals (including ones with a positive exponent), and annotations survive
+/// the round trip.
+fn test_msgpack_round_trip(#[case] ext_type_args: &[&str]) -> Result<()> {
+    let ion = r#"
+    reading::{
+        at: 2022-01-02T03:04:05.5Z,
+        celsius: 21.50,
+        raw: {{aGVsbG8=}},
+        tags: ["indoor", "north"]
+    }
+    -7
+    15d2
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "msgpack"])
+        .args(ext_type_args)
+        .write_stdin(ion);
+    let msgpack = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "msgpack", "--format", "binary"])
+        .args(ext_type_args)
+        .write_stdin(msgpack);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case("to")]
+#[case("from")]
+/// Calls ion-cli beta to and from msgpack with the same extension type for decimals and annotated
+/// values, which a reader couldn't tell apart, and checks that it's rejected.
+fn test_msgpack_same_ext_types(#[case] direction: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        direction,
+        "msgpack",
+        "--decimal-ext-type",
+        "5",
+        "--annotations-ext-type",
+        "5",
+    ])
+    .write_stdin("");
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(stderr.contains("must be different"), "{}", stderr);
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta from toml and checks that each kind of TOML date-time becomes a timestamp
+/// with the same precision.
+fn test_from_toml() -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "toml", "--format", "binary"])
+        .write_stdin(concat!(
+            "name = \"api\"\n",
+            "released = 2022-01-02\n",
+            "deployed = 2022-01-02T03:04:05.5-07:00\n",
+            "checked = 2022-01-02T03:04:05\n",
+            "[limits]\n",
+            "ports = [8000, 8001]\n",
+        ));
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        br#"{
+            name: "api",
+            released: 2022-01-02T,
+            deployed: 2022-01-02T03:04:05.5-07:00,
+            checked: 2022-01-02T03:04:05-00:00,
+            limits: {ports: [8000, 8001]}
+        }"#,
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case("[1]", "[0] to TOML: only a struct can be a TOML document")]
+#[case("{} {}", "[1] to TOML: a TOML document holds a single top-level table")]
+#[case(
+    "{a: {b: [1, null]}}",
+    "[0].a.b[1] to TOML: TOML does not support nulls"
+)]
+#[case(
+    "{a: [1, \"x\"]}",
+    "[0].a to TOML: TOML arrays cannot mix integers and strings"
+)]
+#[case("{'a b': 2022T}", "[0].'a b' to TOML: TOML dates must include a day")]
+/// Calls ion-cli beta to toml with values that TOML can't represent and checks that the error
+/// names the value's path.
+fn test_to_toml_unsupported(#[case] ion: &str, #[case] expected_error: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "toml"]).write_stdin(ion);
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case("sym", r#"{"$ion_symbol":"sym"}"#)]
+#[case("17.50", r#"{"$ion_decimal":"1750d-2"}"#)]
+#[case("+inf", r#"{"$ion_float":"+inf"}"#)]
+#[case("null.int", r#"{"$ion_null":"int"}"#)]
+#[case("a::{b: 1}", r#"{"$ion_annotations":["a"],"$ion_value":{"b":1}}"#)]
+#[case("{a: 1, a: 2}", r#"{"$ion_struct":[["a",1],["a",2]]}"#)]
+/// Calls ion-cli beta to json --preserve-types and checks how values that JSON can't represent
+/// are written.
+fn test_to_json_preserve_types(#[case] ion: &str, #[case] expected_json: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "json", "--preserve-types"])
+        .write_stdin(ion);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), expected_json);
+    Ok(())
+}
+
+#[test]
+/// Converts Ion to JSON and back with --preserve-types and checks that every value survives the
+/// round trip unchanged.
+fn test_json_preserve_types_round_trip() -> Result<()> {
+    let ion = r#"
+    reading::{
+        at: 2022-01-02T03:04:05.50Z,
+        celsius: 21.50,
+        unit: C,
+        raw: {{aGVsbG8=}},
+        note: {{"hi"}},
+        expr: (+ 1 2),
+        missing: null.string,
+        large: 123456789012345678901234567890,
+        ratio: 1e0,
+        limit: -inf,
+        "$ion_symbol": "not a symbol",
+        tag: x,
+        tag: y
+    }
+    [1, "two", null]
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "json", "--preserve-types"])
+        .write_stdin(ion);
+    let json = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "from",
+        "json",
+        "--preserve-types",
+        "--format",
+        "binary",
+    ])
+    .write_stdin(json);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case::flags(&["--timestamps", "--decimals", "--symbol-fields", "status,tags", "--blob-fields", "image"], None)]
+#[case::rules_file(&["--decimals"], Some("{timestamps: true, symbol_fields: [status, tags], blob_fields: [image]}"))]
+/// Calls ion-cli beta from json with up-conversion rules given as flags or in a rules file and
+/// checks that matching values are converted to the requested Ion types.
+fn test_from_json_up_conversion(#[case] args: &[&str], #[case] rules: Option<&str>) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "json", "--format", "binary"])
+        .args(args);
+    if let Some(rules) = rules {
+        let rules_path = temp_dir.path().join("rules.ion");
+        File::create(&rules_path)?.write_all(rules.as_bytes())?;
+        cmd.args(["--rules", rules_path.to_str().unwrap()]);
+    }
+    cmd.write_stdin(concat!(
+        r#"{"at": "2022-01-02T03:04:05Z", "on": "2022-01-02", "when": "soon", "#,
+        r#""size": 1.5e3, "status": "ok", "tags": ["a", "b"], "name": "ok", "#,
+        r#""image": "aGVsbG8=", "caption": "aGVsbG8="}"#
+    ));
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        br#"{
+            at: 2022-01-02T03:04:05Z,
+            on: 2022-01-02T,
+            when: "soon",
+            size: 15d2,
+            status: ok,
+            tags: [a, b],
+            name: "ok",
+            image: {{aGVsbG8=}},
+            caption: "aGVsbG8="
+        }"#,
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case::lines(&[], "{\"b\":\"é\",\"a\":1}\n2\n")]
+#[case::pretty(&["--pretty"], "{\n  \"b\": \"é\",\n  \"a\": 1\n}\n2\n")]
+#[case::array(&["--layout", "array"], "[{\"b\":\"é\",\"a\":1},2]\n")]
+#[case::array_indent(&["--layout", "array", "--indent", "1"], "[\n {\n  \"b\": \"é\",\n  \"a\": 1\n },\n 2\n]\n")]
+#[case::sorted_ascii(&["--sort-keys", "--ascii"], "{\"a\":1,\"b\":\"\\u00e9\"}\n2\n")]
+/// Calls ion-cli beta to json with each of the layout options and checks the output text.
+fn test_to_json_layout(#[case] args: &[&str], #[case] expected_json: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "json"])
+        .args(args)
+        .write_stdin(r#"{b: "é", a: 1} 2"#);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout, expected_json);
+    Ok(())
+}
+
+#[test]
+/// Calls ion-cli beta to json with a binary and a text input file and checks that their values
+/// are converted as a single stream.
+fn test_to_json_multiple_inputs() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let binary_path = temp_dir.path().join("first.10n");
+    let text_path = temp_dir.path().join("second.ion");
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["dump", "--format", "binary"])
+        .write_stdin("{a: 1} [b]");
+    File::create(&binary_path)?.write_all(&cmd.assert().success().get_output().stdout)?;
+    File::create(&text_path)?.write_all(b"\"c\"")?;
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "json",
+        binary_path.to_str().unwrap(),
+        text_path.to_str().unwrap(),
+    ]);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout, "{\"a\":1}\n[\"b\"]\n\"c\"\n");
+    Ok(())
+}
+
+#[rstest]
+#[case::structs(&[], r#"{order: {'@id': "1", item: ["apple", {'@sku': "B2", '#text': "banana"}], note: null}}"#)]
+#[case::repeated_fields(&["--repeated", "fields"], r#"{order: {'@id': "1", item: "apple", item: {'@sku': "B2", '#text': "banana"}, note: null}}"#)]
+#[case::sexps(&["--mapping", "sexp"], r#"(order {id: "1"} (item {} "apple") (item {sku: "B2"} "banana") (note {}))"#)]
+/// Calls ion-cli beta from xml with each mapping, then converts the result back with beta to xml
+/// and checks that the original document is written.
+fn test_xml_round_trip(#[case] args: &[&str], #[case] expected_ion: &str) -> Result<()> {
+    let xml = r#"<order id="1"><item>apple</item><item sku="B2">banana</item><note/></order>"#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "xml", "--format", "binary"])
+        .args(args)
+        .write_stdin(xml);
+    let command_assert = cmd.assert().success();
+    let ion = command_assert.get_output().stdout.clone();
+    let actual = element_reader().read_all(&ion)?;
+    let expected = element_reader().read_all(expected_ion.as_bytes())?;
+    assert_eq!(expected, actual);
+
+    let mapping = if args.contains(&"sexp") {
+        "sexp"
+    } else {
+        "struct"
+    };
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "xml", "--mapping", mapping])
+        .write_stdin(ion);
+    let command_assert = cmd.assert().success();
+    let stdout = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(stdout.trim_end(), xml);
+    Ok(())
+}
+
+#[rstest]
+#[case::structs(&[], r#"{r: "1"} {r: {'@id': "2"}}"#, "<r>1</r>\n<r id=\"2\"></r>\n")]
+#[case::sexps(&["--mapping", "sexp"], r#"(r {} "1") (r {id: "2"})"#, "<r>1</r>\n<r id=\"2\"/>\n")]
+/// Converts a stream of values to XML with beta to xml, then reads it back with beta from xml and
+/// checks that each root element becomes its own value again.
+fn test_xml_multiple_values_round_trip(
+    #[case] args: &[&str],
+    #[case] ion: &str,
+    #[case] expected_xml: &str,
+) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "xml"]).args(args).write_stdin(ion);
+    let command_assert = cmd.assert().success();
+    let xml = command_assert.get_output().stdout.clone();
+    assert_eq!(String::from_utf8(xml.clone())?, expected_xml);
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "xml", "--format", "binary"])
+        .args(args)
+        .write_stdin(xml);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case::keep("keep", r#"{'x:a': {'@xmlns:x': "urn:x", 'x:b': "1"}}"#)]
+#[case::strip("strip", r#"{a: {b: "1"}}"#)]
+#[case::expand("expand", r#"{'{urn:x}a': {'{urn:x}b': "1"}}"#)]
+/// Calls ion-cli beta from xml with each way of handling namespaces and checks the names.
+fn test_from_xml_namespaces(#[case] namespaces: &str, #[case] expected_ion: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "xml", "--format", "binary"])
+        .args(["--namespaces", namespaces])
+        .write_stdin(r#"<x:a xmlns:x="urn:x"><x:b>1</x:b></x:a>"#);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(expected_ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+const AVRO_SCHEMA: &str = r#"{
+    "type": "record", "name": "Order", "fields": [
+        {"name": "id", "type": "long"},
+        {"name": "status", "type": {"type": "enum", "name": "Status", "symbols": ["NEW", "SHIPPED"]}},
+        {"name": "total", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+        {"name": "placed", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 4}},
+        {"name": "note", "type": ["null", "string"]},
+        {"name": "weight", "type": ["null", "double"]},
+        {"name": "items", "type": {"type": "array", "items": {"type": "record", "name": "Item", "fields": [
+            {"name": "sku", "type": "string"}, {"name": "qty", "type": "int"}
+        ]}}}
+    ]
+}"#;
+
+#[test]
+/// Converts Ion to Avro and back with a schema that uses records, enums, fixed, unions, and the
+/// decimal and timestamp logical types, and checks that the values survive the round trip.
+fn test_avro_round_trip() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("order.avsc");
+    File::create(&schema_path)?.write_all(AVRO_SCHEMA.as_bytes())?;
+    let ion = r#"
+    {
+        id: 1,
+        status: NEW,
+        total: 12.50,
+        placed: 2022-01-02T03:04:05.123Z,
+        hash: {{AQIDBA==}},
+        note: null,
+        weight: 2.5e0,
+        items: [{sku: "a", qty: 2}]
+    }
+    {
+        id: 2,
+        status: SHIPPED,
+        total: -0.05,
+        placed: 2022-01-03T00:00:00.000Z,
+        hash: {{BAMCAQ==}},
+        note: "fragile",
+        weight: null,
+        items: []
+    }
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "avro",
+        "--schema",
+        schema_path.to_str().unwrap(),
+    ])
+    .write_stdin(ion);
+    let avro = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "avro", "--format", "binary"])
+        .write_stdin(avro);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(ion.as_bytes())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+const AVRO_TIMES_SCHEMA: &str = r#"{
+    "type": "record", "name": "Shift", "fields": [
+        {"name": "day", "type": {"type": "int", "logicalType": "date"}},
+        {"name": "starts", "type": {"type": "long", "logicalType": "local-timestamp-millis"}},
+        {"name": "logged", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "opens", "type": {"type": "long", "logicalType": "time-micros"}},
+        {"name": "closes", "type": {"type": "int", "logicalType": "time-millis"}}
+    ]
+}"#;
+
+#[test]
+/// Converts timestamps with an offset to Avro and back and checks that dates and local
+/// timestamps keep their wall-clock date and time, while timestamps are shifted to UTC. Times of
+/// day may be strings or ints in the schema's unit.
+fn test_avro_local_times() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("shift.avsc");
+    File::create(&schema_path)?.write_all(AVRO_TIMES_SCHEMA.as_bytes())?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "avro",
+        "--schema",
+        schema_path.to_str().unwrap(),
+    ])
+    .write_stdin(
+        "{day: 2020-05-06T23:30-07:00, starts: 2020-05-06T07:00-07:00, \
+         logged: 2020-05-06T07:00-07:00, opens: \"07:32:00.25\", closes: 72000000}",
+    );
+    let avro = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "avro", "--format", "binary"])
+        .write_stdin(avro);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        b"{day: 2020-05-06T, starts: 2020-05-06T07:00:00.000-00:00, \
+          logged: 2020-05-06T14:00:00.000Z, opens: \"07:32:00.250000\", closes: \"20:00:00.000\"}",
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case::hours("\"24:00\"", "0", "[0].opens to Avro: \"24:00\" does not match")]
+#[case::minutes("\"07:60\"", "0", "[0].opens to Avro: \"07:60\" does not match")]
+#[case::seconds("\"25:99:99\"", "0", "[0].opens to Avro: \"25:99:99\" does not match")]
+#[case::negative("\"-1:00\"", "0", "[0].opens to Avro: \"-1:00\" does not match")]
+#[case::micros_past_day("86400000000", "0", "[0].opens to Avro: 86400000000 does not match")]
+#[case::millis_past_day("0", "86400000", "[0].closes to Avro: 86400000 does not match")]
+#[case::negative_millis("0", "-1", "[0].closes to Avro: -1 does not match")]
+/// Calls ion-cli beta to avro with times of day outside of a day and checks that the error names
+/// the value's path.
+fn test_to_avro_time_out_of_range(
+    #[case] opens: &str,
+    #[case] closes: &str,
+    #[case] expected_error: &str,
+) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("shift.avsc");
+    File::create(&schema_path)?.write_all(AVRO_TIMES_SCHEMA.as_bytes())?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "avro",
+        "--schema",
+        schema_path.to_str().unwrap(),
+    ])
+    .write_stdin(format!(
+        "{{day: 2020-05-06, starts: 2020-05-06T00:00Z, logged: 2020-05-06T00:00Z, \
+         opens: {}, closes: {}}}",
+        opens, closes
+    ));
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "{id: \"x\"}",
+    "[0].id to Avro: \"x\" does not match the schema type long"
+)]
+#[case(
+    "{id: 1, status: OLD}",
+    "[0].status to Avro: 'OLD' is not one of the enum 'Status'"
+)]
+#[case(
+    "{id: 1, status: NEW, total: 1.234}",
+    "[0].total to Avro: 1234d-3 has more than 2 digits"
+)]
+#[case(
+    "{id: 1, status: NEW, total: 1, placed: 2022T, hash: {{AQIDBA==}}, items: [{sku: 7}]}",
+    "[0].items[0].sku to Avro: 7 does not match the schema type string"
+)]
+#[case(
+    "{id: 1, status: NEW, total: 1, placed: 2022T, hash: {{AQIDBA==}}, weight: kg::2.5, items: [7]}",
+    "[0].items[0] to Avro: 7 does not match the schema type record"
+)]
+#[case(
+    "{id: 1, extra: 2}",
+    "[0].extra to Avro: the record 'Order' has no field 'extra'"
+)]
+/// Calls ion-cli beta to avro with values that don't fit the schema and checks that the error
+/// names the value's path. Annotated numbers, like `kg::2.5`, fit a double.
+fn test_to_avro_mismatch(#[case] ion: &str, #[case] expected_error: &str) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("order.avsc");
+    File::create(&schema_path)?.write_all(AVRO_SCHEMA.as_bytes())?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "avro",
+        "--schema",
+        schema_path.to_str().unwrap(),
+    ])
+    .write_stdin(ion);
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}
+
+#[test]
+/// Converts structs to Parquet without a schema and checks the inferred column types, the row
+/// groups, and the values read back from the file.
+fn test_to_parquet_inferred_schema() -> Result<()> {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Decimal128Type, Int64Type, TimestampMicrosecondType};
+    use arrow_array::Array;
+    use arrow_schema::{DataType, TimeUnit};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let temp_dir = TempDir::new()?;
+    let output_path = temp_dir.path().join("rows.parquet");
+    let ion = r#"
+        {id: 1, price: 1.5, placed: 2020-01-02T03:04:05.123Z, tags: [a, b], size: {w: 1}}
+        {id: 2, price: 17, placed: 2020-01-02T00:00:00-07:00, hash: {{AQI=}}, size: {w: 3, h: 2}}
+        {id: 3, price: 0.25, tags: []}
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "parquet",
+        "--batch-size",
+        "2",
+        "-o",
+        output_path.to_str().unwrap(),
+    ])
+    .write_stdin(ion);
+    cmd.assert().success();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&output_path)?)?;
+    assert_eq!(2, builder.metadata().num_row_groups());
+    let schema = builder.schema().clone();
+    let data_types: Vec<(&str, &DataType)> = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type()))
+        .collect();
+    assert_eq!("id", data_types[0].0);
+    assert_eq!(&DataType::Int64, data_types[0].1);
+    assert_eq!(("price", &DataType::Decimal128(38, 2)), data_types[1]);
+    assert_eq!(
+        (
+            "placed",
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        ),
+        data_types[2]
+    );
+    assert!(matches!(data_types[3], ("tags", DataType::List(_))));
+    assert!(matches!(data_types[4], ("size", DataType::Struct(fields)) if fields.len() == 2));
+    assert_eq!(("hash", &DataType::Binary), data_types[5]);
+
+    let batch = builder.build()?.next().unwrap()?;
+    assert_eq!(
+        vec![1, 2, 3],
+        batch
+            .column(0)
+            .as_primitive::<Int64Type>()
+            .values()
+            .to_vec()
+    );
+    let prices = batch.column(1).as_primitive::<Decimal128Type>();
+    assert_eq!(vec![150, 1700, 25], prices.values().to_vec());
+    let placed = batch.column(2).as_primitive::<TimestampMicrosecondType>();
+    assert_eq!(1_577_934_245_123_000, placed.value(0));
+    assert_eq!(1_577_948_400_000_000, placed.value(1));
+    assert!(placed.is_null(2));
+    let tags = batch.column(3).as_list::<i32>();
+    assert_eq!(
+        vec!["a", "b"],
+        tags.value(0)
+            .as_string::<i32>()
+            .iter()
+            .flatten()
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+/// Converts values to Parquet with a schema file and checks that the rows that don't fit it are
+/// written to the rejects file unchanged.
+fn test_to_parquet_rejects() -> Result<()> {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("schema.ion");
+    File::create(&schema_path)?
+        .write_all(b"{id: int, total: (decimal 9 2), items: [{sku: string}], raw: ion}")?;
+    let output_path = temp_dir.path().join("rows.parquet");
+    let rejects_path = temp_dir.path().join("rejects.ion");
+    let ion = r#"
+        {id: 1, total: 1.25, items: [{sku: "a"}], raw: [1, "x"]}
+        {id: 2, total: 1.234}
+        {id: 3, items: [{sku: 7}]}
+        {id: 4, other: true}
+        five
+        {id: 6, total: null, raw: {a: 1}}
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "parquet",
+        "--schema",
+        schema_path.to_str().unwrap(),
+        "--rejects",
+        rejects_path.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ])
+    .write_stdin(ion);
+    let command_assert = cmd.assert().success();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains("Wrote 4 value(s)") && stderr.contains("[1].total to Parquet"),
+        "{}",
+        stderr
+    );
+
+    let mut rejects = Vec::new();
+    File::open(&rejects_path)?.read_to_end(&mut rejects)?;
+    let expected = element_reader().read_all(
+        br#"{id: 2, total: 1.234} {id: 3, items: [{sku: 7}]} {id: 4, other: true} five"#,
+    )?;
+    assert_eq!(expected, element_reader().read_all(&rejects)?);
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&output_path)?)?.build()?;
+    let rows: usize = reader
+        .map(|batch| batch.map(|batch| batch.num_rows()))
+        .sum::<Result<usize, _>>()?;
+    assert_eq!(2, rows);
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "{id: \"x\"}",
+    "[0].id to Parquet: \"x\" does not fit the column type int"
+)]
+#[case(
+    "{total: 1.234}",
+    "[0].total to Parquet: 1234d-3 does not fit the column type (decimal 9 2)"
+)]
+#[case(
+    "{items: [{sku: a}, {sku: 1}]}",
+    "[0].items[1].sku to Parquet: 1 does not fit the column type string"
+)]
+#[case("{id: 1, id: 2}", "[0].id to Parquet: the field is repeated")]
+#[case("[1]", "[0] to Parquet: only structs can be written as rows")]
+/// Calls ion-cli beta to parquet with values that don't fit the schema and no rejects file, and
+/// checks that the error names the value's path.
+fn test_to_parquet_mismatch(#[case] ion: &str, #[case] expected_error: &str) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let schema_path = temp_dir.path().join("schema.ion");
+    File::create(&schema_path)?
+        .write_all(b"{id: int, total: (decimal 9 2), items: [{sku: string}]}")?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "parquet",
+        "--schema",
+        schema_path.to_str().unwrap(),
+        "-o",
+        temp_dir.path().join("rows.parquet").to_str().unwrap(),
+    ])
+    .write_stdin(ion);
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case::file("file")]
+#[case::stream("stream")]
+/// Converts Ion with every scalar type to Arrow IPC and back, and checks the values that come
+/// back. Missing fields come back as nulls and timestamps with microsecond precision.
+fn test_arrow_round_trip(#[case] ipc: &str) -> Result<()> {
+    let ion = r#"
+        {b: true, i: 1, f: 2.5e0, d: 1.50, t: 2020-01-02T03:04:05.123Z, s: "a", y: sym,
+         l: {{aGk=}}, c: {{"clob"}}, list: [1, 2], sexp: (x y), st: {x: 1}, any: 1, n: null}
+        {b: false, i: -2, d: 17, t: 2020-01-02T00:00:00-07:00, s: "b", y: 'other sym',
+         l: {{}}, list: [], st: {x: 2, z: "q"}, any: [a]}
+    "#;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "arrow", "--ipc", ipc, "--batch-size", "1"])
+        .write_stdin(ion);
+    let arrow = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "arrow", "--format", "binary"])
+        .write_stdin(arrow);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        br#"
+        {b: true, i: 1, f: 2.5e0, d: 1.50, t: 2020-01-02T03:04:05.123000Z, s: "a", y: sym,
+         l: {{aGk=}}, c: {{Y2xvYg==}}, list: [1, 2], sexp: [x, y], st: {x: 1, z: null},
+         any: 1, n: null}
+        {b: false, i: -2, f: null, d: 17.00, t: 2020-01-02T07:00:00.000000Z, s: "b",
+         y: 'other sym', l: {{}}, c: null, list: [], sexp: null, st: {x: 2, z: "q"},
+         any: [a], n: null}
+        "#,
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case::drop("drop", Some("{id: 1, price: 2}"))]
+#[case::column("column", Some("row::{id: 1, price: usd::2}"))]
+#[case::error("error", None)]
+/// Converts annotated values to Arrow with each of the --annotations policies and checks what
+/// comes back, or that the conversion fails.
+fn test_to_arrow_annotations(#[case] policy: &str, #[case] expected: Option<&str>) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "arrow", "--annotations", policy])
+        .write_stdin("row::{id: 1, price: usd::2}");
+    let expected = match expected {
+        Some(expected) => expected,
+        None => {
+            let command_assert = cmd.assert().failure();
+            let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+            assert!(
+                stderr.contains("[0] to Arrow: annotations are not allowed"),
+                "{}",
+                stderr
+            );
+            return Ok(());
+        }
+    };
+    let arrow = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "arrow", "--format", "binary"])
+        .write_stdin(arrow);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    assert_eq!(element_reader().read_all(expected.as_bytes())?, actual);
+    Ok(())
+}
+
+/// Builds a descriptor set for the following, as `protoc --include_imports --descriptor_set_out`
+/// would:
+/// ```proto
+/// syntax = "proto3";
+/// package shop;
+/// import "google/protobuf/timestamp.proto";
+/// enum Status { NEW = 0; SHIPPED = 1; }
+/// message Order {
+///   int64 id = 1;
+///   Status status = 2;
+///   repeated string tags = 3;
+///   google.protobuf.Timestamp placed = 4;
+///   bytes hash = 5;
+///   map<string, int32> counts = 6;
+///   uint32 item_count = 7;
+///   double total = 8;
+/// }
+/// ```
+fn protobuf_descriptor_set() -> Vec<u8> {
+    use prost_reflect::prost::Message;
+    use prost_reflect::prost_types::field_descriptor_proto::{Label, Type};
+    use prost_reflect::prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto, FileDescriptorSet, MessageOptions,
+    };
+
+    let field =
+        |name: &str, number: i32, field_type: Type, type_name: Option<&str>| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(field_type as i32),
+            type_name: type_name.map(str::to_string),
+            ..Default::default()
+        };
+    let timestamp = FileDescriptorProto {
+        name: Some("google/protobuf/timestamp.proto".to_string()),
+        package: Some("google.protobuf".to_string()),
+        message_type: vec![DescriptorProto {
+            name: Some("Timestamp".to_string()),
+            field: vec![
+                field("seconds", 1, Type::Int64, None),
+                field("nanos", 2, Type::Int32, None),
+            ],
+            ..Default::default()
+        }],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    };
+    let counts_entry = DescriptorProto {
+        name: Some("CountsEntry".to_string()),
+        field: vec![
+            field("key", 1, Type::String, None),
+            field("value", 2, Type::Int32, None),
+        ],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let order = DescriptorProto {
+        name: Some("Order".to_string()),
+        field: vec![
+            field("id", 1, Type::Int64, None),
+            field("status", 2, Type::Enum, Some(".shop.Status")),
+            FieldDescriptorProto {
+                label: Some(Label::Repeated as i32),
+                ..field("tags", 3, Type::String, None)
+            },
+            field(
+                "placed",
+                4,
+                Type::Message,
+                Some(".google.protobuf.Timestamp"),
+            ),
+            field("hash", 5, Type::Bytes, None),
+            FieldDescriptorProto {
+                label: Some(Label::Repeated as i32),
+                ..field("counts", 6, Type::Message, Some(".shop.Order.CountsEntry"))
+            },
+            field("item_count", 7, Type::Uint32, None),
+            field("total", 8, Type::Double, None),
+        ],
+        nested_type: vec![counts_entry],
+        ..Default::default()
+    };
+    let status = EnumDescriptorProto {
+        name: Some("Status".to_string()),
+        value: ["NEW", "SHIPPED"]
+            .iter()
+            .enumerate()
+            .map(|(number, name)| EnumValueDescriptorProto {
+                name: Some(name.to_string()),
+                number: Some(number as i32),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let shop = FileDescriptorProto {
+        name: Some("shop.proto".to_string()),
+        package: Some("shop".to_string()),
+        dependency: vec!["google/protobuf/timestamp.proto".to_string()],
+        message_type: vec![order],
+        enum_type: vec![status],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    };
+    FileDescriptorSet {
+        file: vec![timestamp, shop],
+    }
+    .encode_to_vec()
+}
+
+#[test]
+/// Converts Ion to a stream of length-delimited protobuf messages and back, and checks that the
+/// values survive the round trip. JSON field names are accepted, and unset fields are left out.
+fn test_protobuf_round_trip() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let descriptor_set_path = temp_dir.path().join("shop.pb");
+    File::create(&descriptor_set_path)?.write_all(&protobuf_descriptor_set())?;
+    let ion = r#"
+        {id: 1, status: SHIPPED, tags: ["a", "b"], placed: 2022-01-02T03:04:05.123Z,
+         hash: {{AQID}}, counts: {x: 2, y: -1}, itemCount: 3, total: 12.5e0}
+        {id: 2, status: NEW, tags: [], placed: null, counts: {}}
+        {id: 3, status: 7, total: 0.25}
+    "#;
+    let args = [
+        "--descriptor-set",
+        descriptor_set_path.to_str().unwrap(),
+        "--message",
+        "shop.Order",
+    ];
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "protobuf"])
+        .args(args)
+        .write_stdin(ion);
+    let protobuf = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "from", "protobuf", "--format", "binary"])
+        .args(args)
+        .write_stdin(protobuf);
+    let command_assert = cmd.assert().success();
+    let actual = element_reader().read_all(&command_assert.get_output().stdout)?;
+    let expected = element_reader().read_all(
+        br#"
+        {id: 1, status: SHIPPED, tags: ["a", "b"], placed: 2022-01-02T03:04:05.123Z,
+         hash: {{AQID}}, counts: {x: 2, y: -1}, item_count: 3, total: 12.5e0}
+        {id: 2}
+        {id: 3, status: 7, total: 0.25e0}
+        "#,
+    )?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case(&[0x05, 0x08, 0x01], "expected 5 bytes but found 2")]
+#[case(
+    &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+    "expected 72057594037927935 bytes but found 0"
+)]
+#[case(&[0x02, 0x08, 0x01, 0x80], "ends in the middle of a message length")]
+/// Calls ion-cli beta from protobuf with a truncated stream or an oversized length prefix and
+/// checks that it fails with an error rather than trying to allocate the whole message up front.
+fn test_from_protobuf_truncated(#[case] protobuf: &[u8], #[case] expected: &str) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let descriptor_set_path = temp_dir.path().join("shop.pb");
+    File::create(&descriptor_set_path)?.write_all(&protobuf_descriptor_set())?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "from",
+        "protobuf",
+        "--descriptor-set",
+        descriptor_set_path.to_str().unwrap(),
+        "--message",
+        "shop.Order",
+    ])
+    .write_stdin(protobuf.to_vec());
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(stderr.contains(expected), "{}", stderr);
+    Ok(())
+}
+
+#[rstest]
+#[case(
+    "{id: \"x\"}",
+    "[0].id to Protobuf: \"x\" does not match the field type int64"
+)]
+#[case(
+    "{status: OLD}",
+    "[0].status to Protobuf: 'OLD' is not one of the enum 'shop.Status' values"
+)]
+#[case(
+    "{tags: [a, 1]}",
+    "[0].tags[1] to Protobuf: 1 does not match the field type string"
+)]
+#[case(
+    "{item_count: -1}",
+    "[0].item_count to Protobuf: -1 is out of range for uint32"
+)]
+#[case(
+    "{counts: {a: 1.5}}",
+    "[0].counts.a to Protobuf: 15d-1 does not match the field type int32"
+)]
+#[case(
+    "{extra: 1}",
+    "[0].extra to Protobuf: the message 'shop.Order' has no field 'extra'"
+)]
+/// Calls ion-cli beta to protobuf with values that don't fit the message and checks that the error
+/// names the value's path.
+fn test_to_protobuf_mismatch(#[case] ion: &str, #[case] expected_error: &str) -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let descriptor_set_path = temp_dir.path().join("shop.pb");
+    File::create(&descriptor_set_path)?.write_all(&protobuf_descriptor_set())?;
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args([
+        "beta",
+        "to",
+        "protobuf",
+        "--descriptor-set",
+        descriptor_set_path.to_str().unwrap(),
+        "--message",
+        "shop.Order",
+    ])
+    .write_stdin(ion);
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case::insert(
+    &[],
+    "{id: 1, name: \"O'Brien\", price: 1.50, data: {{AQI=}}, tags: [a, \"b\"], n: null} {}",
+    "INSERT INTO \"orders\" (\"id\", \"name\", \"price\", \"data\", \"tags\", \"n\") \
+     VALUES (1, 'O''Brien', 1.50, X'0102', '[a, \"b\"]', NULL);\n\
+     INSERT INTO \"orders\" DEFAULT VALUES;\n"
+)]
+#[case::create_table(
+    &["--create-table"],
+    "{id: 1, ok: true} {id: 2.5e0, at: 2020-01-02T, extra: null}",
+    "CREATE TABLE \"orders\" (\"id\" REAL, \"ok\" BOOLEAN, \"at\" TEXT, \"extra\" TEXT);\n\
+     INSERT INTO \"orders\" (\"id\", \"ok\") VALUES (1, TRUE);\n\
+     INSERT INTO \"orders\" (\"id\", \"at\", \"extra\") VALUES (2.5, '2020-01-02T00:00:00+00:00', NULL);\n"
+)]
+#[case::timestamps(
+    &[],
+    "{t: 2020T} {t: 2020-05T} {t: 2020-05-06T07:08-07:00} {t: 2020-05-06T07:08:09.5Z}",
+    "INSERT INTO \"orders\" (\"t\") VALUES ('2020-01-01T00:00:00+00:00');\n\
+     INSERT INTO \"orders\" (\"t\") VALUES ('2020-05-01T00:00:00+00:00');\n\
+     INSERT INTO \"orders\" (\"t\") VALUES ('2020-05-06T07:08:00-07:00');\n\
+     INSERT INTO \"orders\" (\"t\") VALUES ('2020-05-06T07:08:09.5+00:00');\n"
+)]
+#[case::postgres(
+    &["--create-table", "--dialect", "postgres", "--nested", "json"],
+    "{id: 1, data: {{AQI=}}, tags: [a]} {id: 20000000000, price: 2.5}",
+    "CREATE TABLE \"orders\" (\"id\" BIGINT, \"data\" BYTEA, \"tags\" JSONB, \"price\" NUMERIC);\n\
+     INSERT INTO \"orders\" (\"id\", \"data\", \"tags\") VALUES (1, '\\x0102', '[\"a\"]');\n\
+     INSERT INTO \"orders\" (\"id\", \"price\") VALUES (20000000000, 2.5);\n"
+)]
+/// Calls ion-cli beta to sql with the given options and checks the statements it writes.
+fn test_to_sql(#[case] args: &[&str], #[case] ion: &str, #[case] expected: &str) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "sql", "--table", "orders"])
+        .args(args)
+        .write_stdin(ion);
+    let command_assert = cmd.assert().success();
+    let actual = String::from_utf8(command_assert.get_output().stdout.clone())?;
+    assert_eq!(expected, actual);
+    Ok(())
+}
+
+#[rstest]
+#[case(&["--table", "t"], "{a: 1} 2", "Cannot convert [1] to SQL: only structs can be written as rows")]
+#[case(&["--table", "t"], "{a: 1, a: 2}", "Cannot convert [0] to SQL: the field 'a' is repeated")]
+#[case(&["--table", "t", "--nested", "error"], "{a: [1]}", "nested values are not allowed")]
+#[case(&[], "{a: 1}", "Converting to SQL requires a table name (see --table)")]
+/// Calls ion-cli beta to sql with values or options it can't convert and checks the error.
+fn test_to_sql_errors(
+    #[case] args: &[&str],
+    #[case] ion: &str,
+    #[case] expected_error: &str,
+) -> Result<()> {
+    let mut cmd = Command::cargo_bin("ion")?;
+    cmd.args(["beta", "to", "sql"]).args(args).write_stdin(ion);
+    let command_assert = cmd.assert().failure();
+    let stderr = String::from_utf8(command_assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.contains(expected_error),
+        "'{}' does not contain '{}'",
+        stderr,
+        expected_error
+    );
+    Ok(())
+}